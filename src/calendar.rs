@@ -1,44 +1,127 @@
+use crate::config::AppConfig;
+use crate::models::{Event as LocalEvent, Priority, Schedule};
+use crate::storage::Storage;
 use anyhow::Result;
-use chrono::{DateTime, Utc, Duration, TimeZone, Datelike};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc, Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
 use chrono_tz::Asia::Tokyo;
-use schedule_ai_agent::GoogleCalendarClient;
-use google_calendar3::api::{Event, Events};
+use schedule_ai_agent::clock::{Clock, SystemClock};
+use schedule_ai_agent::{render_description_template, AuthFlow, EventBuilder, GoogleCalendarClient};
+use std::sync::Arc;
+use google_calendar3::api::{CalendarListEntry, Event, EventAttendee, EventDateTime, EventReminder, EventReminders, Events, FreeBusyResponse};
+use serde::Deserialize;
+
+/// 招待への返答として指定できる値
+pub const RSVP_RESPONSES: [&str; 3] = ["accepted", "declined", "tentative"];
+
+/// イベントの書き込み先となるカレンダー
+#[async_trait]
+pub trait CalendarBackend: Send + Sync {
+    async fn create_primary_event(&self, event: Event) -> Result<Event>;
+    async fn list_primary_events(&self, time_min: DateTime<Utc>, time_max: DateTime<Utc>) -> Result<Vec<Event>>;
+    async fn get_primary_event(&self, event_id: &str) -> Result<Event>;
+    async fn patch_primary_event(&self, event_id: &str, event: Event) -> Result<Event>;
+    async fn delete_primary_event(&self, event_id: &str, send_updates: &str) -> Result<()>;
+    /// 指定したカレンダーの予定が入っている時間帯
+    async fn free_busy(&self, calendar_ids: &[String], time_min: DateTime<Utc>, time_max: DateTime<Utc>) -> Result<FreeBusyResponse>;
+    /// 認証済みアカウントのメールアドレス
+    async fn account_email(&self) -> Result<String>;
+    /// 参照できるカレンダーの一覧
+    async fn list_calendars(&self) -> Result<Vec<CalendarListEntry>>;
+}
+
+#[async_trait]
+impl CalendarBackend for GoogleCalendarClient {
+    async fn create_primary_event(&self, event: Event) -> Result<Event> {
+        GoogleCalendarClient::create_primary_event(self, event).await
+    }
+
+    async fn list_primary_events(&self, time_min: DateTime<Utc>, time_max: DateTime<Utc>) -> Result<Vec<Event>> {
+        self.get_all_events_in_range("primary", time_min, time_max).await
+    }
+
+    async fn get_primary_event(&self, event_id: &str) -> Result<Event> {
+        self.get_primary_event_by_id(event_id).await
+    }
+
+    async fn patch_primary_event(&self, event_id: &str, event: Event) -> Result<Event> {
+        GoogleCalendarClient::patch_primary_event(self, event_id, event).await
+    }
+
+    async fn delete_primary_event(&self, event_id: &str, send_updates: &str) -> Result<()> {
+        GoogleCalendarClient::delete_primary_event(self, event_id, send_updates).await
+    }
+
+    async fn free_busy(&self, calendar_ids: &[String], time_min: DateTime<Utc>, time_max: DateTime<Utc>) -> Result<FreeBusyResponse> {
+        self.query_free_busy(calendar_ids, time_min, time_max).await
+    }
+
+    async fn account_email(&self) -> Result<String> {
+        self.primary_calendar_email().await
+    }
+
+    async fn list_calendars(&self) -> Result<Vec<CalendarListEntry>> {
+        GoogleCalendarClient::list_calendars(self).await
+    }
+}
 
 /// カレンダーサービス
 pub struct CalendarService {
     client: GoogleCalendarClient,
+    clock: Arc<dyn Clock>,
 }
 
 impl CalendarService {
     /// 新しいカレンダーサービスを作成
-    pub async fn new(client_secret_path: &str, token_cache_path: &str) -> Result<Self> {
-        let client = GoogleCalendarClient::new(client_secret_path, token_cache_path).await?;
-        Ok(Self { client })
+    pub async fn new(client_secret_path: &str, token_cache_path: &str, auth_flow: AuthFlow, open_browser: bool) -> Result<Self> {
+        let client = GoogleCalendarClient::new(client_secret_path, token_cache_path, auth_flow, open_browser).await?;
+        Ok(Self {
+            client,
+            clock: Arc::new(SystemClock),
+        })
     }
 
-    /// 今日の予定を取得する
-    pub async fn get_today_events(&self) -> Result<Events> {
-        let now_jst = Utc::now().with_timezone(&Tokyo);
-        let start_of_day = Tokyo.with_ymd_and_hms(now_jst.year(), now_jst.month(), now_jst.day(), 0, 0, 0)
-            .single()
-            .unwrap()
-            .with_timezone(&Utc);
-        let end_of_day = Tokyo.with_ymd_and_hms(now_jst.year(), now_jst.month(), now_jst.day(), 23, 59, 59)
-            .single()
-            .unwrap()
-            .with_timezone(&Utc);
-        
+    /// プライマリカレンダーにアクセスできるか確認する（未認証ならここで認証する）
+    pub async fn verify_access(&self) -> Result<()> {
+        self.client.ping().await
+    }
+
+    /// 現在時刻の取得元を差し替える
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 通知の指定がない予定を作成するときに、開始の`minutes`分前の通知を付ける
+    pub fn with_default_reminders(self, minutes: Option<&[i64]>) -> Self {
+        Self {
+            client: self.client.with_default_reminders(minutes.map(reminder_overrides)),
+            ..self
+        }
+    }
+
+    /// API呼び出し1回あたりの制限時間を設定する（Noneなら制限なし）
+    pub fn with_request_timeout(self, limit: Option<std::time::Duration>) -> Self {
+        Self {
+            client: self.client.with_request_timeout(limit),
+            ..self
+        }
+    }
+
+    /// 今日の予定を取得する（`remaining_only`なら現在時刻以降の予定のみ）
+    pub async fn get_today_events(&self, remaining_only: bool) -> Result<Events> {
+        let (start, end) = today_window(self.clock.now(), remaining_only);
         self.client.get_events_in_range(
             "primary",
-            start_of_day,
-            end_of_day,
+            start,
+            end,
             50
         ).await
     }
 
     /// 今週の予定を取得する
     pub async fn get_week_events(&self) -> Result<Events> {
-        let now_jst = Utc::now().with_timezone(&Tokyo);
+        let now_jst = self.clock.now().with_timezone(&Tokyo);
         let week_later_jst = now_jst + Duration::weeks(1);
         
         self.client.get_events_in_range(
@@ -59,82 +142,147 @@ impl CalendarService {
         self.client.get_events_in_range("primary", start, end, max_results).await
     }
 
-    /// 空き時間を検索する
+    /// 指定した期間の予定をページングしながらすべて取得する
+    pub async fn all_events_in_period(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Event>> {
+        self.client.get_all_events_in_range("primary", start, end).await
+    }
+
+    /// 空き時間を検索する（予定の前後`buffer_minutes`分は空き時間に含めない）
     pub async fn find_free_time(
         &self,
         start: DateTime<Utc>,
         end: DateTime<Utc>,
-        duration_minutes: i64
+        duration_minutes: i64,
+        buffer_minutes: i64,
     ) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
         let events = self.get_events_in_period(start, end, 100).await?;
-        let mut free_slots = Vec::new();
-        
-        if let Some(items) = &events.items {
-            let mut busy_times = Vec::new();
-            
-            // 忙しい時間帯を収集
-            for event in items {
-                if let (Some(start_time), Some(end_time)) = (
-                    event.start.as_ref().and_then(|s| s.date_time.as_ref()),
-                    event.end.as_ref().and_then(|e| e.date_time.as_ref())
-                ) {
-                    busy_times.push((start_time.clone(), end_time.clone()));
-                }
-            }
-            
-            // 忙しい時間帯をソート
-            busy_times.sort_by(|a, b| a.0.cmp(&b.0));
-            
-            // 空き時間を計算
-            let mut current_time = start;
-            let duration = Duration::minutes(duration_minutes);
-            
-            for (busy_start, busy_end) in busy_times {
-                // 現在時刻から忙しい時間帯の開始まで空きがあるかチェック
-                if busy_start > current_time && busy_start - current_time >= duration {
-                    free_slots.push((current_time, busy_start));
-                }
-                current_time = current_time.max(busy_end);
-            }
-            
-            // 最後の忙しい時間帯から終了時刻まで空きがあるかチェック
-            if current_time < end && end - current_time >= duration {
-                free_slots.push((current_time, end));
-            }
-        } else {
-            // イベントがない場合は全体が空き時間
-            free_slots.push((start, end));
-        }
-        
-        Ok(free_slots)
+        let items = events.items.unwrap_or_default();
+        Ok(find_free_slots_with_buffer(
+            &items,
+            start,
+            end,
+            Duration::minutes(duration_minutes),
+            Duration::minutes(buffer_minutes),
+        ))
+    }
+
+    /// 参加者ごとの空き状況を取得する
+    pub async fn attendee_availability(
+        &self,
+        attendees: &[String],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<(String, AttendeeAvailability)> {
+        check_attendee_availability(&self.client, attendees, start, end).await
     }
 
     /// イベントを作成する
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_event(
         &self,
         title: &str,
         description: Option<&str>,
         location: Option<&str>,
         start_time: DateTime<Utc>,
-        end_time: DateTime<Utc>
+        end_time: DateTime<Utc>,
+        priority: Option<&Priority>,
+        attendees: &[String],
     ) -> Result<Event> {
-        use schedule_ai_agent::EventBuilder;
-        
-        let mut builder = EventBuilder::new()
-            .summary(title)
-            .start_time(start_time)
-            .end_time(end_time);
-            
-        if let Some(desc) = description {
-            builder = builder.description(desc);
-        }
-        
-        if let Some(loc) = location {
-            builder = builder.location(loc);
+        create_event_with(&self.client, title, description, location, start_time, end_time, priority, attendees).await
+    }
+
+    /// 今から`days`日先までの予定をキーワードで検索する
+    pub async fn search_events(&self, query: &str, days: i64) -> Result<Vec<Event>> {
+        let now = self.clock.now();
+        search_events(&self.client, query, now, now + Duration::days(days)).await
+    }
+
+    /// キーワードに一致する次回の予定を`days`日先まで探す
+    pub async fn find_next_event(&self, query: &str, days: i64) -> Result<Option<Event>> {
+        find_next_event(&self.client, query, self.clock.now(), Duration::days(days)).await
+    }
+
+    /// 読み込んだ予定を順に作成する
+    pub async fn import_events<F>(
+        &self,
+        rows: Vec<(usize, Result<EventRow>)>,
+        priority: Option<&Priority>,
+        parse_datetime: F,
+    ) -> Vec<(usize, Result<Event>)>
+    where
+        F: Fn(&str) -> Result<DateTime<Utc>>,
+    {
+        import_events(&self.client, rows, priority, parse_datetime).await
+    }
+
+    /// `start`〜`end`と重なる予定を取得する（空なら空き）
+    pub async fn busy_events(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Event>> {
+        busy_events(&self.client, start, end).await
+    }
+
+    /// 指定した期間の予定のうち、自分が主催する予定を取得する
+    pub async fn my_events_in_period(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Event>> {
+        my_events(&self.client, start, end).await
+    }
+
+    /// 招待された予定に返答する
+    pub async fn respond_to_invitation(&self, event_id: &str, response: &str) -> Result<Event> {
+        respond_to_invitation(&self.client, event_id, response).await
+    }
+
+    /// 不在の予定を作成する
+    pub async fn create_out_of_office(
+        &self,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        message: Option<&str>,
+    ) -> Result<Event> {
+        create_out_of_office(&self.client, start_time, end_time, message).await
+    }
+
+    /// Google Calendarに予定を作成し、ローカルのスケジュールにも保存する
+    pub async fn create_event_with_local_mirror(
+        &self,
+        storage: &Storage,
+        schedule: &mut Schedule,
+        local_event: LocalEvent,
+    ) -> Result<Event> {
+        create_event_with_local_mirror(&self.client, storage, schedule, local_event).await
+    }
+
+    /// 既存の予定を別の日時に複製する
+    pub async fn duplicate_event(
+        &self,
+        event_id: &str,
+        start_time: DateTime<Utc>,
+        end_time: Option<DateTime<Utc>>,
+    ) -> Result<Event> {
+        duplicate_event(&self.client, event_id, start_time, end_time).await
+    }
+
+    /// 予定をまとめて削除する（個別の失敗があっても残りの削除を続ける）
+    pub async fn delete_events(&self, events: &[Event], send_updates: Option<&str>) -> Vec<(Event, Result<()>)> {
+        delete_events(&self.client, events, send_updates).await
+    }
+
+    /// 今日の残りの予定をまとめて`delta`だけずらす
+    ///
+    /// 現在時刻より後に開始する予定のみが対象で、終日予定は変更しない。
+    /// 戻り値は元のイベントと更新結果の組で、個別の失敗があっても処理を続行する。
+    pub async fn shift_remaining_today_events(&self, delta: Duration) -> Result<Vec<(Event, Result<Event>)>> {
+        let events = self.get_today_events(false).await?;
+        let items = events.items.unwrap_or_default();
+
+        let mut results = Vec::new();
+        for (original, patch) in build_shift_patches(&items, self.clock.now(), delta) {
+            let result = match &original.id {
+                Some(event_id) => self.client.patch_primary_event(event_id, patch).await,
+                None => Err(anyhow::anyhow!("イベントIDが見つかりません")),
+            };
+            results.push((original, result));
         }
-        
-        let event = builder.build();
-        self.client.create_primary_event(event).await
+
+        Ok(results)
     }
 
     /// カレンダー情報をコンソールに表示する
@@ -143,7 +291,7 @@ impl CalendarService {
         
         // 今日の予定
         println!("\n📅 今日の予定:");
-        let today_events = self.get_today_events().await?;
+        let today_events = self.get_today_events(false).await?;
         self.client.display_events(&today_events);
         
         // 今週の予定数
@@ -154,3 +302,2410 @@ impl CalendarService {
         Ok(())
     }
 }
+
+/// `now`より後に開始する予定について、開始・終了を`delta`だけずらすパッチを作成する
+///
+/// 終日予定（`date`のみの予定）は対象外。戻り値は元のイベントとパッチの組。
+fn build_shift_patches(items: &[Event], now: DateTime<Utc>, delta: Duration) -> Vec<(Event, Event)> {
+    items
+        .iter()
+        .filter_map(|event| {
+            let start = event.start.as_ref()?.date_time?;
+            let end = event.end.as_ref()?.date_time?;
+            if start <= now {
+                return None;
+            }
+
+            let patch = Event {
+                start: Some(EventDateTime {
+                    date_time: Some(start + delta),
+                    time_zone: Some("Asia/Tokyo".to_string()),
+                    ..Default::default()
+                }),
+                end: Some(EventDateTime {
+                    date_time: Some(end + delta),
+                    time_zone: Some("Asia/Tokyo".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            Some((event.clone(), patch))
+        })
+        .collect()
+}
+
+/// 新しいイベントを指定のバックエンドに作成する
+///
+/// 優先度はGoogle Calendarの色として反映する（Mediumは既定色のまま）。
+#[allow(clippy::too_many_arguments)]
+pub async fn create_event_with<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    title: &str,
+    description: Option<&str>,
+    location: Option<&str>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    priority: Option<&Priority>,
+    attendees: &[String],
+) -> Result<Event> {
+    let mut builder = EventBuilder::new()
+        .summary(title)
+        .start_time(start_time)
+        .end_time(end_time)
+        .attendees(attendees);
+
+    if let Some(desc) = description {
+        builder = builder.description(&render_description_template(desc, title, start_time, end_time));
+    }
+
+    if let Some(loc) = location {
+        builder = builder.location(loc);
+    }
+
+    if let Some(color_id) = priority.and_then(priority_color_id) {
+        builder = builder.color_id(color_id);
+    }
+
+    backend.create_primary_event(builder.build()).await
+}
+
+/// 不在（outOfOffice）の予定を作成する
+///
+/// 期間中に届いた招待は`message`を添えて自動的に辞退される。
+pub async fn create_out_of_office<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    message: Option<&str>,
+) -> Result<Event> {
+    if end_time <= start_time {
+        return Err(anyhow::anyhow!("終了時刻は開始時刻より後である必要があります"));
+    }
+    let event = EventBuilder::new()
+        .summary("不在")
+        .start_time(start_time)
+        .end_time(end_time)
+        .out_of_office(message)
+        .build();
+    backend.create_primary_event(event).await
+}
+
+/// Google Calendarに予定を作成し、成功した場合だけローカルのスケジュールにも保存する
+///
+/// ローカルの予定には作成されたイベントIDを記録する。Google側で失敗した場合はローカルを変更しない。
+pub async fn create_event_with_local_mirror<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    storage: &Storage,
+    schedule: &mut Schedule,
+    mut local_event: LocalEvent,
+) -> Result<Event> {
+    let created = create_event_with(
+        backend,
+        &local_event.title,
+        local_event.description.as_deref(),
+        local_event.location.as_deref(),
+        local_event.start_time,
+        local_event.end_time,
+        Some(&local_event.priority),
+        &[],
+    )
+    .await?;
+
+    local_event.google_event_id = created.id.clone();
+    let mut mirrored = schedule.clone();
+    mirrored.add_event(local_event);
+    storage.save_schedule(&mirrored).map_err(|e| {
+        anyhow::anyhow!("Google Calendarには作成しましたが、ローカルへの保存に失敗しました: {}", e)
+    })?;
+    *schedule = mirrored;
+
+    Ok(created)
+}
+
+/// 今日（日本時間）の予定を取得する範囲
+///
+/// `remaining_only`なら開始を現在時刻にし、終わった予定を含めない
+/// （現在時刻が範囲の開始になるため、進行中の予定は含まれる）。
+pub fn today_window(now: DateTime<Utc>, remaining_only: bool) -> (DateTime<Utc>, DateTime<Utc>) {
+    let (start_of_day, end_of_day) = day_window(now.with_timezone(&Tokyo).date_naive());
+    if remaining_only {
+        (now.max(start_of_day), end_of_day)
+    } else {
+        (start_of_day, end_of_day)
+    }
+}
+
+/// 空き時間を探す範囲から現在時刻より前を除く（範囲がすべて過去なら`None`）
+pub fn upcoming_window(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = start.max(now);
+    (start < end).then_some((start, end))
+}
+
+/// 指定日（日本時間）の00:00〜23:59:59をUTCで返す
+pub fn day_window(date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let at = |hour, minute, second| {
+        Tokyo
+            .from_local_datetime(&date.and_hms_opt(hour, minute, second).unwrap())
+            .single()
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+    (at(0, 0, 0), at(23, 59, 59))
+}
+
+/// 期間内で予定の入っていない、`duration`以上の時間帯を求める（終日予定は無視する）
+pub fn find_free_slots(
+    items: &[Event],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    find_free_slots_with_buffer(items, start, end, duration, Duration::zero())
+}
+
+/// 各予定の前後`buffer`を忙しい時間とみなして、`duration`以上の空き時間を求める
+///
+/// 予定に挟まれた空き時間は両端から`buffer`ずつ削られ、残りが`duration`に満たなければ候補にしない。
+pub fn find_free_slots_with_buffer(
+    items: &[Event],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration: Duration,
+    buffer: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut free_slots = Vec::new();
+
+    // 忙しい時間帯を収集
+    let mut busy_times: Vec<(DateTime<Utc>, DateTime<Utc>)> = items
+        .iter()
+        .filter_map(|event| {
+            Some((
+                event.start.as_ref()?.date_time? - buffer,
+                event.end.as_ref()?.date_time? + buffer,
+            ))
+        })
+        .collect();
+
+    // 忙しい時間帯をソート
+    busy_times.sort_by_key(|busy| busy.0);
+
+    // 空き時間を計算
+    let mut current_time = start;
+    for (busy_start, busy_end) in busy_times {
+        // 現在時刻から忙しい時間帯の開始まで空きがあるかチェック
+        if busy_start > current_time && busy_start - current_time >= duration {
+            free_slots.push((current_time, busy_start));
+        }
+        current_time = current_time.max(busy_end);
+    }
+
+    // 最後の忙しい時間帯から終了時刻まで空きがあるかチェック
+    if current_time < end && end - current_time >= duration {
+        free_slots.push((current_time, end));
+    }
+
+    free_slots
+}
+
+/// `start`〜`end`と時間が重なる予定（終日予定と「予定なし」扱いの予定は除く）
+pub fn conflicting_events(items: &[Event], start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Event> {
+    items
+        .iter()
+        .filter(|event| event.transparency.as_deref() != Some("transparent"))
+        .filter(|event| {
+            match (
+                event.start.as_ref().and_then(|s| s.date_time),
+                event.end.as_ref().and_then(|e| e.date_time),
+            ) {
+                (Some(busy_start), Some(busy_end)) => busy_start < end && start < busy_end,
+                _ => false,
+            }
+        })
+        .collect()
+}
+
+/// 希望の時間帯に近い順に、同じ長さの空き時間を最大`count`件求める
+///
+/// 空き時間ごとに希望の開始時刻へ最も近い位置を候補とし、`not_before`より前の候補は除く。
+pub fn nearest_free_slots(
+    items: &[Event],
+    window: (DateTime<Utc>, DateTime<Utc>),
+    requested: (DateTime<Utc>, DateTime<Utc>),
+    not_before: DateTime<Utc>,
+    count: usize,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let duration = requested.1 - requested.0;
+    let busy: Vec<Event> = items
+        .iter()
+        .filter(|event| event.transparency.as_deref() != Some("transparent"))
+        .cloned()
+        .collect();
+    let window_start = window.0.max(not_before);
+
+    let mut candidates: Vec<(DateTime<Utc>, DateTime<Utc>)> = find_free_slots(&busy, window_start, window.1, duration)
+        .into_iter()
+        .map(|(free_start, free_end)| {
+            let start = requested.0.clamp(free_start, free_end - duration);
+            (start, start + duration)
+        })
+        .collect();
+    candidates.sort_by_key(|(start, _)| (*start - requested.0).num_seconds().abs());
+    candidates.truncate(count);
+    candidates
+}
+
+/// 予定を同じ長さのまま、`not_before`以降で最初に入る勤務時間内の空き時間へ移す
+///
+/// 元の開始時刻から`search_days`日先までを探し、移動後の予定を返す。
+/// 今の時間帯のままで空いている場合は更新せずにそのまま返す。
+/// 空き時間が見つからない場合は`None`を返し、予定は変更しない。
+pub async fn reschedule_to_next_free_slot(
+    backend: &dyn CalendarBackend,
+    event: &Event,
+    not_before: DateTime<Utc>,
+    search_days: i64,
+    working_hours: (NaiveTime, NaiveTime),
+) -> Result<Option<Event>> {
+    let (Some(start), Some(end)) = (
+        event.start.as_ref().and_then(|start| start.date_time),
+        event.end.as_ref().and_then(|end| end.date_time),
+    ) else {
+        anyhow::bail!("終日の予定は空き時間へ移動できません");
+    };
+    let event_id = event
+        .id
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("イベントIDが見つかりません"))?;
+
+    let window_start = start.max(not_before);
+    let window_end = window_start + Duration::days(search_days);
+    // 移動する予定自体と「予定なし」扱いの予定は空き時間を塞がない
+    let busy: Vec<Event> = backend
+        .list_primary_events(window_start, window_end)
+        .await?
+        .into_iter()
+        .filter(|item| item.id.as_deref() != Some(event_id))
+        .filter(|item| item.transparency.as_deref() != Some("transparent"))
+        .collect();
+    let Some(new_start) = working_windows(window_start, window_end, working_hours)
+        .into_iter()
+        .find_map(|(from, to)| find_free_slots(&busy, from, to, end - start).into_iter().next())
+        .map(|(free_start, _)| free_start)
+    else {
+        return Ok(None);
+    };
+    if new_start == start {
+        return Ok(Some(event.clone()));
+    }
+
+    let patch = Event {
+        start: Some(EventDateTime {
+            date_time: Some(new_start),
+            time_zone: Some("Asia/Tokyo".to_string()),
+            ..Default::default()
+        }),
+        end: Some(EventDateTime {
+            date_time: Some(new_start + (end - start)),
+            time_zone: Some("Asia/Tokyo".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    backend.patch_primary_event(event_id, patch).await.map(Some)
+}
+
+/// 参加者の空き状況
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttendeeAvailability {
+    Free,
+    /// 重なっている予定の時間帯
+    Busy(Vec<(DateTime<Utc>, DateTime<Utc>)>),
+    /// カレンダーが共有されていないなどで確認できない
+    Unknown,
+}
+
+/// 参加者ごとに`start`〜`end`の空き状況を調べる
+///
+/// 問い合わせ自体に失敗した場合は、全員を確認できなかったものとして扱う。
+pub async fn check_attendee_availability(
+    backend: &dyn CalendarBackend,
+    attendees: &[String],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<(String, AttendeeAvailability)> {
+    let calendars = match backend.free_busy(attendees, start, end).await {
+        Ok(response) => response.calendars.unwrap_or_default(),
+        Err(_) => Default::default(),
+    };
+
+    attendees
+        .iter()
+        .map(|attendee| {
+            let availability = match calendars.get(attendee) {
+                Some(calendar) if calendar.errors.as_ref().is_none_or(|errors| errors.is_empty()) => {
+                    let busy: Vec<(DateTime<Utc>, DateTime<Utc>)> = calendar
+                        .busy
+                        .as_deref()
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|period| Some((period.start?, period.end?)))
+                        .filter(|(busy_start, busy_end)| *busy_start < end && start < *busy_end)
+                        .collect();
+                    if busy.is_empty() {
+                        AttendeeAvailability::Free
+                    } else {
+                        AttendeeAvailability::Busy(busy)
+                    }
+                }
+                _ => AttendeeAvailability::Unknown,
+            };
+            (attendee.clone(), availability)
+        })
+        .collect()
+}
+
+/// 参加者の空き状況を「・a@example.com: 予定あり（10:00-11:00）」の形式で表示する
+pub fn format_attendee_availability(
+    attendee: &str,
+    availability: &AttendeeAvailability,
+    app_config: &AppConfig,
+) -> String {
+    let state = match availability {
+        AttendeeAvailability::Free => "空き".to_string(),
+        AttendeeAvailability::Busy(periods) => {
+            let times: Vec<String> = periods
+                .iter()
+                .map(|(start, end)| {
+                    format!(
+                        "{}-{}",
+                        start.with_timezone(&Tokyo).format(app_config.time_format()),
+                        end.with_timezone(&Tokyo).format(app_config.time_format())
+                    )
+                })
+                .collect();
+            format!("予定あり（{}）", times.join(", "))
+        }
+        AttendeeAvailability::Unknown => "不明".to_string(),
+    };
+    format!("・{}: {}", attendee, state)
+}
+
+/// 空いていない（予定あり・不明の）参加者だけを表示用の行にする
+pub fn unavailable_attendee_lines(
+    results: &[(String, AttendeeAvailability)],
+    app_config: &AppConfig,
+) -> Vec<String> {
+    results
+        .iter()
+        .filter(|(_, availability)| *availability != AttendeeAvailability::Free)
+        .map(|(attendee, availability)| format_attendee_availability(attendee, availability, app_config))
+        .collect()
+}
+
+/// `exclude_id`の予定を除いて、`start`〜`end`と時間が重なる予定
+///
+/// 予定を移動するとき、移動前の自分自身と重なっているとみなさないようにする。
+pub fn conflicting_events_excluding<'a>(
+    items: &'a [Event],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    exclude_id: &str,
+) -> Vec<&'a Event> {
+    conflicting_events(items, start, end)
+        .into_iter()
+        .filter(|event| event.id.as_deref() != Some(exclude_id))
+        .collect()
+}
+
+/// 空き時間の並べ替え方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotPreference {
+    /// 午前にかかる時間帯を優先
+    Morning,
+    /// 午後にかかる時間帯を優先
+    Afternoon,
+    /// 長い時間帯を優先
+    Longest,
+}
+
+impl SlotPreference {
+    /// "morning"・"afternoon"・"longest"を並べ替え方として解釈する
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "morning" => Some(SlotPreference::Morning),
+            "afternoon" => Some(SlotPreference::Afternoon),
+            "longest" => Some(SlotPreference::Longest),
+            _ => None,
+        }
+    }
+}
+
+/// 空き時間を指定の優先順に並べ替える（同順位の中では元の時系列順を保つ）
+///
+/// 午前・午後の判定は日本時間の正午を境にする。
+pub fn sort_free_slots(slots: &mut [(DateTime<Utc>, DateTime<Utc>)], preference: SlotPreference) {
+    let noon = chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+    match preference {
+        SlotPreference::Morning => {
+            slots.sort_by_key(|(start, _)| start.with_timezone(&Tokyo).time() >= noon)
+        }
+        SlotPreference::Afternoon => slots.sort_by_key(|(start, end)| {
+            let (start, end) = (start.with_timezone(&Tokyo), end.with_timezone(&Tokyo));
+            end.date_naive() == start.date_naive() && end.time() <= noon
+        }),
+        SlotPreference::Longest => slots.sort_by_key(|(start, end)| std::cmp::Reverse(*end - *start)),
+    }
+}
+
+/// 一括作成で読み込む1件分の予定
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EventRow {
+    pub title: String,
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+}
+
+/// CSV（1行目はヘッダー）またはJSON Lines形式の予定を1行ずつ解析する
+///
+/// 戻り値は行番号と解析結果の組で、空行は読み飛ばす。
+pub fn parse_event_rows(content: &str, csv: bool) -> Vec<(usize, Result<EventRow>)> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    if !csv {
+        return lines
+            .map(|(number, line)| {
+                let row = serde_json::from_str::<EventRow>(line)
+                    .map_err(|e| anyhow::anyhow!("JSONの解析に失敗しました: {}", e));
+                (number, row)
+            })
+            .collect();
+    }
+
+    let Some((header_number, header)) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: Vec<String> = split_csv_line(header).into_iter().map(|c| c.to_lowercase()).collect();
+    let index_of = |name: &str| columns.iter().position(|c| c == name);
+    let (Some(title), Some(start), Some(end)) = (index_of("title"), index_of("start"), index_of("end")) else {
+        return vec![(
+            header_number,
+            Err(anyhow::anyhow!("ヘッダーに title・start・end の列が必要です")),
+        )];
+    };
+    let description = index_of("description");
+    let location = index_of("location");
+
+    lines
+        .map(|(number, line)| {
+            let fields = split_csv_line(line);
+            let field = |index: usize| fields.get(index).filter(|v| !v.is_empty()).cloned();
+            let row = match (field(title), field(start), field(end)) {
+                (Some(title), Some(start), Some(end)) => Ok(EventRow {
+                    title,
+                    start,
+                    end,
+                    description: description.and_then(field),
+                    location: location.and_then(field),
+                }),
+                _ => Err(anyhow::anyhow!("title・start・end のいずれかが空です")),
+            };
+            (number, row)
+        })
+        .collect()
+}
+
+/// CSVの1行を列に分割する（ダブルクォートで囲んだ列内のカンマと""に対応）
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current.trim().to_string());
+    fields
+}
+
+/// 解析済みの予定を1件ずつ作成し、行ごとの結果を返す（失敗した行があっても続行する）
+///
+/// 作成する予定にはすべて`priority`の優先度を付ける（app.default_priority）。
+pub async fn import_events<B, F>(
+    backend: &B,
+    rows: Vec<(usize, Result<EventRow>)>,
+    priority: Option<&Priority>,
+    parse_datetime: F,
+) -> Vec<(usize, Result<Event>)>
+where
+    B: CalendarBackend + ?Sized,
+    F: Fn(&str) -> Result<DateTime<Utc>>,
+{
+    let mut results = Vec::new();
+    for (number, row) in rows {
+        let result = match row {
+            Ok(row) => create_event_from_row(backend, &row, priority, &parse_datetime).await,
+            Err(e) => Err(e),
+        };
+        results.push((number, result));
+    }
+    results
+}
+
+async fn create_event_from_row<B, F>(
+    backend: &B,
+    row: &EventRow,
+    priority: Option<&Priority>,
+    parse_datetime: &F,
+) -> Result<Event>
+where
+    B: CalendarBackend + ?Sized,
+    F: Fn(&str) -> Result<DateTime<Utc>>,
+{
+    let start_time = parse_datetime(&row.start)?;
+    let end_time = parse_datetime(&row.end)?;
+    if end_time <= start_time {
+        return Err(anyhow::anyhow!("終了時刻は開始時刻より後にしてください"));
+    }
+
+    create_event_with(
+        backend,
+        &row.title,
+        row.description.as_deref(),
+        row.location.as_deref(),
+        start_time,
+        end_time,
+        priority,
+        &[],
+    )
+    .await
+}
+
+/// 招待された予定への返答（accepted・declined・tentative）を登録する
+pub async fn respond_to_invitation<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    event_id: &str,
+    response: &str,
+) -> Result<Event> {
+    if !RSVP_RESPONSES.contains(&response) {
+        return Err(anyhow::anyhow!(
+            "返答は {} のいずれかを指定してください: {}",
+            RSVP_RESPONSES.join("・"),
+            response
+        ));
+    }
+
+    let event = backend.get_primary_event(event_id).await?;
+    let email = backend.account_email().await?;
+    let patch = build_rsvp_patch(&event, &email, response)?;
+    backend.patch_primary_event(event_id, patch).await
+}
+
+/// `email`のアカウントが主催する予定か（主催者の情報がなければ作成者で判定する）
+pub fn is_organized_by(event: &Event, email: &str) -> bool {
+    let is_me = |is_self: Option<bool>, address: Option<&str>| {
+        is_self == Some(true) || address.is_some_and(|address| address.eq_ignore_ascii_case(email))
+    };
+    match (&event.organizer, &event.creator) {
+        (Some(organizer), _) => is_me(organizer.self_, organizer.email.as_deref()),
+        (None, Some(creator)) => is_me(creator.self_, creator.email.as_deref()),
+        (None, None) => false,
+    }
+}
+
+/// `start`〜`end`の予定のうち、認証済みのアカウントが主催する予定だけを返す
+pub async fn my_events<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Event>> {
+    let email = backend.account_email().await?;
+    let events = backend.list_primary_events(start, end).await?;
+    Ok(events.into_iter().filter(|event| is_organized_by(event, &email)).collect())
+}
+
+/// 自分の参加者情報の返答状況だけを書き換えた部分更新用のイベントを作る
+///
+/// 参加者リストは部分更新でも丸ごと置き換わるため、他の参加者もそのまま含める。
+fn build_rsvp_patch(event: &Event, email: &str, response: &str) -> Result<Event> {
+    let mut attendees: Vec<EventAttendee> = event.attendees.clone().unwrap_or_default();
+    let me = attendees
+        .iter_mut()
+        .find(|attendee| {
+            attendee.self_ == Some(true)
+                || attendee
+                    .email
+                    .as_deref()
+                    .is_some_and(|address| address.eq_ignore_ascii_case(email))
+        })
+        .ok_or_else(|| anyhow::anyhow!("この予定の参加者ではないため返答できません"))?;
+    me.response_status = Some(response.to_string());
+
+    Ok(Event {
+        attendees: Some(attendees),
+        ..Default::default()
+    })
+}
+
+/// 削除時の参加者への通知方法として指定できる値
+pub const SEND_UPDATES_OPTIONS: [&str; 3] = ["all", "externalOnly", "none"];
+
+/// 予定を削除し、必要に応じて参加者にキャンセル通知を送る
+///
+/// `configured`が未指定の場合、参加者がいれば"all"、いなければ"none"で削除する。
+pub async fn delete_event_with_notice<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    event: &Event,
+    configured: Option<&str>,
+) -> Result<()> {
+    let event_id = event
+        .id
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("イベントIDが見つかりません"))?;
+    backend
+        .delete_primary_event(event_id, send_updates_for(event, configured))
+        .await
+}
+
+/// 予定を順に削除し、予定ごとの結果を返す
+///
+/// 途中で失敗しても残りの予定の削除を続ける。通知方法は`delete_event_with_notice`と同じく決める。
+pub async fn delete_events<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    events: &[Event],
+    configured: Option<&str>,
+) -> Vec<(Event, Result<()>)> {
+    let mut results = Vec::new();
+    for event in events {
+        let result = delete_event_with_notice(backend, event, configured).await;
+        results.push((event.clone(), result));
+    }
+    results
+}
+
+/// 重複している予定の組
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// 残す予定（最も早く作成されたもの）
+    pub keep: Event,
+    /// 削除候補の予定
+    pub duplicates: Vec<Event>,
+}
+
+/// タイトル（空白・大文字小文字の違いは無視）と開始・終了日時が同じ予定をまとめる
+///
+/// 各組では作成日時が最も早い予定を残し、それ以外を削除候補とする。組は開始日時の順に並べる。
+pub fn find_duplicate_events(events: &[Event]) -> Vec<DuplicateGroup> {
+    let normalize = |title: &str| title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+
+    let key = |event: &Event| {
+        Some((
+            normalize(event.summary.as_deref().unwrap_or_default()),
+            event_start(event)?,
+            event_end(event)?,
+        ))
+    };
+
+    let mut groups: Vec<Vec<Event>> = Vec::new();
+    for event in events.iter().filter(|event| key(event).is_some()) {
+        match groups.iter_mut().find(|members| key(&members[0]) == key(event)) {
+            Some(members) => members.push(event.clone()),
+            None => groups.push(vec![event.clone()]),
+        }
+    }
+
+    let mut duplicates: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            // 作成日時が不明な予定は後ろに回す
+            members.sort_by_key(|event| (event.created.is_none(), event.created));
+            let keep = members.remove(0);
+            DuplicateGroup { keep, duplicates: members }
+        })
+        .collect();
+    duplicates.sort_by_key(|group| event_start(&group.keep));
+    duplicates
+}
+
+/// 削除時に指定するsendUpdatesの値を決める
+fn send_updates_for<'a>(event: &Event, configured: Option<&'a str>) -> &'a str {
+    configured.unwrap_or_else(|| {
+        if event.attendees.as_ref().is_some_and(|a| !a.is_empty()) {
+            "all"
+        } else {
+            "none"
+        }
+    })
+}
+
+/// 既存の予定を取得し、内容をコピーした新しい予定を作成する
+///
+/// `end_time`を省略した場合は元の予定の長さを保つ。
+pub async fn duplicate_event<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    event_id: &str,
+    start_time: DateTime<Utc>,
+    end_time: Option<DateTime<Utc>>,
+) -> Result<Event> {
+    let original = backend.get_primary_event(event_id).await?;
+    let copy = build_duplicate(&original, start_time, end_time)?;
+    backend.create_primary_event(copy).await
+}
+
+/// タイトル・説明・場所・参加者を引き継いだ複製用のイベントを作る
+///
+/// 参加者の返答状況は引き継がず、新しい招待として扱う。
+fn build_duplicate(original: &Event, start_time: DateTime<Utc>, end_time: Option<DateTime<Utc>>) -> Result<Event> {
+    let end_time = match end_time {
+        Some(end) => end,
+        None => {
+            let (start, end) = event_start(original)
+                .zip(event_end(original))
+                .ok_or_else(|| anyhow::anyhow!("元の予定の時刻が不明なため終了時刻を指定してください"))?;
+            start_time + (end - start)
+        }
+    };
+    if end_time <= start_time {
+        return Err(anyhow::anyhow!("終了時刻は開始時刻より後である必要があります"));
+    }
+
+    let attendees = original.attendees.as_ref().map(|attendees| {
+        attendees
+            .iter()
+            .map(|attendee| EventAttendee {
+                email: attendee.email.clone(),
+                display_name: attendee.display_name.clone(),
+                optional: attendee.optional,
+                ..Default::default()
+            })
+            .collect()
+    });
+
+    Ok(Event {
+        summary: original.summary.clone(),
+        description: original.description.clone(),
+        location: original.location.clone(),
+        color_id: original.color_id.clone(),
+        attendees,
+        start: Some(EventDateTime { date_time: Some(start_time), ..Default::default() }),
+        end: Some(EventDateTime { date_time: Some(end_time), ..Default::default() }),
+        ..Default::default()
+    })
+}
+
+/// 既存の参加者リストに参加者を追加・削除した新しいリストを返す
+///
+/// 追加はメールアドレスで指定し、既に含まれている場合は何もしない。
+/// 削除はメールアドレスまたは表示名（「さん」付きでも可）で指定する。
+pub fn apply_attendee_changes(
+    attendees: &[EventAttendee],
+    add: &[String],
+    remove: &[String],
+) -> Result<Vec<EventAttendee>> {
+    let mut updated: Vec<EventAttendee> = attendees
+        .iter()
+        .filter(|attendee| !remove.iter().any(|key| attendee_matches(attendee, key)))
+        .cloned()
+        .collect();
+
+    for email in add {
+        let email = email.trim();
+        if !email.contains('@') {
+            return Err(anyhow::anyhow!("参加者を追加するにはメールアドレスが必要です: {}", email));
+        }
+        if !updated.iter().any(|attendee| attendee_matches(attendee, email)) {
+            updated.push(EventAttendee {
+                email: Some(email.to_string()),
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(updated)
+}
+
+/// 既存の説明の末尾に改行を挟んで文章を追記する
+pub fn append_description(existing: Option<&str>, addition: &str) -> String {
+    match existing.map(str::trim_end).filter(|existing| !existing.is_empty()) {
+        Some(existing) => format!("{}\n{}", existing, addition.trim()),
+        None => addition.trim().to_string(),
+    }
+}
+
+/// 参加者がメールアドレスまたは表示名で指定されたものと一致するか
+fn attendee_matches(attendee: &EventAttendee, key: &str) -> bool {
+    let key = key.trim();
+    let name = key.strip_suffix("さん").unwrap_or(key);
+    attendee.email.as_deref().is_some_and(|email| email.eq_ignore_ascii_case(key))
+        || attendee
+            .display_name
+            .as_deref()
+            .is_some_and(|display_name| !name.is_empty() && display_name.contains(name))
+}
+
+/// 取得に成功した予定をキャッシュし、失敗した場合は同じ期間のキャッシュを返す
+///
+/// 戻り値の`bool`はキャッシュから返したかどうか。キャッシュもない場合は取得時のエラーを返す。
+/// `storage`がNoneの場合はキャッシュを使わない。
+pub async fn fetch_with_cache(
+    storage: Option<&Storage>,
+    key: &str,
+    fetch: impl std::future::Future<Output = Result<Events>>,
+) -> Result<(Events, bool)> {
+    let Some(storage) = storage else {
+        return fetch.await.map(|events| (events, false));
+    };
+
+    match fetch.await {
+        Ok(events) => {
+            // キャッシュの保存に失敗しても表示は続ける
+            let _ = storage.save_event_cache(key, &events);
+            Ok((events, false))
+        }
+        Err(e) => match storage.load_event_cache(key) {
+            Ok(Some(cached)) => Ok((cached, true)),
+            _ => Err(e),
+        },
+    }
+}
+
+/// キーワードに一致する予定のうち、`now`以降で最も早く始まるものを返す
+pub async fn find_next_event<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    query: &str,
+    now: DateTime<Utc>,
+    horizon: Duration,
+) -> Result<Option<Event>> {
+    let matches = search_events(backend, query, now, now + horizon).await?;
+    Ok(matches
+        .into_iter()
+        .filter_map(|event| event_start(&event).map(|start| (start, event)))
+        .filter(|(start, _)| *start >= now)
+        .min_by_key(|(start, _)| *start)
+        .map(|(_, event)| event))
+}
+
+/// `start`〜`end`に予定が入っているか調べ、重なる予定を開始順に返す（空なら空き）
+pub async fn busy_events<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Event>> {
+    let events = backend.list_primary_events(start, end).await?;
+    let mut busy: Vec<Event> = conflicting_events(&events, start, end).into_iter().cloned().collect();
+    busy.sort_by_key(event_start);
+    Ok(busy)
+}
+
+/// 予定の開始日時（終日予定は日本時間の0時とみなす）
+pub fn event_start(event: &Event) -> Option<DateTime<Utc>> {
+    let start = event.start.as_ref()?;
+    if let Some(date_time) = start.date_time {
+        return Some(date_time);
+    }
+    let date = start.date?;
+    Tokyo
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// 予定の終了日時（終日予定は日本時間の0時とみなす）
+fn event_end(event: &Event) -> Option<DateTime<Utc>> {
+    let end = event.end.as_ref()?;
+    if let Some(date_time) = end.date_time {
+        return Some(date_time);
+    }
+    let date = end.date?;
+    Tokyo
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// カレンダー名（calendarListの表示名）からカレンダーIDを求める
+///
+/// 名前が一致するものを優先し、なければ名前を含むものを探す（末尾の「カレンダー」は省略できる）。
+/// 見つからない場合や複数に一致する場合は、候補を示すエラーを返す。
+pub async fn resolve_calendar_id<B: CalendarBackend + ?Sized>(backend: &B, name: &str) -> Result<String> {
+    let calendars = backend.list_calendars().await?;
+    let key = name.trim();
+    let short = key
+        .strip_suffix("カレンダー")
+        .map(str::trim)
+        .filter(|short| !short.is_empty())
+        .unwrap_or(key);
+    let names = |calendar: &CalendarListEntry| {
+        [calendar.summary_override.as_deref(), calendar.summary.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(|name| name.to_lowercase())
+            .collect::<Vec<_>>()
+    };
+
+    let exact: Vec<&CalendarListEntry> = calendars
+        .iter()
+        .filter(|calendar| {
+            names(calendar)
+                .iter()
+                .any(|name| *name == key.to_lowercase() || *name == short.to_lowercase())
+        })
+        .collect();
+    let candidates = if exact.is_empty() {
+        calendars
+            .iter()
+            .filter(|calendar| names(calendar).iter().any(|name| name.contains(&short.to_lowercase())))
+            .collect()
+    } else {
+        exact
+    };
+
+    let labels = |calendars: &[&CalendarListEntry]| {
+        calendars
+            .iter()
+            .map(|calendar| format!("「{}」", calendar_label(calendar)))
+            .collect::<Vec<_>>()
+            .join("、")
+    };
+    match candidates.as_slice() {
+        [calendar] => calendar
+            .id
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("カレンダー「{}」のIDが取得できません", calendar_label(calendar))),
+        [] => anyhow::bail!(
+            "カレンダー「{}」が見つかりません（利用できるカレンダー: {}）",
+            key,
+            labels(&calendars.iter().collect::<Vec<_>>())
+        ),
+        _ => anyhow::bail!(
+            "「{}」に一致するカレンダーが複数あります: {}。名前を正確に指定してください",
+            key,
+            labels(&candidates)
+        ),
+    }
+}
+
+/// カレンダーの表示名（自分で付けた名前があればそちらを使う）
+fn calendar_label(calendar: &CalendarListEntry) -> &str {
+    calendar
+        .summary_override
+        .as_deref()
+        .or(calendar.summary.as_deref())
+        .unwrap_or("(名前なし)")
+}
+
+/// 期間内の予定を取得し、タイトル・説明・場所にキーワードを含むものだけを返す
+pub async fn search_events<B: CalendarBackend + ?Sized>(
+    backend: &B,
+    query: &str,
+    time_min: DateTime<Utc>,
+    time_max: DateTime<Utc>,
+) -> Result<Vec<Event>> {
+    let events = backend.list_primary_events(time_min, time_max).await?;
+    Ok(filter_events_by_query(events, query))
+}
+
+/// タイトル・説明・場所にキーワードを含む予定を抽出する（大文字小文字は区別しない）
+fn filter_events_by_query(events: Vec<Event>, query: &str) -> Vec<Event> {
+    events
+        .into_iter()
+        .filter(|event| google_event_query_match(event, query).is_some())
+        .collect()
+}
+
+/// 検索語に一致した項目と位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryMatch<'a> {
+    /// 一致した項目名（タイトル・説明・場所）
+    pub field: &'static str,
+    pub text: &'a str,
+    /// `text`内で一致した範囲（バイト位置）
+    pub range: std::ops::Range<usize>,
+}
+
+impl QueryMatch<'_> {
+    /// 一致箇所の前後`radius`文字を切り出し、(前, 一致部分, 後)を返す
+    pub fn context(&self, radius: usize) -> (String, &str, String) {
+        let before: Vec<char> = self.text[..self.range.start].chars().collect();
+        let before = if before.len() > radius {
+            format!("…{}", before[before.len() - radius..].iter().collect::<String>())
+        } else {
+            before.into_iter().collect()
+        };
+        let after = &self.text[self.range.end..];
+        let after = if after.chars().count() > radius {
+            format!("{}…", after.chars().take(radius).collect::<String>())
+        } else {
+            after.to_string()
+        };
+        (before, &self.text[self.range.clone()], after)
+    }
+}
+
+/// タイトル・説明・場所の順に検索語（大文字小文字を区別しない）を探し、最初の一致を返す
+pub fn find_query_match<'a>(
+    fields: [(&'static str, Option<&'a str>); 3],
+    query: &str,
+) -> Option<QueryMatch<'a>> {
+    fields.into_iter().find_map(|(field, text)| {
+        let text = text?;
+        let range = find_ignore_case(text, query)?;
+        Some(QueryMatch { field, text, range })
+    })
+}
+
+/// Google Calendarのイベントで検索語に一致した項目を返す
+pub fn google_event_query_match<'a>(event: &'a Event, query: &str) -> Option<QueryMatch<'a>> {
+    find_query_match(
+        [
+            ("タイトル", event.summary.as_deref()),
+            ("説明", event.description.as_deref()),
+            ("場所", event.location.as_deref()),
+        ],
+        query,
+    )
+}
+
+/// 大文字小文字を区別せずに`query`を探し、元の文字列でのバイト範囲を返す
+fn find_ignore_case(text: &str, query: &str) -> Option<std::ops::Range<usize>> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return None;
+    }
+    text.char_indices().find_map(|(start, _)| {
+        let mut lowered = Vec::new();
+        for (offset, c) in text[start..].char_indices() {
+            lowered.extend(c.to_lowercase());
+            if lowered.len() >= query.len() {
+                return (lowered == query).then(|| start..start + offset + c.len_utf8());
+            }
+        }
+        None
+    })
+}
+
+/// 予定の日時を「07/01 15:00-16:00」のように表示用に整形する（日本時間）
+pub fn format_event_time_range(event: &Event, app_config: &AppConfig) -> String {
+    let mut time_info = String::new();
+    if let Some(start) = &event.start {
+        if let Some(date_time) = &start.date_time {
+            let start_jst = date_time.with_timezone(&Tokyo);
+            time_info.push_str(&format!(
+                "{} {}",
+                app_config.format_date(start_jst.date_naive()),
+                start_jst.format(app_config.time_format())
+            ));
+        } else if let Some(date) = &start.date {
+            time_info.push_str(&app_config.format_date(*date));
+        }
+    }
+
+    if let Some(end) = &event.end {
+        if let Some(date_time) = &end.date_time {
+            let end_jst = date_time.with_timezone(&Tokyo);
+            let start_date = event
+                .start
+                .as_ref()
+                .and_then(|start| start.date_time)
+                .map(|start| start.with_timezone(&Tokyo).date_naive());
+            // 日をまたぐ予定は終了日も表示する（例: 07/01 22:00-07/02 09:00）
+            if start_date.is_some_and(|start_date| start_date != end_jst.date_naive()) {
+                time_info.push_str(&format!(
+                    "-{} {}",
+                    app_config.format_date(end_jst.date_naive()),
+                    end_jst.format(app_config.time_format())
+                ));
+            } else {
+                time_info.push_str(&format!("-{}", end_jst.format(app_config.time_format())));
+            }
+        } else if let Some(date) = &end.date {
+            // 終日予定の終了日は含まれないため、前日を最終日として表示する（1日だけなら日付のみ）
+            let last_day = date.pred_opt().unwrap_or(*date);
+            let start_date = event.start.as_ref().and_then(|start| start.date);
+            if !time_info.is_empty() && start_date.is_some_and(|start_date| start_date < last_day) {
+                time_info.push_str(&format!("-{}", app_config.format_date(last_day)));
+            }
+        }
+    }
+    time_info
+}
+
+/// 住所として扱う英語の通りの名前
+const STREET_WORDS: [&str; 12] = ["st", "street", "ave", "avenue", "rd", "road", "blvd", "pkwy", "dr", "drive", "ln", "way"];
+
+/// 場所が住所らしいか（会議室名やURLなどは除く）
+pub fn looks_like_address(location: &str) -> bool {
+    if location.contains("://") {
+        return false;
+    }
+    let has_digit = location.chars().any(|c| c.is_ascii_digit() || ('０'..='９').contains(&c));
+    if location.contains('〒') || location.contains("丁目") || location.contains("番地") {
+        return true;
+    }
+    let japanese = ["都", "道", "府", "県", "市", "区", "町", "村"]
+        .iter()
+        .any(|marker| location.contains(marker));
+    let english = location
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '.')
+        .any(|word| STREET_WORDS.contains(&word.to_lowercase().as_str()));
+    has_digit && (japanese || english)
+}
+
+/// 住所らしい場所のGoogleマップのURL（住所でなければNone）
+pub fn map_url(location: &str) -> Option<String> {
+    looks_like_address(location).then(|| {
+        format!(
+            "https://www.google.com/maps/search/?api=1&query={}",
+            urlencoding::encode(location.trim())
+        )
+    })
+}
+
+/// 場所を表示用にする（`hyperlinks`がtrueなら住所をOSC 8のリンクにして地図を開けるようにする）
+pub fn format_location(location: &str, hyperlinks: bool) -> String {
+    match map_url(location) {
+        Some(url) if hyperlinks => format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, location),
+        _ => location.to_string(),
+    }
+}
+
+/// 予定を「07/01 15:00-16:00 会議 @会議室A」のように1行で表す
+pub fn format_compact_event(event: &Event, app_config: &AppConfig, hyperlinks: bool) -> String {
+    let mut line = format!(
+        "{} {}",
+        format_event_time_range(event, app_config),
+        event
+            .summary
+            .as_deref()
+            .map_or_else(|| "(タイトルなし)".to_string(), |summary| summary.lines().collect::<Vec<_>>().join(" "))
+    );
+    if let Some(location) = event.location.as_deref().filter(|location| !location.is_empty()) {
+        line.push_str(&format!(" @{}", format_location(location, hyperlinks)));
+    }
+    line
+}
+
+/// 通知のタイミングを「10分前 / 1時間前」のように表示する
+pub fn format_reminders(event: &Event) -> String {
+    let reminders = match &event.reminders {
+        Some(reminders) if reminders.use_default != Some(true) => reminders,
+        _ => return "カレンダーの既定の通知".to_string(),
+    };
+    let mut minutes: Vec<i32> = reminders
+        .overrides
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|reminder| reminder.minutes)
+        .collect();
+    if minutes.is_empty() {
+        return "通知なし".to_string();
+    }
+    minutes.sort_unstable();
+    minutes.dedup();
+    minutes
+        .iter()
+        .map(|&minutes| match minutes {
+            0 => "開始時".to_string(),
+            m if m % 1440 == 0 => format!("{}日前", m / 1440),
+            m if m % 60 == 0 => format!("{}時間前", m / 60),
+            m => format!("{}分前", m),
+        })
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// 開始の`minutes`分前にポップアップで通知する設定（空なら通知なし）
+pub fn reminder_overrides(minutes: &[i64]) -> EventReminders {
+    EventReminders {
+        use_default: Some(false),
+        overrides: Some(
+            minutes
+                .iter()
+                .map(|&minutes| EventReminder {
+                    method: Some("popup".to_string()),
+                    minutes: i32::try_from(minutes).ok(),
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// 分を「3時間30分」の形式にする
+pub fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}分", m),
+        (h, 0) => format!("{}時間", h),
+        (h, m) => format!("{}時間{}分", h, m),
+    }
+}
+
+/// 予定の件数と、時刻指定のある予定の合計時間（例: 「5件（合計 3時間30分）」）
+pub fn format_event_count(items: &[Event]) -> String {
+    let minutes: i64 = items
+        .iter()
+        .filter_map(|event| {
+            let start = event.start.as_ref()?.date_time?;
+            let end = event.end.as_ref()?.date_time?;
+            Some((end - start).num_minutes().max(0))
+        })
+        .sum();
+    if minutes == 0 {
+        return format!("{}件", items.len());
+    }
+    format!("{}件（合計 {}）", items.len(), format_minutes(minutes))
+}
+
+/// 期間内に予定が入っている時間と、その期間の勤務時間
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utilization {
+    pub booked_minutes: i64,
+    pub working_minutes: i64,
+}
+
+impl Utilization {
+    /// 勤務時間に対する予定の割合（%）。勤務時間がない期間ならNone
+    pub fn percent(&self) -> Option<f64> {
+        (self.working_minutes > 0).then(|| self.booked_minutes as f64 * 100.0 / self.working_minutes as f64)
+    }
+}
+
+/// 時刻指定があり、「予定なし」扱いでない予定の時間帯
+pub fn busy_interval(event: &Event) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if event.transparency.as_deref() == Some("transparent") {
+        return None;
+    }
+    Some((event.start.as_ref()?.date_time?, event.end.as_ref()?.date_time?))
+}
+
+/// `start`〜`end`に予定が入っている時間と、平日の勤務時間の合計を求める
+///
+/// 重なっている予定は二重に数えず、期間からはみ出す部分は切り捨てる。
+pub fn compute_utilization(
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    working_hours: (NaiveTime, NaiveTime),
+) -> Utilization {
+    let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)> = busy
+        .iter()
+        .map(|(busy_start, busy_end)| ((*busy_start).max(start), (*busy_end).min(end)))
+        .filter(|(busy_start, busy_end)| busy_start < busy_end)
+        .collect();
+    intervals.sort();
+
+    let mut booked = Duration::zero();
+    let mut current: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    for (busy_start, busy_end) in intervals {
+        current = match current {
+            Some((current_start, current_end)) if busy_start <= current_end => {
+                Some((current_start, current_end.max(busy_end)))
+            }
+            Some((current_start, current_end)) => {
+                booked += current_end - current_start;
+                Some((busy_start, busy_end))
+            }
+            None => Some((busy_start, busy_end)),
+        };
+    }
+    if let Some((current_start, current_end)) = current {
+        booked += current_end - current_start;
+    }
+
+    let working = working_windows(start, end, working_hours)
+        .into_iter()
+        .fold(Duration::zero(), |total, (from, to)| total + (to - from));
+
+    Utilization { booked_minutes: booked.num_minutes(), working_minutes: working.num_minutes() }
+}
+
+/// `start`〜`end`のうち、平日の勤務時間（日本時間）にあたる時間帯を日ごとに返す
+pub fn working_windows(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    working_hours: (NaiveTime, NaiveTime),
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let last_date = end.with_timezone(&Tokyo).date_naive();
+    start
+        .with_timezone(&Tokyo)
+        .date_naive()
+        .iter_days()
+        .take_while(|date| *date <= last_date)
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .filter_map(|date| {
+            let day_start = Tokyo.from_local_datetime(&date.and_time(working_hours.0)).earliest()?;
+            let day_end = Tokyo.from_local_datetime(&date.and_time(working_hours.1)).earliest()?;
+            let from = day_start.with_timezone(&Utc).max(start);
+            let to = day_end.with_timezone(&Utc).min(end);
+            (from < to).then_some((from, to))
+        })
+        .collect()
+}
+
+/// Markdownの表のセルに入れられるよう、`|`をエスケープし改行を空白にする
+fn escape_markdown_cell(text: &str) -> String {
+    text.lines().collect::<Vec<_>>().join(" ").replace('|', "\\|")
+}
+
+/// 予定をMarkdownの表（Date / Time / Title / Location）にする
+pub fn format_markdown_table(items: &[Event], app_config: &AppConfig) -> String {
+    let mut table = String::from("| Date | Time | Title | Location |\n| --- | --- | --- | --- |\n");
+    for event in items {
+        let (date, time) = match event.start.as_ref().and_then(|start| start.date_time) {
+            Some(start) => {
+                let start_jst = start.with_timezone(&Tokyo);
+                let mut time = start_jst.format(app_config.time_format()).to_string();
+                if let Some(end) = event.end.as_ref().and_then(|end| end.date_time) {
+                    let end_jst = end.with_timezone(&Tokyo);
+                    if end_jst.date_naive() != start_jst.date_naive() {
+                        time.push_str(&format!("-{} ", app_config.format_date(end_jst.date_naive())));
+                    } else {
+                        time.push('-');
+                    }
+                    time.push_str(&end_jst.format(app_config.time_format()).to_string());
+                }
+                (app_config.format_date(start_jst.date_naive()), time)
+            }
+            None => (
+                event
+                    .start
+                    .as_ref()
+                    .and_then(|start| start.date)
+                    .map(|date| app_config.format_date(date))
+                    .unwrap_or_default(),
+                "終日".to_string(),
+            ),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            date,
+            time,
+            escape_markdown_cell(event.summary.as_deref().unwrap_or("(タイトルなし)")),
+            escape_markdown_cell(event.location.as_deref().unwrap_or(""))
+        ));
+    }
+    table
+}
+
+/// 予定を開始日（日本時間）ごとにまとめる
+///
+/// 日付の古い順に並べ、同じ日の予定は元の順序を保つ。開始日時のない予定は除く。
+pub fn group_events_by_day(items: &[Event]) -> Vec<(NaiveDate, Vec<&Event>)> {
+    let mut days: Vec<(NaiveDate, Vec<&Event>)> = Vec::new();
+    for event in items {
+        let Some(start) = event_start(event) else { continue };
+        let date = start.with_timezone(&Tokyo).date_naive();
+        match days.iter_mut().find(|(day, _)| *day == date) {
+            Some((_, events)) => events.push(event),
+            None => days.push((date, vec![event])),
+        }
+    }
+    days.sort_by_key(|(day, _)| *day);
+    days
+}
+
+/// 優先度に対応するGoogle Calendarの色ID
+pub fn priority_color_id(priority: &Priority) -> Option<&'static str> {
+    match priority {
+        Priority::Urgent => Some("11"), // トマト
+        Priority::High => Some("6"),    // ミカン
+        Priority::Medium => None,
+        Priority::Low => Some("8"),     // グラファイト
+    }
+}
+
+/// Google Calendarの色IDから優先度を求める（`priority_color_id`の逆変換）
+///
+/// 対応しない色IDはMediumとして扱う。
+pub fn color_id_to_priority(color_id: &str) -> Priority {
+    match color_id {
+        "11" => Priority::Urgent,
+        "6" => Priority::High,
+        "8" => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+/// イベントの色から優先度を求める（色が未設定ならMedium）
+pub fn event_priority(event: &Event) -> Priority {
+    event
+        .color_id
+        .as_deref()
+        .map_or(Priority::Medium, color_id_to_priority)
+}
+
+/// 予定の削除前に確認が必要か（優先度が高い・緊急の予定は`confirm_all`に関わらず確認する）
+pub fn deletion_needs_confirmation(event: &Event, confirm_all: bool) -> bool {
+    confirm_all || matches!(event_priority(event), Priority::High | Priority::Urgent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::sync::Mutex;
+
+    /// 作成要求を記録するだけのモックバックエンド
+    #[derive(Default)]
+    struct MockBackend {
+        created: Mutex<Vec<Event>>,
+        patched: Mutex<Vec<(String, Event)>>,
+        /// 削除要求（イベントIDとsendUpdates）
+        deleted: Mutex<Vec<(String, String)>>,
+        /// trueの場合は作成要求を失敗させる
+        fail_create: bool,
+        /// freebusyの応答（未設定なら問い合わせを失敗させる）
+        free_busy: Option<FreeBusyResponse>,
+        /// calendarListの応答
+        calendars: Vec<CalendarListEntry>,
+    }
+
+    #[async_trait]
+    impl CalendarBackend for MockBackend {
+        async fn create_primary_event(&self, event: Event) -> Result<Event> {
+            if self.fail_create {
+                return Err(anyhow::anyhow!("Google Calendar API error"));
+            }
+            self.created.lock().unwrap().push(event.clone());
+            Ok(Event { id: Some("mock-id".to_string()), ..event })
+        }
+
+        async fn list_primary_events(&self, _time_min: DateTime<Utc>, _time_max: DateTime<Utc>) -> Result<Vec<Event>> {
+            Ok(self.created.lock().unwrap().clone())
+        }
+
+        async fn get_primary_event(&self, event_id: &str) -> Result<Event> {
+            self.created
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|event| event.id.as_deref() == Some(event_id))
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("not found"))
+        }
+
+        async fn patch_primary_event(&self, event_id: &str, event: Event) -> Result<Event> {
+            self.patched.lock().unwrap().push((event_id.to_string(), event.clone()));
+            Ok(event)
+        }
+
+        async fn delete_primary_event(&self, event_id: &str, send_updates: &str) -> Result<()> {
+            self.deleted
+                .lock()
+                .unwrap()
+                .push((event_id.to_string(), send_updates.to_string()));
+            Ok(())
+        }
+
+        async fn free_busy(&self, _calendar_ids: &[String], _time_min: DateTime<Utc>, _time_max: DateTime<Utc>) -> Result<FreeBusyResponse> {
+            self.free_busy.clone().ok_or_else(|| anyhow::anyhow!("freebusy unavailable"))
+        }
+
+        async fn account_email(&self) -> Result<String> {
+            Ok("me@example.com".to_string())
+        }
+
+        async fn list_calendars(&self) -> Result<Vec<CalendarListEntry>> {
+            Ok(self.calendars.clone())
+        }
+    }
+
+    fn timed_event(summary: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Event {
+        Event {
+            id: Some(summary.to_string()),
+            summary: Some(summary.to_string()),
+            start: Some(EventDateTime { date_time: Some(start), ..Default::default() }),
+            end: Some(EventDateTime { date_time: Some(end), ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    fn nine_to_six() -> (NaiveTime, NaiveTime) {
+        (NaiveTime::from_hms_opt(9, 0, 0).unwrap(), NaiveTime::from_hms_opt(18, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_shift_patches_only_remaining_timed_events() {
+        let now = Utc.with_ymd_and_hms(2025, 7, 1, 3, 0, 0).unwrap();
+        let hour = Duration::hours(1);
+        let all_day = Event {
+            summary: Some("終日".to_string()),
+            start: Some(EventDateTime { date: Some(now.date_naive()), ..Default::default() }),
+            end: Some(EventDateTime { date: now.date_naive().succ_opt(), ..Default::default() }),
+            ..Default::default()
+        };
+        let items = vec![
+            timed_event("朝会", now - hour * 2, now - hour),
+            timed_event("会議A", now + hour, now + hour * 2),
+            timed_event("会議B", now + hour * 3, now + hour * 4),
+            timed_event("会議C", now + hour * 5, now + hour * 6),
+            all_day,
+        ];
+
+        let patches = build_shift_patches(&items, now, hour);
+
+        assert_eq!(patches.len(), 3);
+        for (original, patch) in &patches {
+            let original_start = original.start.as_ref().unwrap().date_time.unwrap();
+            assert_eq!(patch.start.as_ref().unwrap().date_time, Some(original_start + hour));
+        }
+        assert!(patches.iter().all(|(original, _)| original.summary.as_deref() != Some("朝会")));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_event_preserves_duration() {
+        let backend = MockBackend::default();
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let mut original = timed_event("定例", start, start + Duration::minutes(90));
+        original.location = Some("会議室B".to_string());
+        original.attendees = Some(vec![EventAttendee {
+            email: Some("a@example.com".to_string()),
+            response_status: Some("accepted".to_string()),
+            ..Default::default()
+        }]);
+        backend.created.lock().unwrap().push(original);
+
+        let new_start = start + Duration::days(7);
+        let copy = duplicate_event(&backend, "定例", new_start, None).await.unwrap();
+
+        assert_eq!(copy.summary.as_deref(), Some("定例"));
+        assert_eq!(copy.location.as_deref(), Some("会議室B"));
+        assert_eq!(copy.start.unwrap().date_time, Some(new_start));
+        assert_eq!(copy.end.unwrap().date_time, Some(new_start + Duration::minutes(90)));
+        let attendees = copy.attendees.unwrap();
+        assert_eq!(attendees[0].email.as_deref(), Some("a@example.com"));
+        assert_eq!(attendees[0].response_status, None);
+    }
+
+    #[tokio::test]
+    async fn test_delete_notifies_attendees_only_when_present() {
+        let backend = MockBackend::default();
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let mut meeting = timed_event("定例", start, start + Duration::hours(1));
+        meeting.id = Some("with-guests".to_string());
+        meeting.attendees = Some(vec![EventAttendee {
+            email: Some("guest@example.com".to_string()),
+            ..Default::default()
+        }]);
+        let mut solo = timed_event("作業", start, start + Duration::hours(1));
+        solo.id = Some("solo".to_string());
+
+        delete_event_with_notice(&backend, &meeting, None).await.unwrap();
+        delete_event_with_notice(&backend, &solo, None).await.unwrap();
+        delete_event_with_notice(&backend, &meeting, Some("externalOnly")).await.unwrap();
+
+        let deleted = backend.deleted.lock().unwrap();
+        assert_eq!(deleted[0], ("with-guests".to_string(), "all".to_string()));
+        assert_eq!(deleted[1], ("solo".to_string(), "none".to_string()));
+        assert_eq!(deleted[2].1, "externalOnly");
+    }
+
+    #[tokio::test]
+    async fn test_delete_events_deletes_every_event_in_range() {
+        let backend = MockBackend::default();
+        let start = Utc.with_ymd_and_hms(2025, 8, 10, 1, 0, 0).unwrap();
+        let mut flight = timed_event("フライト", start, start + Duration::hours(2));
+        flight.attendees = Some(vec![EventAttendee {
+            email: Some("family@example.com".to_string()),
+            ..Default::default()
+        }]);
+        let events = vec![
+            flight,
+            timed_event("ホテル", start + Duration::days(1), start + Duration::days(1) + Duration::hours(1)),
+            timed_event("観光", start + Duration::days(2), start + Duration::days(2) + Duration::hours(3)),
+        ];
+
+        let results = delete_events(&backend, &events, None).await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        let deleted = backend.deleted.lock().unwrap();
+        assert_eq!(
+            *deleted,
+            [
+                ("フライト".to_string(), "all".to_string()),
+                ("ホテル".to_string(), "none".to_string()),
+                ("観光".to_string(), "none".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_free_slots_by_preference() {
+        let jst = |day: u32, hour: u32| Tokyo.with_ymd_and_hms(2025, 7, day, hour, 0, 0).unwrap().with_timezone(&Utc);
+        let afternoon_short = (jst(1, 14), jst(1, 15));
+        let morning_short = (jst(2, 9), jst(2, 10));
+        let afternoon_long = (jst(2, 13), jst(2, 17));
+        let slots = vec![afternoon_short, morning_short, afternoon_long];
+
+        let mut sorted = slots.clone();
+        sort_free_slots(&mut sorted, SlotPreference::Morning);
+        assert_eq!(sorted, vec![morning_short, afternoon_short, afternoon_long]);
+
+        let mut sorted = slots.clone();
+        sort_free_slots(&mut sorted, SlotPreference::Afternoon);
+        assert_eq!(sorted, vec![afternoon_short, afternoon_long, morning_short]);
+
+        let mut sorted = slots;
+        sort_free_slots(&mut sorted, SlotPreference::Longest);
+        assert_eq!(sorted, vec![afternoon_long, afternoon_short, morning_short]);
+
+        assert_eq!(SlotPreference::parse("longest"), Some(SlotPreference::Longest));
+        assert_eq!(SlotPreference::parse("evening"), None);
+    }
+
+    #[test]
+    fn test_query_match_identifies_field() {
+        let event = Event {
+            summary: Some("定例ミーティング".to_string()),
+            description: Some("四半期のRoadmapレビューと来期の計画について議論する".to_string()),
+            location: Some("会議室A".to_string()),
+            ..Default::default()
+        };
+
+        let found = google_event_query_match(&event, "roadmap").unwrap();
+        assert_eq!(found.field, "説明");
+        assert_eq!(&found.text[found.range.clone()], "Roadmap");
+        assert_eq!(found.context(3), ("…半期の".to_string(), "Roadmap", "レビュ…".to_string()));
+
+        assert_eq!(google_event_query_match(&event, "会議室").unwrap().field, "場所");
+        assert_eq!(google_event_query_match(&event, "定例").unwrap().field, "タイトル");
+        assert!(google_event_query_match(&event, "採用").is_none());
+    }
+
+    #[test]
+    fn test_compact_event_is_single_line() {
+        let jst = |hour: u32| Tokyo.with_ymd_and_hms(2025, 7, 1, hour, 0, 0).unwrap().with_timezone(&Utc);
+        let mut event = timed_event("会議", jst(15), jst(16));
+        event.location = Some("会議室A".to_string());
+        let app_config = crate::config::Config::default().app;
+
+        assert_eq!(format_compact_event(&event, &app_config, true), "07/01 15:00-16:00 会議 @会議室A");
+
+        event.location = None;
+        event.summary = Some("会議\n（延長あり）".to_string());
+        let line = format_compact_event(&event, &app_config, false);
+        assert_eq!(line, "07/01 15:00-16:00 会議 （延長あり）");
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn test_apply_attendee_changes_keeps_existing() {
+        let existing = vec![
+            EventAttendee { email: Some("sato@example.com".to_string()), display_name: Some("佐藤".to_string()), ..Default::default() },
+            EventAttendee { email: Some("suzuki@example.com".to_string()), ..Default::default() },
+        ];
+
+        let added = apply_attendee_changes(&existing, &["tanaka@example.com".to_string()], &[]).unwrap();
+        assert_eq!(added.len(), 3);
+        assert_eq!(added[2].email.as_deref(), Some("tanaka@example.com"));
+
+        let removed = apply_attendee_changes(&added, &[], &["佐藤さん".to_string()]).unwrap();
+        let emails: Vec<_> = removed.iter().filter_map(|a| a.email.as_deref()).collect();
+        assert_eq!(emails, vec!["suzuki@example.com", "tanaka@example.com"]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_calendar_name_to_id() {
+        let calendar = |id: &str, summary: &str| CalendarListEntry {
+            id: Some(id.to_string()),
+            summary: Some(summary.to_string()),
+            ..Default::default()
+        };
+        let backend = MockBackend {
+            calendars: vec![
+                calendar("me@example.com", "me@example.com"),
+                calendar("work@group.calendar.google.com", "仕事"),
+                calendar("family@group.calendar.google.com", "家族"),
+                calendar("family-events@group.calendar.google.com", "家族イベント"),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(resolve_calendar_id(&backend, "仕事カレンダー").await.unwrap(), "work@group.calendar.google.com");
+        assert_eq!(resolve_calendar_id(&backend, "仕事").await.unwrap(), "work@group.calendar.google.com");
+        // 完全に一致するものを優先する
+        assert_eq!(resolve_calendar_id(&backend, "家族").await.unwrap(), "family@group.calendar.google.com");
+
+        let unknown = resolve_calendar_id(&backend, "趣味カレンダー").await.unwrap_err().to_string();
+        assert!(unknown.contains("見つかりません") && unknown.contains("「仕事」"));
+
+        let ambiguous = resolve_calendar_id(&backend, "族").await.unwrap_err().to_string();
+        assert!(ambiguous.contains("複数") && ambiguous.contains("「家族」") && ambiguous.contains("「家族イベント」"));
+    }
+
+    #[test]
+    fn test_duplicate_pair_is_identified() {
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let end = start + Duration::hours(1);
+        let created = |event: Event, id: &str, minutes: i64| Event {
+            id: Some(id.to_string()),
+            created: Some(start - Duration::days(1) + Duration::minutes(minutes)),
+            ..event
+        };
+        let events = vec![
+            created(timed_event("Weekly Sync", start, end), "later", 30),
+            created(timed_event("設計レビュー", start, end), "review", 0),
+            created(timed_event(" weekly  sync ", start, end), "original", 0),
+            created(timed_event("Weekly Sync", start + Duration::days(7), end + Duration::days(7)), "next-week", 0),
+        ];
+
+        let groups = find_duplicate_events(&events);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].keep.id.as_deref(), Some("original"));
+        let duplicate_ids: Vec<_> = groups[0].duplicates.iter().filter_map(|event| event.id.as_deref()).collect();
+        assert_eq!(duplicate_ids, ["later"]);
+    }
+
+    #[test]
+    fn test_append_description_keeps_existing_text() {
+        let description = append_description(Some("定例会議\n"), "議題: 来期の予算");
+        assert_eq!(description, "定例会議\n議題: 来期の予算");
+        assert!(description.contains("定例会議") && description.contains("議題: 来期の予算"));
+
+        assert_eq!(append_description(None, "議題: 採用"), "議題: 採用");
+        assert_eq!(append_description(Some(""), "議題: 採用"), "議題: 採用");
+    }
+
+    #[tokio::test]
+    async fn test_out_of_office_sets_event_type() {
+        let backend = MockBackend::default();
+        let start = Utc.with_ymd_and_hms(2025, 8, 10, 0, 0, 0).unwrap();
+
+        create_out_of_office(&backend, start, start + Duration::days(3), Some("夏季休暇中です"))
+            .await
+            .unwrap();
+
+        let created = backend.created.lock().unwrap();
+        assert_eq!(created[0].event_type.as_deref(), Some("outOfOffice"));
+        let properties = created[0].out_of_office_properties.as_ref().unwrap();
+        assert_eq!(properties.auto_decline_mode.as_deref(), Some("declineAllConflictingInvitations"));
+        assert_eq!(properties.decline_message.as_deref(), Some("夏季休暇中です"));
+    }
+
+    #[tokio::test]
+    async fn test_create_event_issues_create_call() {
+        let backend = MockBackend::default();
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+
+        let event = create_event_with(
+            &backend,
+            "設計レビュー",
+            Some("資料を事前共有"),
+            Some("会議室A"),
+            start,
+            start + Duration::hours(1),
+            Some(&Priority::High),
+            &[],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(event.id.as_deref(), Some("mock-id"));
+        let created = backend.created.lock().unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].summary.as_deref(), Some("設計レビュー"));
+        assert_eq!(created[0].location.as_deref(), Some("会議室A"));
+        assert_eq!(created[0].color_id.as_deref(), Some("6"));
+    }
+
+    #[tokio::test]
+    async fn test_search_returns_only_matching_events() {
+        let backend = MockBackend::default();
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        for (title, location) in [("標準会議", None), ("ランチ", Some("社食"))] {
+            let mut event = timed_event(title, start, start + Duration::hours(1));
+            event.location = location.map(|s| s.to_string());
+            backend.created.lock().unwrap().push(event);
+        }
+
+        let found = search_events(&backend, "標準会議", start, start + Duration::days(30))
+            .await
+            .unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].summary.as_deref(), Some("標準会議"));
+    }
+
+    #[test]
+    fn test_color_id_to_priority() {
+        assert!(matches!(color_id_to_priority("11"), Priority::Urgent));
+        assert!(matches!(color_id_to_priority("99"), Priority::Medium));
+        for priority in [Priority::Low, Priority::High, Priority::Urgent] {
+            let color_id = priority_color_id(&priority).unwrap();
+            assert_eq!(
+                std::mem::discriminant(&color_id_to_priority(color_id)),
+                std::mem::discriminant(&priority)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_events_returned_when_fetch_fails() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let cached = Events {
+            items: Some(vec![timed_event("朝会", start, start + Duration::minutes(15))]),
+            ..Default::default()
+        };
+        storage.save_event_cache("today:2025-07-01", &cached)?;
+
+        let (events, from_cache) = fetch_with_cache(Some(&storage), "today:2025-07-01", async {
+            Err(anyhow::anyhow!("ネットワークに接続できません"))
+        })
+        .await?;
+
+        assert!(from_cache);
+        assert_eq!(events.items.unwrap()[0].summary.as_deref(), Some("朝会"));
+        assert!(fetch_with_cache(Some(&storage), "today:2025-07-02", async { Err(anyhow::anyhow!("offline")) })
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_local_mirror_is_not_saved_when_google_fails() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let local_event = || LocalEvent::new("設計レビュー".to_string(), start, start + Duration::hours(1));
+
+        let failing = MockBackend { fail_create: true, ..Default::default() };
+        let mut schedule = Schedule::new();
+        assert!(create_event_with_local_mirror(&failing, &storage, &mut schedule, local_event()).await.is_err());
+        assert!(schedule.events.is_empty());
+        assert!(storage.load_schedule()?.events.is_empty());
+
+        let backend = MockBackend::default();
+        create_event_with_local_mirror(&backend, &storage, &mut schedule, local_event()).await?;
+        let saved = storage.load_schedule()?;
+        assert_eq!(saved.events.len(), 1);
+        assert_eq!(saved.events[0].google_event_id.as_deref(), Some("mock-id"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_next_event_returns_earliest_future_match() {
+        let backend = MockBackend::default();
+        let now = Utc.with_ymd_and_hms(2025, 7, 1, 3, 0, 0).unwrap();
+        let day = Duration::days(1);
+        for (title, start) in [
+            ("田中さんと1on1", now + day * 7),
+            ("田中さんと1on1", now - day),
+            ("チーム定例", now + day),
+            ("田中さんと1on1", now + day * 2),
+        ] {
+            backend.created.lock().unwrap().push(timed_event(title, start, start + Duration::minutes(30)));
+        }
+
+        let next = find_next_event(&backend, "1on1", now, Duration::days(90)).await.unwrap().unwrap();
+
+        assert_eq!(next.start.unwrap().date_time, Some(now + day * 2));
+        assert!(find_next_event(&backend, "歯医者", now, Duration::days(90)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_busy_events_reports_overlapping_events() {
+        let backend = MockBackend::default();
+        let at = |hour| Utc.with_ymd_and_hms(2025, 7, 1, hour, 0, 0).unwrap();
+        let mut focus = timed_event("集中時間", at(6), at(7));
+        focus.transparency = Some("transparent".to_string());
+        backend.created.lock().unwrap().extend([
+            timed_event("設計レビュー", at(6), at(8)),
+            timed_event("週次定例", at(5), at(6)),
+            focus,
+        ]);
+
+        let busy = busy_events(&backend, at(6), at(7)).await.unwrap();
+        assert_eq!(busy.len(), 1);
+        assert_eq!(busy[0].summary.as_deref(), Some("設計レビュー"));
+
+        assert!(busy_events(&backend, at(8), at(9)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_my_events_keeps_only_events_i_organize() {
+        use google_calendar3::api::{EventCreator, EventOrganizer};
+
+        let backend = MockBackend::default();
+        let now = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let organized_by = |summary: &str, email: &str| Event {
+            organizer: Some(EventOrganizer { email: Some(email.to_string()), ..Default::default() }),
+            ..timed_event(summary, now, now + Duration::hours(1))
+        };
+        let created_by_me = Event {
+            creator: Some(EventCreator { email: Some("me@example.com".to_string()), ..Default::default() }),
+            ..timed_event("メモ", now, now + Duration::hours(1))
+        };
+        backend.created.lock().unwrap().extend([
+            organized_by("週次定例", "Me@Example.com"),
+            organized_by("他チームの会議", "owner@example.com"),
+            created_by_me,
+            timed_event("主催者不明", now, now + Duration::hours(1)),
+        ]);
+
+        let mine = my_events(&backend, now, now + Duration::days(1)).await.unwrap();
+
+        let titles: Vec<_> = mine.iter().filter_map(|event| event.summary.as_deref()).collect();
+        assert_eq!(titles, ["週次定例", "メモ"]);
+    }
+
+    #[tokio::test]
+    async fn test_rsvp_patches_own_response_status() {
+        let backend = MockBackend::default();
+        let attendee = |email: &str| EventAttendee {
+            email: Some(email.to_string()),
+            response_status: Some("needsAction".to_string()),
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let mut invitation = timed_event("設計レビュー", now, now + Duration::hours(1));
+        invitation.attendees = Some(vec![attendee("owner@example.com"), attendee("Me@Example.com")]);
+        let mut not_invited = timed_event("他チーム定例", now, now + Duration::hours(1));
+        not_invited.attendees = Some(vec![attendee("owner@example.com")]);
+        backend.created.lock().unwrap().extend([invitation, not_invited]);
+
+        respond_to_invitation(&backend, "設計レビュー", "declined").await.unwrap();
+
+        let patched = backend.patched.lock().unwrap().clone();
+        assert_eq!(patched.len(), 1);
+        assert_eq!(patched[0].0, "設計レビュー");
+        let attendees = patched[0].1.attendees.clone().unwrap();
+        assert_eq!(attendees[0].response_status.as_deref(), Some("needsAction"));
+        assert_eq!(attendees[1].response_status.as_deref(), Some("declined"));
+
+        assert!(respond_to_invitation(&backend, "他チーム定例", "accepted").await.is_err());
+        assert!(respond_to_invitation(&backend, "設計レビュー", "maybe").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_creates_each_row_and_reports_failures() {
+        let backend = MockBackend::default();
+        let content = "title,start,end,location\n\
+            定例,2025-07-01T10:00:00+09:00,2025-07-01T11:00:00+09:00,\"会議室A, 3F\"\n\
+            レビュー,2025-07-02T14:00:00+09:00,2025-07-02T15:00:00+09:00,\n\
+            壊れた行,明日,2025-07-03T10:00:00+09:00,\n";
+        let parse = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|e| anyhow::anyhow!("{}", e))
+        };
+
+        let results = import_events(&backend, parse_event_rows(content, true), Some(&Priority::High), parse).await;
+
+        let created = backend.created.lock().unwrap();
+        assert_eq!(created.len(), 2);
+        assert_eq!(created[0].location.as_deref(), Some("会議室A, 3F"));
+        assert_eq!(created[0].color_id.as_deref(), priority_color_id(&Priority::High));
+        assert_eq!(results.iter().filter(|(_, r)| r.is_ok()).count(), 2);
+        assert_eq!(results[2].0, 4);
+        assert!(results[2].1.is_err());
+    }
+
+    #[test]
+    fn test_parse_json_lines_rows() {
+        let content = r#"{"title":"定例","start":"2025-07-01 10:00","end":"2025-07-01 11:00"}
+
+{"title":"欠損"}"#;
+
+        let rows = parse_event_rows(content, false);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].1.as_ref().unwrap().title, "定例");
+        assert_eq!(rows[1].0, 3);
+        assert!(rows[1].1.is_err());
+    }
+
+    #[test]
+    fn test_overnight_event_shows_end_date() {
+        let app_config = crate::config::Config::default().app;
+        let at = |day, hour| Tokyo.with_ymd_and_hms(2025, 7, day, hour, 0, 0).unwrap().with_timezone(&Utc);
+
+        let overnight = timed_event("夜間メンテナンス", at(1, 22), at(2, 9));
+        assert_eq!(format_event_time_range(&overnight, &app_config), "07/01 22:00-07/02 09:00");
+
+        let same_day = timed_event("定例", at(1, 10), at(1, 11));
+        assert_eq!(format_event_time_range(&same_day, &app_config), "07/01 10:00-11:00");
+    }
+
+    #[test]
+    fn test_all_day_event_shows_inclusive_last_day() {
+        let app_config = crate::config::Config::default().app;
+        let date = |day| NaiveDate::from_ymd_opt(2025, 7, day).unwrap();
+
+        // Google Calendarの終日予定はend.dateを含まない
+        let all_day = |start: NaiveDate, end: NaiveDate| Event {
+            start: Some(EventDateTime { date: Some(start), ..Default::default() }),
+            end: Some(EventDateTime { date: Some(end), ..Default::default() }),
+            ..Default::default()
+        };
+
+        // 7/10〜7/11の予定は7/10の1日だけ
+        assert_eq!(format_event_time_range(&all_day(date(10), date(11)), &app_config), "07/10");
+        // 7/10〜7/13の予定は7/12までの3日間
+        assert_eq!(format_event_time_range(&all_day(date(10), date(13)), &app_config), "07/10-07/12");
+        // 月をまたいでも最終日は前日になる
+        let august_first = NaiveDate::from_ymd_opt(2025, 8, 1).unwrap();
+        assert_eq!(format_event_time_range(&all_day(date(30), august_first), &app_config), "07/30-07/31");
+    }
+
+    #[test]
+    fn test_event_count_sums_booked_minutes() {
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let all_day = Event {
+            start: Some(EventDateTime { date: NaiveDate::from_ymd_opt(2025, 7, 2), ..Default::default() }),
+            ..Default::default()
+        };
+        let items = vec![
+            timed_event("定例", start, start + Duration::hours(1)),
+            timed_event("1on1", start + Duration::hours(2), start + Duration::minutes(150)),
+            all_day,
+        ];
+
+        assert_eq!(format_event_count(&items), "3件（合計 1時間30分）");
+        assert_eq!(format_event_count(&items[2..]), "1件");
+    }
+
+    #[test]
+    fn test_format_reminders_lists_overrides() {
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let mut event = timed_event("定例", start, start + Duration::hours(1));
+        assert_eq!(format_reminders(&event), "カレンダーの既定の通知");
+
+        event.reminders = Some(reminder_overrides(&[60, 10, 1440]));
+        assert_eq!(format_reminders(&event), "10分前 / 1時間前 / 1日前");
+
+        event.reminders = Some(reminder_overrides(&[]));
+        assert_eq!(format_reminders(&event), "通知なし");
+    }
+
+    #[test]
+    fn test_markdown_table_escapes_pipes() {
+        let start = Tokyo.with_ymd_and_hms(2025, 7, 1, 10, 0, 0).unwrap().with_timezone(&Utc);
+        let mut event = timed_event("設計 | レビュー", start, start + Duration::hours(1));
+        event.location = Some("会議室A".to_string());
+
+        let table = format_markdown_table(&[event], &crate::config::Config::default().app);
+
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "| Date | Time | Title | Location |");
+        assert_eq!(lines[1], "| --- | --- | --- | --- |");
+        assert_eq!(lines[2], "| 07/01 | 10:00-11:00 | 設計 \\| レビュー | 会議室A |");
+    }
+
+    #[test]
+    fn test_group_events_by_day_orders_headers() {
+        let at = |day, hour| Tokyo.with_ymd_and_hms(2025, 7, day, hour, 0, 0).unwrap().with_timezone(&Utc);
+        let items = vec![
+            timed_event("3日の会議", at(3, 10), at(3, 11)),
+            timed_event("1日の朝会", at(1, 9), at(1, 10)),
+            // 日本時間では2日の0時台（UTCでは1日）
+            timed_event("2日の深夜作業", at(2, 0), at(2, 1)),
+            timed_event("1日の夕会", at(1, 18), at(1, 19)),
+        ];
+
+        let days = group_events_by_day(&items);
+
+        let dates: Vec<u32> = days.iter().map(|(date, _)| date.day()).collect();
+        assert_eq!(dates, vec![1, 2, 3]);
+        let first_day: Vec<&str> = days[0].1.iter().filter_map(|event| event.summary.as_deref()).collect();
+        assert_eq!(first_day, vec!["1日の朝会", "1日の夕会"]);
+    }
+
+    #[test]
+    fn test_conflict_check_can_exclude_the_event_being_moved() {
+        let at = |hour, minute| Tokyo.with_ymd_and_hms(2025, 7, 1, hour, minute, 0).unwrap().with_timezone(&Utc);
+        let mut meeting = timed_event("定例", at(10, 0), at(11, 0));
+        meeting.id = Some("meeting".to_string());
+        let items = vec![meeting, timed_event("ランチ", at(12, 0), at(13, 0))];
+
+        // 10分後ろへずらしても、移動前の自分自身とは重複とみなさない
+        assert_eq!(conflicting_events(&items, at(10, 10), at(11, 10)).len(), 1);
+        assert!(conflicting_events_excluding(&items, at(10, 10), at(11, 10), "meeting").is_empty());
+
+        // 他の予定との重なりは引き続き検出する
+        let conflicts = conflicting_events_excluding(&items, at(11, 40), at(12, 10), "meeting");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].summary.as_deref(), Some("ランチ"));
+    }
+
+    #[test]
+    fn test_nearest_free_slots_surround_conflict() {
+        let at = |hour, minute| Tokyo.with_ymd_and_hms(2025, 7, 1, hour, minute, 0).unwrap().with_timezone(&Utc);
+        let meeting = timed_event("m", at(10, 0), at(11, 0));
+        let lunch = timed_event("l", at(12, 0), at(13, 0));
+        let mut free = timed_event("f", at(14, 0), at(15, 0));
+        free.transparency = Some("transparent".to_string());
+        let items = vec![meeting, lunch, free];
+
+        let requested = (at(10, 30), at(11, 30));
+        let conflicts = conflicting_events(&items, requested.0, requested.1);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicting_events(&items, at(14, 0), at(15, 0)).is_empty());
+
+        let window = day_window(NaiveDate::from_ymd_opt(2025, 7, 1).unwrap());
+        let slots = nearest_free_slots(&items, window, requested, at(8, 0), 3);
+        assert_eq!(
+            slots,
+            vec![(at(11, 0), at(12, 0)), (at(9, 0), at(10, 0)), (at(13, 0), at(14, 0))]
+        );
+    }
+
+    #[test]
+    fn test_remaining_today_window_starts_now() {
+        let now = Tokyo.with_ymd_and_hms(2025, 7, 1, 13, 30, 0).unwrap().with_timezone(&Utc);
+        let start_of_day = Tokyo.with_ymd_and_hms(2025, 7, 1, 0, 0, 0).unwrap().with_timezone(&Utc);
+        let end_of_day = Tokyo.with_ymd_and_hms(2025, 7, 1, 23, 59, 59).unwrap().with_timezone(&Utc);
+
+        assert_eq!(today_window(now, false), (start_of_day, end_of_day));
+        let (start, end) = today_window(now, true);
+        assert_eq!((start, end), (now, end_of_day));
+
+        // 午前の予定は範囲外、進行中・これからの予定は範囲内
+        let morning = timed_event("朝会", now - Duration::hours(4), now - Duration::hours(3));
+        let ongoing = timed_event("作業", now - Duration::minutes(30), now + Duration::minutes(30));
+        let items = vec![morning, ongoing];
+        let in_window = conflicting_events(&items, start, end);
+        assert_eq!(in_window.len(), 1);
+        assert_eq!(in_window[0].summary.as_deref(), Some("作業"));
+    }
+
+    #[test]
+    fn test_upcoming_window_skips_past_hours() {
+        let now = Tokyo.with_ymd_and_hms(2025, 7, 1, 13, 30, 0).unwrap().with_timezone(&Utc);
+        let (start_of_day, end_of_day) = day_window(NaiveDate::from_ymd_opt(2025, 7, 1).unwrap());
+
+        assert_eq!(upcoming_window(start_of_day, end_of_day, now), Some((now, end_of_day)));
+        let (tomorrow_start, tomorrow_end) = day_window(NaiveDate::from_ymd_opt(2025, 7, 2).unwrap());
+        assert_eq!(
+            upcoming_window(tomorrow_start, tomorrow_end, now),
+            Some((tomorrow_start, tomorrow_end))
+        );
+        let (yesterday_start, yesterday_end) = day_window(NaiveDate::from_ymd_opt(2025, 6, 30).unwrap());
+        assert_eq!(upcoming_window(yesterday_start, yesterday_end, now), None);
+    }
+
+    #[test]
+    fn test_day_window_covers_exactly_the_requested_day() {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 2).unwrap();
+
+        let (start, end) = day_window(date);
+
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 7, 1, 15, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 7, 2, 14, 59, 59).unwrap());
+
+        let meeting = timed_event(
+            "定例",
+            Utc.with_ymd_and_hms(2025, 7, 2, 1, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2025, 7, 2, 2, 0, 0).unwrap(),
+        );
+        let slots = find_free_slots(&[meeting], start, end, Duration::minutes(60));
+        assert_eq!(slots.first().map(|slot| slot.0), Some(start));
+        assert_eq!(slots.last().map(|slot| slot.1), Some(end));
+    }
+
+    #[test]
+    fn test_free_time_buffer_drops_tight_gaps() {
+        let at = |hour: u32, minute: u32| Utc.with_ymd_and_hms(2025, 7, 1, hour, minute, 0).unwrap();
+        let items = [
+            timed_event("朝会", at(9, 0), at(10, 0)),
+            // 朝会との間は30分しかない
+            timed_event("設計レビュー", at(10, 30), at(11, 30)),
+            // 設計レビューとの間は90分
+            timed_event("1on1", at(13, 0), at(13, 30)),
+        ];
+        let buffer = Duration::minutes(15);
+
+        let slots = find_free_slots_with_buffer(&items, at(9, 0), at(14, 0), Duration::minutes(30), buffer);
+        assert_eq!(slots, [(at(11, 45), at(12, 45))]);
+
+        // 前後の余白を引くと長さが足りない、または負になる隙間は候補にしない
+        let slots = find_free_slots_with_buffer(&items, at(9, 0), at(14, 0), Duration::minutes(70), buffer);
+        assert!(slots.is_empty());
+
+        // 余白なしなら30分の隙間も候補になる
+        let slots = find_free_slots(&items, at(9, 0), at(14, 0), Duration::minutes(30));
+        assert_eq!(slots[0], (at(10, 0), at(10, 30)));
+    }
+
+    #[tokio::test]
+    async fn test_busy_attendee_is_flagged_and_unqueryable_is_unknown() {
+        use google_calendar3::api::{Error as FreeBusyError, FreeBusyCalendar, TimePeriod};
+
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let end = start + Duration::hours(1);
+        let calendars = [
+            (
+                "busy@example.com".to_string(),
+                FreeBusyCalendar {
+                    busy: Some(vec![TimePeriod { start: Some(start + Duration::minutes(30)), end: Some(end + Duration::minutes(30)) }]),
+                    errors: None,
+                },
+            ),
+            ("free@example.com".to_string(), FreeBusyCalendar { busy: Some(Vec::new()), errors: None }),
+            (
+                "private@example.com".to_string(),
+                FreeBusyCalendar {
+                    busy: None,
+                    errors: Some(vec![FreeBusyError { domain: Some("global".to_string()), reason: Some("notFound".to_string()) }]),
+                },
+            ),
+        ];
+        let backend = MockBackend {
+            free_busy: Some(FreeBusyResponse { calendars: Some(calendars.into_iter().collect()), ..Default::default() }),
+            ..Default::default()
+        };
+        let attendees: Vec<String> = ["busy@example.com", "free@example.com", "private@example.com", "missing@example.com"]
+            .iter()
+            .map(|email| email.to_string())
+            .collect();
+
+        let result = check_attendee_availability(&backend, &attendees, start, end).await;
+
+        assert_eq!(result[0].1, AttendeeAvailability::Busy(vec![(start + Duration::minutes(30), end + Duration::minutes(30))]));
+        assert_eq!(result[1].1, AttendeeAvailability::Free);
+        assert_eq!(result[2].1, AttendeeAvailability::Unknown);
+        assert_eq!(result[3].1, AttendeeAvailability::Unknown);
+        let app_config = crate::config::Config::default().app;
+        assert_eq!(format_attendee_availability(&result[0].0, &result[0].1, &app_config), "・busy@example.com: 予定あり（10:30-11:30）");
+        assert_eq!(format_attendee_availability(&result[2].0, &result[2].1, &app_config), "・private@example.com: 不明");
+
+        // 問い合わせ自体に失敗した場合は全員「不明」
+        let failing = check_attendee_availability(&MockBackend::default(), &attendees, start, end).await;
+        assert!(failing.iter().all(|(_, availability)| *availability == AttendeeAvailability::Unknown));
+    }
+
+    #[tokio::test]
+    async fn test_reschedule_moves_event_to_next_free_slot() {
+        let start = Tokyo.with_ymd_and_hms(2025, 7, 1, 10, 0, 0).unwrap().with_timezone(&Utc);
+        let meeting = timed_event("定例", start, start + Duration::hours(1));
+        let visitor = timed_event("来客", start, start + Duration::hours(2));
+        let backend = MockBackend { created: Mutex::new(vec![meeting.clone(), visitor]), ..Default::default() };
+
+        let moved = reschedule_to_next_free_slot(&backend, &meeting, start - Duration::hours(1), 7, nine_to_six())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let patched = backend.patched.lock().unwrap();
+        assert_eq!(patched.len(), 1);
+        assert_eq!(patched[0].0, "定例");
+        // 来客の後、12:00から同じ1時間の長さで移動する
+        assert_eq!(moved.start.unwrap().date_time, Some(start + Duration::hours(2)));
+        assert_eq!(moved.end.unwrap().date_time, Some(start + Duration::hours(3)));
+    }
+
+    #[tokio::test]
+    async fn test_reschedule_without_free_slot_leaves_event_unchanged() {
+        let start = Tokyo.with_ymd_and_hms(2025, 7, 1, 10, 0, 0).unwrap().with_timezone(&Utc);
+        let meeting = timed_event("定例", start, start + Duration::hours(1));
+        let blocked = timed_event("出張", start - Duration::days(1), start + Duration::days(3));
+        let backend = MockBackend { created: Mutex::new(vec![meeting.clone(), blocked]), ..Default::default() };
+
+        let moved = reschedule_to_next_free_slot(&backend, &meeting, start, 1, nine_to_six()).await.unwrap();
+
+        assert!(moved.is_none());
+        assert!(backend.patched.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reschedule_keeps_free_slot_and_stays_in_working_hours() {
+        let start = Tokyo.with_ymd_and_hms(2025, 7, 1, 10, 0, 0).unwrap().with_timezone(&Utc);
+        let meeting = timed_event("定例", start, start + Duration::hours(1));
+        let backend = MockBackend { created: Mutex::new(vec![meeting.clone()]), ..Default::default() };
+
+        // 今の時間帯が空いていれば更新しない
+        let kept = reschedule_to_next_free_slot(&backend, &meeting, start - Duration::hours(1), 7, nine_to_six())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(kept.start.unwrap().date_time, Some(start));
+        assert!(backend.patched.lock().unwrap().is_empty());
+
+        // 勤務時間外の予定は翌営業日の始業時刻へ移す（2025-07-04は金曜日）
+        let evening = Tokyo.with_ymd_and_hms(2025, 7, 4, 19, 0, 0).unwrap().with_timezone(&Utc);
+        let late = timed_event("振り返り", evening, evening + Duration::hours(1));
+        let backend = MockBackend { created: Mutex::new(vec![late.clone()]), ..Default::default() };
+        let moved = reschedule_to_next_free_slot(&backend, &late, evening, 7, nine_to_six())
+            .await
+            .unwrap()
+            .unwrap();
+        let monday = Tokyo.with_ymd_and_hms(2025, 7, 7, 9, 0, 0).unwrap().with_timezone(&Utc);
+        assert_eq!(moved.start.unwrap().date_time, Some(monday));
+        assert_eq!(backend.patched.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_utilization_sums_booked_time_against_working_hours() {
+        // 2025-06-30（月）〜07-06（日）の1週間
+        let at = |day, hour, minute| Tokyo.with_ymd_and_hms(2025, 6, day, hour, minute, 0).unwrap().with_timezone(&Utc);
+        let (week_start, _) = day_window(NaiveDate::from_ymd_opt(2025, 6, 30).unwrap());
+        let (_, week_end) = day_window(NaiveDate::from_ymd_opt(2025, 7, 6).unwrap());
+        let transparent = Event { transparency: Some("transparent".to_string()), ..timed_event("集中", at(30, 13, 0), at(30, 17, 0)) };
+        let events = [
+            timed_event("定例", at(30, 10, 0), at(30, 11, 0)),
+            // 定例と30分重なる分は二重に数えない
+            timed_event("レビュー", at(30, 10, 30), at(30, 12, 0)),
+            timed_event("1on1", at(30, 15, 0), at(30, 15, 30)),
+            transparent,
+        ];
+        let busy: Vec<_> = events.iter().filter_map(busy_interval).collect();
+        let working_hours = nine_to_six();
+
+        let utilization = compute_utilization(&busy, week_start, week_end, working_hours);
+
+        assert_eq!(utilization.booked_minutes, 150);
+        // 平日5日 × 9時間
+        assert_eq!(utilization.working_minutes, 5 * 9 * 60);
+        assert_eq!(format!("{:.1}", utilization.percent().unwrap()), "5.6");
+        assert_eq!(format_minutes(utilization.booked_minutes), "2時間30分");
+
+        // 週末だけの期間は勤務時間がないため割合を出さない
+        let (saturday, _) = day_window(NaiveDate::from_ymd_opt(2025, 7, 5).unwrap());
+        assert_eq!(compute_utilization(&busy, saturday, week_end, working_hours).percent(), None);
+    }
+
+    #[test]
+    fn test_address_location_gets_map_link() {
+        let address = "東京都千代田区丸の内1-9-1";
+        let url = map_url(address).unwrap();
+        assert_eq!(
+            url,
+            format!("https://www.google.com/maps/search/?api=1&query={}", urlencoding::encode(address))
+        );
+        assert!(map_url("1600 Amphitheatre Pkwy, Mountain View, CA").is_some());
+
+        // 会議室名やオンライン会議のURLは住所として扱わない
+        assert!(map_url("会議室A, 3F").is_none());
+        assert!(map_url("https://meet.google.com/abc-defg-hij").is_none());
+
+        assert_eq!(format_location(address, true), format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, address));
+        assert_eq!(format_location(address, false), address);
+        assert_eq!(format_location("会議室A", true), "会議室A");
+    }
+}
@@ -1,24 +1,60 @@
-use crate::calendar::CalendarService;
-use crate::config::{Config, ConfigManager};
-use crate::models::{Priority, Schedule};
+use crate::calendar::{
+    busy_interval, compute_utilization, day_window, fetch_with_cache, find_duplicate_events, find_query_match, format_attendee_availability, format_compact_event, format_event_count, format_location, format_markdown_table, format_minutes,
+    google_event_query_match,
+    group_events_by_day, parse_event_rows, sort_free_slots, unavailable_attendee_lines, upcoming_window, CalendarService, QueryMatch, SlotPreference, RSVP_RESPONSES,
+};
+use crate::config::{Config, ConfigManager, OsKeyring, SecretStore, GEMINI_API_KEY_ENTRY};
+use crate::models::{summarize_usage, Priority, Schedule, UsageRecord, PRIORITY_NAMES};
+use crate::scheduler::format_event_line;
 use crate::storage::Storage;
+use crate::webhook::notify_event_created;
 use anyhow::Result;
 use chrono_tz::Asia::Tokyo;
 use clap::{App, Arg, ArgMatches, SubCommand};
 use colored::*;
-use dialoguer::{Confirm, Select};
+use dialoguer::{Confirm, Input, Password, Select};
+use std::sync::Arc;
+use schedule_ai_agent::clock::Clock;
+use schedule_ai_agent::datetime::{normalize_japanese_time, parse_date, parse_datetime_with_zone, split_time_range};
+use schedule_ai_agent::output::{print_json, set_log_target, status, LogTarget};
 use schedule_ai_agent::GoogleCalendarClient;
 
 pub struct Cli {
     pub command: Option<String>,
     pub mock_llm: bool,
     pub verbose: bool,
+    pub no_pager: bool,
+    /// 保存済みの会話履歴を読み込まずに開始する
+    pub start_empty: bool,
+    pub log_output: Option<String>,
     pub matches: ArgMatches<'static>,
 }
 
 impl Cli {
     pub fn parse() -> Self {
-        let matches = App::new("schedule-ai")
+        let matches = Self::app().get_matches();
+
+        let command = matches.subcommand_name().map(|s| s.to_string());
+        let mock_llm = matches.is_present("mock-llm");
+        let verbose = matches.is_present("verbose");
+        let no_pager = matches.is_present("no-pager");
+        let start_empty = matches.is_present("start-empty");
+        let log_output = matches.value_of("log-output").map(|s| s.to_string());
+
+        Self {
+            command,
+            mock_llm,
+            verbose,
+            no_pager,
+            start_empty,
+            log_output,
+            matches,
+        }
+    }
+
+    /// コマンドライン引数の定義
+    fn app() -> App<'static, 'static> {
+        App::new("schedule-ai")
             .version("0.1.0")
             .about("AI-powered schedule management tool")
             .arg(
@@ -33,8 +69,42 @@ impl Cli {
                     .help("Enable verbose output")
                     .takes_value(false),
             )
+            .arg(
+                Arg::with_name("no-pager")
+                    .long("no-pager")
+                    .help("Print long event lists without pausing")
+                    .takes_value(false),
+            )
+            .arg(
+                Arg::with_name("start-empty")
+                    .long("start-empty")
+                    .help("Start the chat without loading the saved conversation history")
+                    .takes_value(false),
+            )
+            .arg(
+                Arg::with_name("log-output")
+                    .long("log-output")
+                    .value_name("TARGET")
+                    .help("Where to write status messages: stderr, stdout, or a file path")
+                    .takes_value(true),
+            )
             .subcommand(SubCommand::with_name("interactive").about("Start interactive mode"))
             .subcommand(SubCommand::with_name("tui").about("Start TUI chat mode"))
+            .subcommand(
+                SubCommand::with_name("chat")
+                    .about("Send one message to the agent and print the reply")
+                    .arg(
+                        Arg::with_name("from-stdin")
+                            .long("from-stdin")
+                            .help("Read the message from the first line of stdin")
+                            .required(true),
+                    )
+                    .arg(
+                        Arg::with_name("json")
+                            .long("json")
+                            .help("Print the message and reply as JSON"),
+                    ),
+            )
             .subcommand(
                 SubCommand::with_name("add")
                     .about("Add a new event")
@@ -53,16 +123,22 @@ impl Cli {
                     .arg(
                         Arg::with_name("start")
                             .long("start")
-                            .help("Start time (ISO 8601 format)")
+                            .help("Start time (ISO 8601 format), or a range like \"2025-07-01 10:00-11:00\"")
                             .takes_value(true)
                             .required(true),
                     )
                     .arg(
                         Arg::with_name("end")
                             .long("end")
-                            .help("End time (ISO 8601 format)")
+                            .help("End time (ISO 8601 format); optional when --start is a range")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("duration")
+                            .long("duration")
+                            .help("Duration in minutes, instead of --end")
                             .takes_value(true)
-                            .required(true),
+                            .conflicts_with("end"),
                     )
                     .arg(
                         Arg::with_name("location")
@@ -75,6 +151,33 @@ impl Cli {
                             .long("priority")
                             .help("Priority (low, medium, high, urgent)")
                             .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("calendar")
+                            .long("calendar")
+                            .help("Create the event in Google Calendar (default)")
+                            .takes_value(false),
+                    )
+                    .arg(
+                        Arg::with_name("local")
+                            .long("local")
+                            .help("Save the event only to local storage (offline)")
+                            .takes_value(false)
+                            .conflicts_with("calendar"),
+                    )
+                    .arg(
+                        Arg::with_name("mirror")
+                            .long("mirror")
+                            .help("Create the event in Google Calendar and keep a local copy")
+                            .takes_value(false)
+                            .conflicts_with("local")
+                            .conflicts_with("calendar"),
+                    )
+                    .arg(
+                        Arg::with_name("allow-past")
+                            .long("allow-past")
+                            .help("Create the event without confirmation even if it starts in the past")
+                            .takes_value(false),
                     ),
             )
             .subcommand(
@@ -97,19 +200,123 @@ impl Cli {
                             .long("limit")
                             .help("Limit number of events")
                             .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("details")
+                            .long("details")
+                            .help("Show the beginning of each event description")
+                            .takes_value(false),
+                    )
+                    .arg(
+                        Arg::with_name("format")
+                            .long("format")
+                            .help("Output format (md: Markdown table)")
+                            .takes_value(true)
+                            .possible_values(&["text", "md"])
+                            .default_value("text"),
+                    )
+                    .arg(
+                        Arg::with_name("count")
+                            .long("count")
+                            .help("Print only the number of events and the total booked time")
+                            .takes_value(false),
+                    )
+                    .arg(
+                        Arg::with_name("mine")
+                            .long("mine")
+                            .help("Show only events you organize")
+                            .takes_value(false),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("search")
+                    .about("Search events")
+                    .arg(
+                        Arg::with_name("query")
+                            .help("Search query")
+                            .required(true)
+                            .index(1),
+                    )
+                    .arg(
+                        Arg::with_name("days")
+                            .long("days")
+                            .help("Number of days to search ahead in Google Calendar")
+                            .takes_value(true)
+                            .default_value("30"),
+                    )
+                    .arg(
+                        Arg::with_name("compact")
+                            .long("compact")
+                            .help("Show one line per event")
+                            .takes_value(false),
+                    )
+                    .arg(
+                        Arg::with_name("local")
+                            .long("local")
+                            .help("Search only the local schedule")
+                            .takes_value(false),
+                    )
+                    .arg(
+                        Arg::with_name("count")
+                            .long("count")
+                            .help("Print only the number of matching events")
+                            .takes_value(false),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("new")
+                    .about("Create an event step by step without the LLM")
+                    .arg(
+                        Arg::with_name("local")
+                            .long("local")
+                            .help("Save the event only to local storage (offline)")
+                            .takes_value(false),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("complete")
+                    .about("Mark a local event as completed")
+                    .arg(
+                        Arg::with_name("id")
+                            .help("Event ID (a unique prefix is enough)")
+                            .required(true)
+                            .index(1),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("stats")
+                    .about("Show statistics")
+                    .arg(
+                        Arg::with_name("google")
+                            .long("google")
+                            .help("Compute this week's booked hours from Google Calendar"),
                     ),
             )
             .subcommand(
-                SubCommand::with_name("search").about("Search events").arg(
-                    Arg::with_name("query")
-                        .help("Search query")
-                        .required(true)
-                        .index(1),
-                ),
+                SubCommand::with_name("metrics")
+                    .about("Summarize the usage metrics log (app.usage_metrics)")
+                    .arg(
+                        Arg::with_name("days")
+                            .long("days")
+                            .help("Only include requests from the last N days")
+                            .takes_value(true),
+                    ),
             )
-            .subcommand(SubCommand::with_name("stats").about("Show statistics"))
             .subcommand(SubCommand::with_name("backup").about("Backup schedule"))
-            .subcommand(SubCommand::with_name("restore").about("Restore from backup"))
+            .subcommand(
+                SubCommand::with_name("doctor")
+                    .about("Check configuration, LLM, Google Calendar and data directories"),
+            )
+            .subcommand(
+                SubCommand::with_name("restore")
+                    .about("Restore from backup")
+                    .arg(
+                        Arg::with_name("dry-run")
+                            .long("dry-run")
+                            .help("Show what would change without restoring")
+                            .takes_value(false),
+                    ),
+            )
             .subcommand(
                 SubCommand::with_name("debug")
                     .about("Debug mode control")
@@ -130,13 +337,17 @@ impl Cli {
                 SubCommand::with_name("conversation")
                     .about("Conversation history management")
                     .subcommand(
-                        SubCommand::with_name("show").about("Show conversation history"),
+                        SubCommand::with_name("show")
+                            .about("Show conversation history")
+                            .arg(conversation_format_arg()),
                     )
                     .subcommand(
                         SubCommand::with_name("clear").about("Clear conversation history"),
                     )
                     .subcommand(
-                        SubCommand::with_name("summary").about("Show conversation summary"),
+                        SubCommand::with_name("summary")
+                            .about("Show conversation summary")
+                            .arg(conversation_format_arg()),
                     ),
             )
             .subcommand(
@@ -157,6 +368,12 @@ impl Cli {
                             .help("Import file path")
                             .required(true)
                             .index(1),
+                    )
+                    .arg(
+                        Arg::with_name("dry-run")
+                            .long("dry-run")
+                            .help("Show what would change without importing")
+                            .takes_value(false),
                     ),
             )
             .subcommand(
@@ -165,10 +382,33 @@ impl Cli {
                     .subcommand(
                         SubCommand::with_name("init").about("Initialize configuration files"),
                     )
+                    .subcommand(
+                        SubCommand::with_name("examples")
+                            .about("Regenerate the example config and secret files without touching config.toml"),
+                    )
                     .subcommand(SubCommand::with_name("show").about("Show current configuration"))
                     .subcommand(SubCommand::with_name("path").about("Show configuration file path"))
                     .subcommand(
                         SubCommand::with_name("edit").about("Open configuration file in editor"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("migrate")
+                            .about("Add newly available settings to the existing configuration file"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("set-key")
+                            .about("Store the Gemini API key in the OS keyring"),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("set-priority")
+                            .about("Set the priority given to new events created without one")
+                            .arg(
+                                Arg::with_name("priority")
+                                    .help("Default priority")
+                                    .required(true)
+                                    .possible_values(&PRIORITY_NAMES)
+                                    .index(1),
+                            ),
                     ),
             )
             .subcommand(
@@ -179,11 +419,46 @@ impl Cli {
                     )
                     .subcommand(
                         SubCommand::with_name("today")
-                            .about("Show today's events from Google Calendar"),
+                            .about("Show today's events from Google Calendar")
+                            .arg(
+                                Arg::with_name("remaining")
+                                    .long("remaining")
+                                    .help("Show only events that have not ended yet")
+                                    .takes_value(false),
+                            )
+                            .arg(
+                                Arg::with_name("compact")
+                                    .long("compact")
+                                    .help("Show one line per event")
+                                    .takes_value(false),
+                            ),
                     )
                     .subcommand(
                         SubCommand::with_name("week")
-                            .about("Show this week's events from Google Calendar"),
+                            .about("Show this week's events from Google Calendar")
+                            .arg(
+                                Arg::with_name("compact")
+                                    .long("compact")
+                                    .help("Show one line per event")
+                                    .takes_value(false),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("agenda")
+                            .about("Show upcoming events grouped by day")
+                            .arg(
+                                Arg::with_name("days")
+                                    .long("days")
+                                    .help("Number of days to show, starting today")
+                                    .takes_value(true)
+                                    .default_value("7"),
+                            )
+                            .arg(
+                                Arg::with_name("compact")
+                                    .long("compact")
+                                    .help("Show one line per event")
+                                    .takes_value(false),
+                            ),
                     )
                     .subcommand(
                         SubCommand::with_name("sync").about("Sync events with Google Calendar"),
@@ -207,9 +482,15 @@ impl Cli {
                             .arg(
                                 Arg::with_name("end")
                                     .long("end")
-                                    .help("End time (ISO 8601 format)")
+                                    .help("End time (ISO 8601 format); optional when --start is a range")
+                                    .takes_value(true),
+                            )
+                            .arg(
+                                Arg::with_name("duration")
+                                    .long("duration")
+                                    .help("Duration in minutes, instead of --end")
                                     .takes_value(true)
-                                    .required(true),
+                                    .conflicts_with("end"),
                             )
                             .arg(
                                 Arg::with_name("description")
@@ -222,6 +503,192 @@ impl Cli {
                                     .long("location")
                                     .help("Location")
                                     .takes_value(true),
+                            )
+                            .arg(
+                                Arg::with_name("attendees")
+                                    .long("attendees")
+                                    .help("Comma-separated attendee emails; their availability is checked before creating")
+                                    .takes_value(true),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("shift-rest")
+                            .about("Shift today's remaining events")
+                            .arg(
+                                Arg::with_name("by")
+                                    .long("by")
+                                    .help("Shift amount (e.g. +1h, 30m, -15m)")
+                                    .takes_value(true)
+                                    .allow_hyphen_values(true)
+                                    .default_value("+1h"),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("delete-range")
+                            .about("Delete all events between two dates after confirmation")
+                            .arg(
+                                Arg::with_name("since")
+                                    .long("since")
+                                    .help("First day (e.g. 2025-08-10, 明日)")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("until")
+                                    .long("until")
+                                    .help("Last day, inclusive")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("yes")
+                                    .long("yes")
+                                    .short("y")
+                                    .help("Delete without asking for confirmation")
+                                    .takes_value(false),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("dedupe")
+                            .about("Find events with the same title and time and delete the extra copies after confirmation")
+                            .arg(
+                                Arg::with_name("days")
+                                    .long("days")
+                                    .help("Number of days to check, starting today")
+                                    .takes_value(true)
+                                    .default_value("30"),
+                            )
+                            .arg(
+                                Arg::with_name("yes")
+                                    .long("yes")
+                                    .short("y")
+                                    .help("Delete duplicates without asking for confirmation")
+                                    .takes_value(false),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("import-events")
+                            .about("Create events from a CSV or JSON lines file")
+                            .arg(
+                                Arg::with_name("file")
+                                    .help("File with title,start,end,description,location (.csv or JSON lines)")
+                                    .required(true)
+                                    .index(1),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("duplicate")
+                            .about("Copy an existing event to a new time")
+                            .arg(
+                                Arg::with_name("id")
+                                    .long("id")
+                                    .help("ID of the event to copy")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("start")
+                                    .long("start")
+                                    .help("New start time")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("end")
+                                    .long("end")
+                                    .help("New end time (defaults to the original duration)")
+                                    .takes_value(true),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("rsvp")
+                            .about("Respond to an event invitation")
+                            .arg(
+                                Arg::with_name("id")
+                                    .long("id")
+                                    .help("Event ID")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("response")
+                                    .long("response")
+                                    .help("Response to the invitation")
+                                    .takes_value(true)
+                                    .possible_values(&RSVP_RESPONSES)
+                                    .required(true),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("ooo")
+                            .about("Create an out-of-office event that auto-declines invitations")
+                            .arg(
+                                Arg::with_name("start")
+                                    .long("start")
+                                    .help("Start time")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("end")
+                                    .long("end")
+                                    .help("End time")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("message")
+                                    .long("message")
+                                    .help("Message sent when declining invitations")
+                                    .takes_value(true),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("next")
+                            .about("Show the next occurrence of a named event")
+                            .arg(
+                                Arg::with_name("query")
+                                    .help("Event title keyword")
+                                    .required(true)
+                                    .index(1),
+                            )
+                            .arg(
+                                Arg::with_name("days")
+                                    .long("days")
+                                    .help("Number of days to search ahead")
+                                    .takes_value(true)
+                                    .default_value("90"),
+                            ),
+                    )
+                    .subcommand(
+                        SubCommand::with_name("availability")
+                            .about("Check whether attendees are free at a proposed time")
+                            .arg(
+                                Arg::with_name("attendees")
+                                    .long("attendees")
+                                    .help("Comma-separated attendee emails")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("start")
+                                    .long("start")
+                                    .help("Start time (ISO 8601 format)")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("end")
+                                    .long("end")
+                                    .help("End time (ISO 8601 format); optional when --start is a range")
+                                    .takes_value(true),
+                            )
+                            .arg(
+                                Arg::with_name("duration")
+                                    .long("duration")
+                                    .help("Duration in minutes, instead of --end")
+                                    .takes_value(true)
+                                    .conflicts_with("end"),
                             ),
                     )
                     .subcommand(
@@ -239,20 +706,209 @@ impl Cli {
                                     .help("Number of days to search ahead")
                                     .takes_value(true)
                                     .default_value("7"),
+                            )
+                            .arg(
+                                Arg::with_name("date")
+                                    .long("date")
+                                    .help("Search only this day (e.g. 2025-07-02, 7/2, 明日); overrides --days")
+                                    .takes_value(true),
+                            )
+                            .arg(
+                                Arg::with_name("prefer")
+                                    .long("prefer")
+                                    .help("Order slots by preference instead of chronologically")
+                                    .takes_value(true)
+                                    .possible_values(&["morning", "afternoon", "longest"]),
                             ),
-                    ),
-            )
-            .get_matches();
-
-        let command = matches.subcommand_name().map(|s| s.to_string());
-        let mock_llm = matches.is_present("mock-llm");
-        let verbose = matches.is_present("verbose");
+                    )
+                    .subcommand(
+                        SubCommand::with_name("freebusy")
+                            .about("Check whether you are free in a time range and list the events in the way")
+                            .arg(
+                                Arg::with_name("start")
+                                    .long("start")
+                                    .help("Start time (ISO 8601 format)")
+                                    .takes_value(true)
+                                    .required(true),
+                            )
+                            .arg(
+                                Arg::with_name("end")
+                                    .long("end")
+                                    .help("End time (ISO 8601 format); optional when --start is a range")
+                                    .takes_value(true),
+                            )
+                            .arg(
+                                Arg::with_name("duration")
+                                    .long("duration")
+                                    .help("Duration in minutes, instead of --end")
+                                    .takes_value(true)
+                                    .conflicts_with("end"),
+                            ),
+                    ),
+            )
+    }
 
-        Self {
-            command,
-            mock_llm,
-            verbose,
-            matches,
+    /// OAuth認証や確認プロンプトなど、ユーザーの操作を待つことがあるコマンドか
+    pub fn waits_for_user(&self) -> bool {
+        let Some(command) = self.command.as_deref() else {
+            return false;
+        };
+        let subcommand = self
+            .matches
+            .subcommand_matches(command)
+            .and_then(|m| m.subcommand_name());
+        USER_WAITING_COMMANDS
+            .iter()
+            .any(|(name, sub)| *name == command && (sub.is_none() || *sub == subcommand))
+    }
+}
+
+/// コマンド全体の時間制限の対象外とするコマンド（サブコマンドがNoneならすべて）
+const USER_WAITING_COMMANDS: [(&str, Option<&str>); 10] = [
+    ("add", None),
+    ("new", None),
+    ("complete", None),
+    ("restore", None),
+    ("import", None),
+    ("calendar", Some("auth")),
+    ("calendar", Some("create")),
+    ("calendar", Some("delete-range")),
+    ("calendar", Some("dedupe")),
+    ("config", Some("set-key")),
+];
+
+/// 対話で1行ずつ入力を受け取る（テストでは入力を差し替える）
+trait LinePrompt {
+    fn ask(&mut self, prompt: &str) -> Result<String>;
+}
+
+/// 端末から入力を受け取る
+struct DialoguerPrompt;
+
+impl LinePrompt for DialoguerPrompt {
+    fn ask(&mut self, prompt: &str) -> Result<String> {
+        Ok(Input::<String>::new()
+            .with_prompt(prompt)
+            .allow_empty(true)
+            .interact_text()?)
+    }
+}
+
+/// 対話で入力された予定の内容
+#[derive(Debug, Clone, PartialEq)]
+struct GuidedEvent {
+    title: String,
+    start: String,
+    end: String,
+    location: Option<String>,
+    priority: Option<String>,
+    description: Option<String>,
+}
+
+/// タイトル・開始・終了・場所・優先度・説明の順に尋ねる
+///
+/// 不正な入力は理由を表示して同じ項目を尋ね直す。任意項目は空欄ならスキップする。
+fn prompt_guided_event<P, F>(prompt: &mut P, parse_datetime: F) -> Result<GuidedEvent>
+where
+    P: LinePrompt,
+    F: Fn(&str) -> Result<chrono::DateTime<chrono::Utc>>,
+{
+    fn ask_until<P: LinePrompt, T>(
+        prompt: &mut P,
+        label: &str,
+        mut accept: impl FnMut(&str) -> Result<T>,
+    ) -> Result<T> {
+        loop {
+            let answer = prompt.ask(label)?;
+            match accept(answer.trim()) {
+                Ok(value) => return Ok(value),
+                Err(e) => println!("{} {}", "✗".red(), e),
+            }
+        }
+    }
+    let optional = |value: &str| (!value.is_empty()).then(|| value.to_string());
+
+    let title = ask_until(prompt, "タイトル", |value| {
+        optional(value).ok_or_else(|| anyhow::anyhow!("タイトルを入力してください"))
+    })?;
+    let start = ask_until(prompt, "開始日時（例: 2025-07-01 15:00）", &parse_datetime)?;
+    let end = ask_until(prompt, "終了日時", |value| {
+        let end = parse_datetime(value)?;
+        if end <= start {
+            return Err(anyhow::anyhow!("終了日時は開始日時より後にしてください"));
+        }
+        Ok(end)
+    })?;
+    let location = optional(prompt.ask("場所（任意）")?.trim());
+    let priority = ask_until(prompt, "優先度（low / medium / high / urgent、任意）", |value| {
+        match value {
+            "" => Ok(None),
+            "low" | "medium" | "high" | "urgent" => Ok(Some(value.to_string())),
+            _ => Err(anyhow::anyhow!("low / medium / high / urgent のいずれかを入力してください")),
+        }
+    })?;
+    let description = optional(prompt.ask("説明（任意）")?.trim());
+
+    Ok(GuidedEvent {
+        title,
+        start: start.to_rfc3339(),
+        end: end.to_rfc3339(),
+        location,
+        priority,
+        description,
+    })
+}
+
+/// `add`で予定を書き込む先
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AddTarget {
+    /// Google Calendarのみ（既定）
+    Calendar,
+    /// ローカルのみ（オフライン）
+    Local,
+    /// Google Calendarに作成し、ローカルにも保存
+    Mirror,
+}
+
+/// 予定の書き込み先を決める（明示した`--local`・`--calendar`・`--mirror`を設定の`mirror_by_default`より優先する）
+fn add_target(matches: &ArgMatches, mirror_by_default: bool) -> AddTarget {
+    if matches.is_present("local") {
+        AddTarget::Local
+    } else if matches.is_present("calendar") {
+        AddTarget::Calendar
+    } else if matches.is_present("mirror") || mirror_by_default {
+        AddTarget::Mirror
+    } else {
+        AddTarget::Calendar
+    }
+}
+
+/// `conversation show`・`conversation summary`の出力形式の指定
+fn conversation_format_arg() -> Arg<'static, 'static> {
+    Arg::with_name("format")
+        .long("format")
+        .help("Output format (json: machine-readable)")
+        .takes_value(true)
+        .possible_values(&["text", "json"])
+        .default_value("text")
+}
+
+/// `list`の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListFormat {
+    /// 番号付きの一覧（既定）
+    Text,
+    /// Markdownの表
+    Markdown,
+    /// 件数と合計時間のみ
+    Count,
+}
+
+impl ListFormat {
+    fn parse(value: &str) -> Self {
+        match value {
+            "md" => ListFormat::Markdown,
+            _ => ListFormat::Text,
         }
     }
 }
@@ -265,11 +921,20 @@ pub struct CliApp {
     calendar_service: Option<CalendarService>,
     #[allow(dead_code)]
     verbose: bool,
+    paging: bool,
+    /// Google Calendarの予定を1行ずつ表示する
+    compact: bool,
+    clock: Arc<dyn Clock>,
 }
 
 impl CliApp {
     // === ヘルパーメソッド ===
 
+    /// 予定キャッシュの保存先（設定で無効な場合はNone）
+    fn event_cache(&self) -> Option<&Storage> {
+        self.config.app.event_cache.unwrap_or(true).then_some(&self.storage)
+    }
+
     /// Google Calendar認証をチェックし、必要に応じて認証を実行
     async fn ensure_calendar_auth(&mut self) -> Result<()> {
         if self.calendar_service.is_none() {
@@ -280,17 +945,90 @@ impl CliApp {
 
     /// 成功メッセージを表示
     fn print_success(&self, message: &str) {
-        println!("{}", message.green());
+        status(&message.green().to_string());
     }
 
     /// エラーメッセージを表示
     fn print_error(&self, prefix: &str, error: &dyn std::fmt::Display) {
-        println!("{}: {}", prefix.red(), error);
+        status(&format!("{}: {}", prefix.red(), error));
     }
 
     /// 警告メッセージを表示
     fn print_warning(&self, message: &str) {
-        println!("{}", message.yellow());
+        status(&message.yellow().to_string());
+    }
+
+    /// 設定で有効な場合、変更前にスケジュールを自動バックアップ
+    fn auto_backup(&self) {
+        match self.storage.backup_before_mutation(&self.config.app) {
+            Ok(Some(backup_path)) => {
+                status(&format!("自動バックアップを作成しました: {}", backup_path.display().to_string().cyan()));
+            }
+            Ok(None) => {}
+            Err(e) => self.print_warning(&format!("自動バックアップに失敗しました: {}", e)),
+        }
+    }
+
+    /// `--local`・`--calendar`・`--mirror`（または設定）から予定の書き込み先を決める
+    fn add_target(&self, matches: &ArgMatches) -> AddTarget {
+        add_target(matches, self.config.app.mirror_local_events.unwrap_or(false))
+    }
+
+    /// 項目を1つずつ尋ねて予定を作成する（LLMを使わない）
+    async fn new_event_command(&mut self, target: AddTarget) -> Result<()> {
+        println!("{}", "=== 予定の作成（空欄で任意項目をスキップ） ===".bold().blue());
+        let event = prompt_guided_event(&mut DialoguerPrompt, |value| Ok(self.parse_datetime(value)?))?;
+        if !self.confirm_past_start(&event.start)? {
+            println!("{}", "予定の作成を取りやめました。".yellow());
+            return Ok(());
+        }
+        self.add_event_command(
+            event.title,
+            event.description,
+            event.start,
+            event.end,
+            event.location,
+            event.priority,
+            target,
+        )
+        .await
+    }
+
+    /// `--start`・`--end`・`--duration`（または設定の既定の長さ）から開始・終了時刻を決める
+    fn resolve_event_times(&self, matches: &ArgMatches) -> Result<(String, String)> {
+        let duration = match matches.value_of("duration") {
+            Some(value) => Some(
+                value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+                    .ok_or_else(|| anyhow::anyhow!("無効な時間です: {}", value))?,
+            ),
+            None => self.config.app.default_event_minutes,
+        };
+        resolve_start_end(
+            matches.value_of("start").unwrap(),
+            matches.value_of("end"),
+            duration.map(chrono::Duration::minutes),
+            self.clock.now().with_timezone(&Tokyo).date_naive(),
+            |value| Ok(self.parse_datetime(value)?),
+        )
+    }
+
+    /// 開始時刻が過去なら作成してよいか確認する（過去でなければ確認せずtrue）
+    fn confirm_past_start(&self, start: &str) -> Result<bool> {
+        let start_time = self.parse_datetime(start)?;
+        if start_time >= self.clock.now() {
+            return Ok(true);
+        }
+        self.print_warning(&format!(
+            "開始時刻 {} は過去の日時です（年や日付の入力ミスではありませんか？）",
+            self.config.app.format_datetime(start_time.with_timezone(&Tokyo).naive_local())
+        ));
+        Ok(Confirm::new()
+            .with_prompt("このまま作成しますか？")
+            .default(false)
+            .interact()?)
     }
 
     /// 日時解析のヘルパー関数
@@ -300,6 +1038,10 @@ impl CliApp {
     ) -> Result<chrono::DateTime<chrono::Utc>, crate::models::SchedulerError> {
         use chrono::TimeZone;
         use chrono_tz::Asia::Tokyo;
+
+        // 「午後3時」のような表現を先に「15:00」へ揃える
+        let normalized = normalize_japanese_time(datetime_str);
+        let datetime_str = normalized.as_str();
         
         // ISO 8601形式の解析を試行
         if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(datetime_str) {
@@ -320,6 +1062,11 @@ impl CliApp {
             }
         }
 
+        // 「2025-07-01 10:00 PST」のように末尾にタイムゾーンがあればその時差で解釈する
+        if let Some(dt) = parse_datetime_with_zone(datetime_str, self.clock.now()) {
+            return Ok(dt);
+        }
+
         // タイムゾーンなしの形式（日本時間として解釈）
         let formats = [
             "%Y-%m-%d %H:%M:%S",
@@ -357,14 +1104,29 @@ impl CliApp {
     }
 
     /// Google Calendarイベントを表示する共通メソッド
-    fn display_calendar_events(&self, events: &google_calendar3::api::Events, title: &str) {
+    ///
+    /// `query`を指定すると、各予定で検索語に一致した箇所も表示する。
+    fn display_calendar_events(&self, events: &google_calendar3::api::Events, title: &str, query: Option<&str>) {
         println!("{}", title.bold().blue());
         if let Some(items) = &events.items {
             if items.is_empty() {
                 self.print_warning("予定はありません。");
             } else {
+                let page_size = self.config.app.page_size.unwrap_or(10);
+                let paging = should_page(
+                    std::io::IsTerminal::is_terminal(&std::io::stdout()),
+                    self.paging,
+                    items.len(),
+                    page_size,
+                );
                 for (i, event) in items.iter().enumerate() {
+                    if paging && i > 0 && i % page_size == 0 && !wait_for_next_page(i, items.len()) {
+                        break;
+                    }
                     self.display_google_calendar_event(event, i + 1);
+                    if let Some(found) = query.and_then(|query| google_event_query_match(event, query)) {
+                        println!("{}", format_query_match(&found));
+                    }
                 }
             }
         } else {
@@ -372,7 +1134,9 @@ impl CliApp {
         }
     }
 
-    pub async fn new(verbose: bool) -> Result<Self> {
+    /// `read_only`が真の場合（--dry-runなど）は、壊れたファイルの退避やGoogle認証による
+    /// トークン保存といったファイルへの書き込みを行わない
+    pub async fn new(verbose: bool, log_output: Option<&str>, read_only: bool) -> Result<Self> {
         let storage = Storage::new()?;
         let mut local_schedule = Schedule::new();
 
@@ -380,23 +1144,31 @@ impl CliApp {
         let config_manager = ConfigManager::new()?;
         let config = config_manager.load_config()?;
 
+        // 状態表示の出力先（コマンドラインの指定を設定ファイルより優先）
+        if let Some(target) = log_output.or(config.app.log_output.as_deref()) {
+            set_log_target(LogTarget::parse(target));
+        }
+
         // 既存のスケジュールを読み込み
-        match storage.load_schedule() {
+        let loaded = if read_only { storage.peek_schedule() } else { storage.load_schedule() };
+        match loaded {
             Ok(schedule) => {
                 local_schedule = schedule;
                 if verbose {
-                    println!("{}", "スケジュールを読み込みました。".green());
+                    status(&"スケジュールを読み込みました。".green().to_string());
                 }
             }
             Err(e) => {
                 if verbose {
-                    println!("{}: {}", "警告".yellow(), e);
+                    status(&format!("{}: {}", "警告".yellow(), e));
                 }
             }
         }
 
         // Google Calendar初期化を試行
-        if let Some(ref google_config) = config.google_calendar {
+        if read_only {
+            None
+        } else if let Some(ref google_config) = config.google_calendar {
             match GoogleCalendarClient::new(
                 google_config
                     .client_secret_path
@@ -406,26 +1178,28 @@ impl CliApp {
                     .token_cache_path
                     .as_deref()
                     .unwrap_or("token_cache.json"),
+                google_config.auth_flow(),
+                google_config.open_browser(),
             )
             .await
             {
                 Ok(client) => {
                     if verbose {
-                        println!("{}", "Google Calendarに接続しました。".green());
+                        status(&"Google Calendarに接続しました。".green().to_string());
                     }
                     Some(client)
                 }
                 Err(e) => {
                     if verbose {
-                        println!("{}: {}", "Google Calendar接続エラー".yellow(), e);
-                        println!("{}", "ローカルスケジュールのみ使用します。".yellow());
+                        status(&format!("{}: {}", "Google Calendar接続エラー".yellow(), e));
+                        status(&"ローカルスケジュールのみ使用します。".yellow().to_string());
                     }
                     None
                 }
             }
         } else {
             if verbose {
-                println!("{}", "Google Calendar設定が見つかりません。".yellow());
+                status(&"Google Calendar設定が見つかりません。".yellow().to_string());
             }
             None
         };
@@ -433,46 +1207,145 @@ impl CliApp {
         Ok(Self {
             local_schedule,
             storage,
+            clock: config.app.clock(),
             config,
             config_manager,
             calendar_service: None, // 初期化時はNone、必要に応じて後で初期化
             verbose,
+            paging: true,
+            compact: false,
         })
     }
 
+    /// `cli`のコマンド全体に掛ける時間制限（ユーザーの操作を待つコマンドと、未設定・0なら制限なし）
+    pub fn command_timeout(&self, cli: &Cli) -> Option<std::time::Duration> {
+        if cli.waits_for_user() {
+            return None;
+        }
+        self.config.app.command_timeout()
+    }
+
     pub async fn run(&mut self, cli: Cli) -> Result<()> {
+        self.paging = !cli.no_pager;
+
         match cli.command.as_deref() {
+            Some("doctor") => {
+                // doctorコマンドはmain.rsで処理される
+                Err(anyhow::anyhow!("この処理はmain.rsで処理されるべきです"))
+            }
             Some("interactive") => {
                 // interactiveコマンドもmain.rsで処理される
                 Err(anyhow::anyhow!("この処理はmain.rsで処理されるべきです"))
             }
+            Some("chat") => {
+                // chatコマンドもmain.rsで処理される
+                Err(anyhow::anyhow!("この処理はmain.rsで処理されるべきです"))
+            }
             Some("add") => {
                 if let Some(add_matches) = cli.matches.subcommand_matches("add") {
                     let title = add_matches.value_of("title").unwrap().to_string();
                     let description = add_matches.value_of("description").map(|s| s.to_string());
-                    let start = add_matches.value_of("start").unwrap().to_string();
-                    let end = add_matches.value_of("end").unwrap().to_string();
+                    let (start, end) = self.resolve_event_times(add_matches)?;
+                    if !add_matches.is_present("allow-past") && !self.confirm_past_start(&start)? {
+                        println!("{}", "予定の作成を取りやめました。".yellow());
+                        return Ok(());
+                    }
                     let location = add_matches.value_of("location").map(|s| s.to_string());
                     let priority = add_matches.value_of("priority").map(|s| s.to_string());
-                    self.add_event_command(title, description, start, end, location, priority)
+                    let target = self.add_target(add_matches);
+                    self.add_event_command(title, description, start, end, location, priority, target)
+                        .await
                 } else {
                     Err(anyhow::anyhow!("Invalid add command"))
                 }
             }
+            Some("new") => {
+                if let Some(new_matches) = cli.matches.subcommand_matches("new") {
+                    let target = self.add_target(new_matches);
+                    self.new_event_command(target).await
+                } else {
+                    Err(anyhow::anyhow!("Invalid new command"))
+                }
+            }
             Some("list") => {
-                todo!("Googleカレンダーに対応させる")
+                if let Some(list_matches) = cli.matches.subcommand_matches("list") {
+                    let limit = match list_matches.value_of("limit") {
+                        Some(value) => value
+                            .parse::<usize>()
+                            .map_err(|_| anyhow::anyhow!("無効な件数です: {}", value))?,
+                        None => 50,
+                    };
+                    let details = list_matches.is_present("details")
+                        || self.config.app.list_show_description.unwrap_or(false);
+                    let format = if list_matches.is_present("count") {
+                        ListFormat::Count
+                    } else {
+                        ListFormat::parse(list_matches.value_of("format").unwrap_or("text"))
+                    };
+                    let mine = list_matches.is_present("mine");
+                    self.list_events_command(list_matches.is_present("today"), limit, details, format, mine)
+                        .await
+                } else {
+                    Err(anyhow::anyhow!("Invalid list command"))
+                }
             }
             Some("search") => {
                 if let Some(search_matches) = cli.matches.subcommand_matches("search") {
                     let query = search_matches.value_of("query").unwrap().to_string();
-                    self.search_events_command(query)
+                    self.compact = search_matches.is_present("compact");
+                    let count = search_matches.is_present("count");
+                    if search_matches.is_present("local") {
+                        self.search_events_command(query, count)
+                    } else {
+                        let days = search_matches
+                            .value_of("days")
+                            .unwrap()
+                            .parse::<i64>()
+                            .map_err(|_| anyhow::anyhow!("無効な日数です"))?;
+                        self.search_calendar_events_command(query, days, count).await
+                    }
                 } else {
                     Err(anyhow::anyhow!("Invalid search command"))
                 }
             }
-            Some("stats") => self.show_statistics(),
+            Some("complete") => {
+                if let Some(complete_matches) = cli.matches.subcommand_matches("complete") {
+                    let id = complete_matches.value_of("id").unwrap();
+                    self.complete_event_command(id)
+                } else {
+                    Err(anyhow::anyhow!("Invalid complete command"))
+                }
+            }
+            Some("stats") => {
+                let google = cli
+                    .matches
+                    .subcommand_matches("stats")
+                    .is_some_and(|stats_matches| stats_matches.is_present("google"));
+                self.show_statistics(google).await
+            }
+            Some("metrics") => {
+                let days = match cli
+                    .matches
+                    .subcommand_matches("metrics")
+                    .and_then(|metrics_matches| metrics_matches.value_of("days"))
+                {
+                    Some(value) => Some(
+                        value
+                            .parse::<i64>()
+                            .map_err(|_| anyhow::anyhow!("無効な日数です: {}", value))?,
+                    ),
+                    None => None,
+                };
+                self.show_usage_metrics(days)
+            }
             Some("backup") => self.backup_command(),
-            Some("restore") => self.restore_command(),
+            Some("restore") => {
+                let dry_run = cli
+                    .matches
+                    .subcommand_matches("restore")
+                    .is_some_and(|m| m.is_present("dry-run"));
+                self.restore_command(dry_run)
+            }
             Some("debug") => {
                 if let Some(debug_matches) = cli.matches.subcommand_matches("debug") {
                     match debug_matches.subcommand() {
@@ -519,9 +1392,13 @@ impl CliApp {
             Some("conversation") => {
                 if let Some(conversation_matches) = cli.matches.subcommand_matches("conversation") {
                     match conversation_matches.subcommand() {
-                        ("show", _) => self.show_conversation_history(),
+                        ("show", Some(show_matches)) => {
+                            self.show_conversation_history(show_matches.value_of("format") == Some("json"))
+                        }
                         ("clear", _) => self.clear_conversation_history(),
-                        ("summary", _) => self.show_conversation_summary(),
+                        ("summary", Some(summary_matches)) => {
+                            self.show_conversation_summary(summary_matches.value_of("format") == Some("json"))
+                        }
                         _ => {
                             println!("利用可能な会話履歴コマンド:");
                             println!("  show    - 会話履歴を表示");
@@ -549,7 +1426,7 @@ impl CliApp {
             Some("import") => {
                 if let Some(import_matches) = cli.matches.subcommand_matches("import") {
                     let path = import_matches.value_of("path").unwrap().to_string();
-                    self.import_command(path)
+                    self.import_command(path, import_matches.is_present("dry-run"))
                 } else {
                     Err(anyhow::anyhow!("Invalid import command"))
                 }
@@ -558,9 +1435,16 @@ impl CliApp {
                 if let Some(config_matches) = cli.matches.subcommand_matches("config") {
                     match config_matches.subcommand() {
                         ("init", _) => self.config_init_command(),
+                        ("examples", _) => self.config_examples_command(),
                         ("show", _) => self.config_show_command(),
                         ("path", _) => self.config_path_command(),
                         ("edit", _) => self.config_show_command(),
+                        ("migrate", _) => self.config_migrate_command(),
+                        ("set-key", _) => self.config_set_key_command(),
+                        ("set-priority", Some(priority_matches)) => {
+                            let priority = priority_matches.value_of("priority").unwrap().to_string();
+                            self.config_set_priority_command(priority)
+                        }
                         _ => self.config_show_command(),
                     }
                 } else {
@@ -571,21 +1455,111 @@ impl CliApp {
                 if let Some(calendar_matches) = cli.matches.subcommand_matches("calendar") {
                     match calendar_matches.subcommand() {
                         ("auth", _) => self.calendar_auth_command().await,
-                        ("today", _) => self.calendar_today_command().await,
-                        ("week", _) => self.calendar_week_command().await,
+                        ("today", Some(today_matches)) => {
+                            self.compact = today_matches.is_present("compact");
+                            self.calendar_today_command(today_matches.is_present("remaining")).await
+                        }
+                        ("week", Some(week_matches)) => {
+                            self.compact = week_matches.is_present("compact");
+                            self.calendar_week_command().await
+                        }
+                        ("agenda", Some(agenda_matches)) => {
+                            self.compact = agenda_matches.is_present("compact");
+                            let days: i64 = agenda_matches
+                                .value_of("days")
+                                .unwrap()
+                                .parse()
+                                .map_err(|_| anyhow::anyhow!("--days には日数を指定してください"))?;
+                            self.calendar_agenda_command(days.max(1)).await
+                        }
                         ("sync", _) => self.calendar_sync_command().await,
                         ("create", Some(create_matches)) => {
                             let title = create_matches.value_of("title").unwrap().to_string();
-                            let start = create_matches.value_of("start").unwrap().to_string();
-                            let end = create_matches.value_of("end").unwrap().to_string();
+                            let (start, end) = self.resolve_event_times(create_matches)?;
                             let description = create_matches
                                 .value_of("description")
                                 .map(|s| s.to_string());
                             let location =
                                 create_matches.value_of("location").map(|s| s.to_string());
-                            self.calendar_create_command(title, start, end, description, location)
+                            let attendees: Vec<String> = create_matches
+                                .value_of("attendees")
+                                .unwrap_or_default()
+                                .split(',')
+                                .map(|email| email.trim().to_string())
+                                .filter(|email| !email.is_empty())
+                                .collect();
+                            self.calendar_create_command(title, start, end, description, location, attendees)
+                                .await
+                        }
+                        ("shift-rest", Some(shift_matches)) => {
+                            let delta = parse_shift_duration(shift_matches.value_of("by").unwrap())?;
+                            self.calendar_shift_rest_command(delta).await
+                        }
+                        ("delete-range", Some(delete_matches)) => {
+                            let today = self.clock.now().with_timezone(&Tokyo).date_naive();
+                            let date = |name: &str| {
+                                let value = delete_matches.value_of(name).unwrap();
+                                parse_date(value, today).ok_or_else(|| anyhow::anyhow!("無効な日付です: {}", value))
+                            };
+                            let (since, until) = (date("since")?, date("until")?);
+                            if until < since {
+                                return Err(anyhow::anyhow!("--until は --since 以降の日付を指定してください"));
+                            }
+                            self.calendar_delete_range_command(since, until, delete_matches.is_present("yes"))
                                 .await
                         }
+                        ("dedupe", Some(dedupe_matches)) => {
+                            let value = dedupe_matches.value_of("days").unwrap();
+                            let days: i64 = value
+                                .parse()
+                                .map_err(|_| anyhow::anyhow!("無効な日数です: {}", value))?;
+                            self.calendar_dedupe_command(days.max(1), dedupe_matches.is_present("yes"))
+                                .await
+                        }
+                        ("import-events", Some(import_matches)) => {
+                            let file = import_matches.value_of("file").unwrap().to_string();
+                            self.calendar_import_events_command(file).await
+                        }
+                        ("duplicate", Some(duplicate_matches)) => {
+                            let event_id = duplicate_matches.value_of("id").unwrap().to_string();
+                            let start = self.parse_datetime(duplicate_matches.value_of("start").unwrap())?;
+                            let end = match duplicate_matches.value_of("end") {
+                                Some(value) => Some(self.parse_datetime(value)?),
+                                None => None,
+                            };
+                            self.calendar_duplicate_command(event_id, start, end).await
+                        }
+                        ("rsvp", Some(rsvp_matches)) => {
+                            let event_id = rsvp_matches.value_of("id").unwrap().to_string();
+                            let response = rsvp_matches.value_of("response").unwrap().to_string();
+                            self.calendar_rsvp_command(event_id, response).await
+                        }
+                        ("ooo", Some(ooo_matches)) => {
+                            let start = self.parse_datetime(ooo_matches.value_of("start").unwrap())?;
+                            let end = self.parse_datetime(ooo_matches.value_of("end").unwrap())?;
+                            let message = ooo_matches.value_of("message").map(|s| s.to_string());
+                            self.calendar_ooo_command(start, end, message).await
+                        }
+                        ("next", Some(next_matches)) => {
+                            let query = next_matches.value_of("query").unwrap().to_string();
+                            let days = next_matches
+                                .value_of("days")
+                                .unwrap()
+                                .parse::<i64>()
+                                .map_err(|_| anyhow::anyhow!("無効な日数です"))?;
+                            self.calendar_next_command(query, days).await
+                        }
+                        ("availability", Some(availability_matches)) => {
+                            let attendees: Vec<String> = availability_matches
+                                .value_of("attendees")
+                                .unwrap()
+                                .split(',')
+                                .map(|email| email.trim().to_string())
+                                .filter(|email| !email.is_empty())
+                                .collect();
+                            let (start, end) = self.resolve_event_times(availability_matches)?;
+                            self.calendar_availability_command(attendees, start, end).await
+                        }
                         ("find-free", Some(free_matches)) => {
                             let duration = free_matches
                                 .value_of("duration")
@@ -597,7 +1571,24 @@ impl CliApp {
                                 .unwrap()
                                 .parse::<i64>()
                                 .unwrap_or(7);
-                            self.calendar_find_free_command(duration, days).await
+                            let today = self.clock.now().with_timezone(&Tokyo).date_naive();
+                            let date = match free_matches.value_of("date") {
+                                Some(value) => Some(
+                                    parse_date(value, today)
+                                        .ok_or_else(|| anyhow::anyhow!("無効な日付です: {}", value))?,
+                                ),
+                                None => None,
+                            };
+                            let prefer = free_matches.value_of("prefer").and_then(SlotPreference::parse);
+                            self.calendar_find_free_command(duration, days, date, prefer).await
+                        }
+                        ("freebusy", Some(freebusy_matches)) => {
+                            let (start, end) = self.resolve_event_times(freebusy_matches)?;
+                            let (start, end) = (self.parse_datetime(&start)?, self.parse_datetime(&end)?);
+                            if end <= start {
+                                return Err(anyhow::anyhow!("終了時刻は開始時刻より後である必要があります"));
+                            }
+                            self.calendar_freebusy_command(start, end).await
                         }
                         _ => {
                             println!("利用可能なカレンダーコマンド:");
@@ -606,7 +1597,17 @@ impl CliApp {
                             println!("  week      - 今週の予定を表示");
                             println!("  sync      - カレンダーと同期");
                             println!("  create    - イベントを作成");
+                            println!("  shift-rest - 今日の残りの予定をずらす");
+                            println!("  delete-range - 期間内の予定をまとめて削除");
+                            println!("  dedupe    - 重複した予定を探して削除");
+                            println!("  next      - 次回の予定を表示");
+                            println!("  ooo       - 不在の予定を作成");
+                            println!("  duplicate - 予定を別の日時に複製");
+                            println!("  rsvp      - 招待に返答");
+                            println!("  import-events - ファイルから予定を一括作成");
+                            println!("  availability - 参加者の空き状況を確認");
                             println!("  find-free - 空き時間を検索");
+                            println!("  freebusy  - 指定した時間が空いているか確認");
                             Ok(())
                         }
                     }
@@ -617,7 +1618,17 @@ impl CliApp {
                     println!("  week      - 今週の予定を表示");
                     println!("  sync      - カレンダーと同期");
                     println!("  create    - イベントを作成");
+                    println!("  shift-rest - 今日の残りの予定をずらす");
+                    println!("  delete-range - 期間内の予定をまとめて削除");
+                    println!("  dedupe    - 重複した予定を探して削除");
+                    println!("  next      - 次回の予定を表示");
+                    println!("  ooo       - 不在の予定を作成");
+                    println!("  duplicate - 予定を別の日時に複製");
+                    println!("  rsvp      - 招待に返答");
+                    println!("  import-events - ファイルから予定を一括作成");
+                    println!("  availability - 参加者の空き状況を確認");
                     println!("  find-free - 空き時間を検索");
+                    println!("  freebusy  - 指定した時間が空いているか確認");
                     Ok(())
                 }
             }
@@ -647,9 +1658,20 @@ impl CliApp {
             .and_then(|gc| gc.token_cache_path.as_ref())
             .ok_or_else(|| anyhow::anyhow!("token_cache_pathが設定されていません"))?;
 
-        match CalendarService::new(client_secret_path, token_cache_path).await {
+        let auth_flow = self.config.google_calendar.as_ref().map(|gc| gc.auth_flow()).unwrap_or_default();
+        let open_browser = self.config.google_calendar.as_ref().is_none_or(|gc| gc.open_browser());
+        let service = match CalendarService::new(client_secret_path, token_cache_path, auth_flow, open_browser).await {
+            Ok(service) => service.verify_access().await.map(|_| service),
+            Err(e) => Err(e),
+        };
+        match service {
             Ok(service) => {
-                self.calendar_service = Some(service);
+                self.calendar_service = Some(
+                    service
+                        .with_clock(self.clock.clone())
+                        .with_default_reminders(self.config.calendar.default_reminders.as_deref())
+                        .with_request_timeout(self.config.app.request_timeout()),
+                );
                 println!("{}", "Google Calendarの認証が完了しました！".green());
             }
             Err(e) => {
@@ -661,18 +1683,35 @@ impl CliApp {
         Ok(())
     }
     
-    /// 今日の予定を表示
-    async fn calendar_today_command(&mut self) -> Result<()> {
+    /// 今日の予定を表示（`remaining_only`なら終わった予定を除く）
+    async fn calendar_today_command(&mut self, remaining_only: bool) -> Result<()> {
         self.ensure_calendar_auth().await?;
 
-        if let Some(service) = &self.calendar_service {
-            match service.get_today_events().await {
-                Ok(events) => {
-                    self.display_calendar_events(&events, "📅 今日のGoogle Calendarの予定");
-                }
-                Err(e) => {
-                    self.print_error("エラー", &e);
+        let key = format!(
+            "{}:{}",
+            if remaining_only { "today-remaining" } else { "today" },
+            self.clock.now().with_timezone(&Tokyo).date_naive()
+        );
+        let result = match &self.calendar_service {
+            Some(service) => {
+                fetch_with_cache(self.event_cache(), &key, service.get_today_events(remaining_only)).await
+            }
+            None => fetch_with_cache(self.event_cache(), &key, calendar_unavailable()).await,
+        };
+        match result {
+            Ok((events, from_cache)) => {
+                if from_cache {
+                    self.print_warning("(オフライン: キャッシュ表示)");
                 }
+                let title = if remaining_only {
+                    "📅 今日の残りのGoogle Calendarの予定"
+                } else {
+                    "📅 今日のGoogle Calendarの予定"
+                };
+                self.display_calendar_events(&events, title, None);
+            }
+            Err(e) => {
+                self.print_error("エラー", &e);
             }
         }
 
@@ -683,32 +1722,79 @@ impl CliApp {
     async fn calendar_week_command(&mut self) -> Result<()> {
         self.ensure_calendar_auth().await?;
 
-        if let Some(service) = &self.calendar_service {
-            match service.get_week_events().await {
-                Ok(events) => {
-                    if let Some(items) = &events.items {
-                        if items.is_empty() {
-                            self.print_warning("今週の予定はありません。");
-                        } else {
-                            println!("{}", "📅 今週のGoogle Calendarの予定".bold().blue());
-                            println!("予定数: {} 件\n", items.len());
-                            for (i, event) in items.iter().enumerate() {
-                                self.display_google_calendar_event(event, i + 1);
-                            }
-                        }
-                    } else {
+        let key = format!("week:{}", self.clock.now().with_timezone(&Tokyo).date_naive());
+        let result = match &self.calendar_service {
+            Some(service) => fetch_with_cache(self.event_cache(), &key, service.get_week_events()).await,
+            None => fetch_with_cache(self.event_cache(), &key, calendar_unavailable()).await,
+        };
+        match result {
+            Ok((events, from_cache)) => {
+                if from_cache {
+                    self.print_warning("(オフライン: キャッシュ表示)");
+                }
+                if let Some(items) = &events.items {
+                    if items.is_empty() {
                         self.print_warning("今週の予定はありません。");
+                    } else {
+                        println!("{}", "📅 今週のGoogle Calendarの予定".bold().blue());
+                        println!("予定数: {} 件", items.len());
+                        self.display_agenda(items);
                     }
+                } else {
+                    self.print_warning("今週の予定はありません。");
                 }
-                Err(e) => {
-                    self.print_error("エラー", &e);
+            }
+            Err(e) => {
+                self.print_error("エラー", &e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 今日から`days`日分の予定を日付ごとに表示
+    async fn calendar_agenda_command(&mut self, days: i64) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        let today = self.clock.now().with_timezone(&Tokyo).date_naive();
+        let (start, _) = day_window(today);
+        let (_, end) = day_window(today + chrono::Duration::days(days - 1));
+        let key = format!("agenda:{}:{}", today, days);
+        let result = match &self.calendar_service {
+            Some(service) => fetch_with_cache(self.event_cache(), &key, service.get_events_in_period(start, end, 250)).await,
+            None => fetch_with_cache(self.event_cache(), &key, calendar_unavailable()).await,
+        };
+        match result {
+            Ok((events, from_cache)) => {
+                if from_cache {
+                    self.print_warning("(オフライン: キャッシュ表示)");
+                }
+                println!("{}", format!("📅 {}日間の予定", days).bold().blue());
+                match events.items.as_deref() {
+                    Some(items) if !items.is_empty() => self.display_agenda(items),
+                    _ => self.print_warning("予定はありません。"),
                 }
             }
+            Err(e) => {
+                self.print_error("エラー", &e);
+            }
         }
 
         Ok(())
     }
 
+    /// 予定を開始日ごとに日付の見出しを付けて表示
+    fn display_agenda(&self, items: &[google_calendar3::api::Event]) {
+        let mut index = 0;
+        for (date, events) in group_events_by_day(items) {
+            println!("\n{}", self.config.app.format_date(date).bold());
+            for event in events {
+                index += 1;
+                self.display_google_calendar_event(event, index);
+            }
+        }
+    }
+
     /// カレンダーと同期
     async fn calendar_sync_command(&mut self) -> Result<()> {
         self.ensure_calendar_auth().await?;
@@ -736,6 +1822,7 @@ impl CliApp {
         end: String,
         description: Option<String>,
         location: Option<String>,
+        attendees: Vec<String>,
     ) -> Result<()> {
         self.ensure_calendar_auth().await?;
 
@@ -748,28 +1835,483 @@ impl CliApp {
                 .map_err(|_| anyhow::anyhow!("無効な終了時刻フォーマット: {}", end))?
                 .with_timezone(&chrono::Utc);
 
-            println!("{}", "📝 Google Calendarにイベントを作成中...".blue());
-            match service
-                .create_event(
-                    &title,
-                    description.as_deref(),
-                    location.as_deref(),
-                    start_time,
-                    end_time,
-                )
-                .await
-            {
-                Ok(event) => {
-                    self.print_success("イベントが作成されました！");
-                    if let Some(summary) = &event.summary {
-                        println!("タイトル: {}", summary);
-                    }
-                    if let Some(event_id) = &event.id {
-                        println!("ID: {}", event_id);
-                    }
-                }
-                Err(e) => {
-                    self.print_error("作成エラー", &e);
+            // 参加者に予定が入っている場合は、作成する前に知らせて確認する
+            if !attendees.is_empty() {
+                let results = service.attendee_availability(&attendees, start_time, end_time).await;
+                let lines = unavailable_attendee_lines(&results, &self.config.app);
+                if !lines.is_empty() {
+                    self.print_warning("参加者の空き状況を確認しました（予定あり・不明の方）");
+                    for line in &lines {
+                        println!("{}", line);
+                    }
+                    let proceed = Confirm::new()
+                        .with_prompt("このまま作成しますか？")
+                        .default(false)
+                        .interact()?;
+                    if !proceed {
+                        println!("予定の作成を取りやめました。");
+                        return Ok(());
+                    }
+                }
+            }
+
+            println!("{}", "📝 Google Calendarにイベントを作成中...".blue());
+            match service
+                .create_event(
+                    &self.config.calendar.decorate_title(&title),
+                    description.as_deref(),
+                    location.as_deref(),
+                    start_time,
+                    end_time,
+                    self.config.app.default_priority().as_ref(),
+                    &attendees,
+                )
+                .await
+            {
+                Ok(event) => {
+                    self.print_success("イベントが作成されました！");
+                    if let Some(summary) = &event.summary {
+                        println!("タイトル: {}", summary);
+                    }
+                    if let Some(event_id) = &event.id {
+                        println!("ID: {}", event_id);
+                    }
+                    notify_event_created(self.config.app.webhook_url.as_deref(), &event, &self.config.app).await;
+                }
+                Err(e) => {
+                    self.print_error("作成エラー", &e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 不在の予定を作成
+    async fn calendar_ooo_command(
+        &mut self,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+        message: Option<String>,
+    ) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            match service.create_out_of_office(start, end, message.as_deref()).await {
+                Ok(_) => {
+                    self.print_success(&format!(
+                        "{}から{}まで不在に設定しました。期間中の招待は自動で辞退されます。",
+                        start.with_timezone(&Tokyo).format("%Y/%m/%d %H:%M"),
+                        end.with_timezone(&Tokyo).format("%Y/%m/%d %H:%M")
+                    ));
+                }
+                Err(e) => {
+                    self.print_error("不在の予定の作成エラー", &e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 名前に一致する次回の予定を表示
+    async fn calendar_next_command(&mut self, query: String, days: i64) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            match service.find_next_event(&query, days).await {
+                Ok(Some(event)) => {
+                    println!("{}", format!("⏭️ 次の「{}」", query).bold().blue());
+                    self.display_google_calendar_event(&event, 1);
+                }
+                Ok(None) => {
+                    self.print_warning(&format!("「{}」の予定は見つかりません", query));
+                }
+                Err(e) => {
+                    self.print_error("検索エラー", &e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// ファイルから予定を一括作成
+    async fn calendar_import_events_command(&mut self, file: String) -> Result<()> {
+        let content = std::fs::read_to_string(&file)
+            .map_err(|e| anyhow::anyhow!("ファイルを読み込めません ({}): {}", file, e))?;
+        let csv = std::path::Path::new(&file)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let rows = parse_event_rows(&content, csv);
+        if rows.is_empty() {
+            self.print_warning("作成する予定がありません。");
+            return Ok(());
+        }
+
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            println!("{}", format!("📥 {}件の予定を作成中...", rows.len()).blue());
+            let titles: Vec<Option<String>> = rows
+                .iter()
+                .map(|(_, row)| row.as_ref().ok().map(|r| r.title.clone()))
+                .collect();
+            let results = service
+                .import_events(rows, self.config.app.default_priority().as_ref(), |s| {
+                    self.parse_datetime(s).map_err(anyhow::Error::from)
+                })
+                .await;
+
+            let total = results.len();
+            let mut failed = 0;
+            for ((number, result), title) in results.into_iter().zip(titles) {
+                let title = title.unwrap_or_else(|| "(不明)".to_string());
+                match result {
+                    Ok(_) => println!("  {} {}行目 {}", "✓".green(), number, title),
+                    Err(e) => {
+                        failed += 1;
+                        println!("  {} {}行目 {}: {}", "✗".red(), number, title, e);
+                    }
+                }
+            }
+
+            if failed == 0 {
+                self.print_success(&format!("✅ {}件の予定を作成しました", total));
+            } else {
+                self.print_warning(&format!("{}件中{}件を作成し、{}件が失敗しました", total, total - failed, failed));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 既存の予定を別の日時に複製
+    async fn calendar_duplicate_command(
+        &mut self,
+        event_id: String,
+        start: chrono::DateTime<chrono::Utc>,
+        end: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            match service.duplicate_event(&event_id, start, end).await {
+                Ok(event) => {
+                    self.print_success("予定を複製しました！");
+                    if let Some(summary) = &event.summary {
+                        println!("タイトル: {}", summary);
+                    }
+                    println!("開始: {}", start.with_timezone(&Tokyo).format("%Y/%m/%d %H:%M"));
+                    if let Some(event_id) = &event.id {
+                        println!("ID: {}", event_id);
+                    }
+                }
+                Err(e) => {
+                    self.print_error("複製エラー", &e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `since`〜`until`（両日を含む）の予定を一覧表示し、確認のうえまとめて削除する
+    async fn calendar_delete_range_command(
+        &mut self,
+        since: chrono::NaiveDate,
+        until: chrono::NaiveDate,
+        assume_yes: bool,
+    ) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            // 1ページ（最大250件）に収まらない期間でも、次のページを順にたどってすべて対象にする
+            let (start, end) = (day_window(since).0, day_window(until).1);
+            let events = service.all_events_in_period(start, end).await?;
+            if events.is_empty() {
+                self.print_warning("削除する予定はありません。");
+                return Ok(());
+            }
+
+            println!("{}", format!("🗑️ 削除対象の予定（{}件）:", events.len()).bold());
+            for event in &events {
+                println!("  {}", format_compact_event(event, &self.config.app, false));
+            }
+            let confirmed = assume_yes
+                || Confirm::new()
+                    .with_prompt(format!("{}件の予定を削除しますか？", events.len()))
+                    .default(false)
+                    .interact()?;
+            if !confirmed {
+                println!("削除を取りやめました。");
+                return Ok(());
+            }
+
+            let send_updates = self.config.calendar.delete_send_updates.as_deref();
+            let results = service.delete_events(&events, send_updates).await;
+            let mut failures = 0;
+            for (event, result) in &results {
+                let title = event.summary.as_deref().unwrap_or("(タイトルなし)");
+                match result {
+                    Ok(()) => println!("  {} {}", "✓".green(), title),
+                    Err(e) => {
+                        failures += 1;
+                        println!("  {} {}: {}", "✗".red(), title, e);
+                    }
+                }
+            }
+            if failures == 0 {
+                self.print_success(&format!("{}件の予定を削除しました。", results.len()));
+            } else {
+                self.print_warning(&format!(
+                    "{}件を削除し、{}件は削除できませんでした。",
+                    results.len() - failures,
+                    failures
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 今日から`days`日間の重複した予定を表示し、確認のうえ余分な予定を削除する
+    async fn calendar_dedupe_command(&mut self, days: i64, assume_yes: bool) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            let today = self.clock.now().with_timezone(&Tokyo).date_naive();
+            let (start, end) = (day_window(today).0, day_window(today + chrono::Duration::days(days - 1)).1);
+            let events = service.get_events_in_period(start, end, 2500).await?.items.unwrap_or_default();
+            let groups = find_duplicate_events(&events);
+            if groups.is_empty() {
+                self.print_success(&format!("今後{}日間に重複した予定はありません。", days));
+                return Ok(());
+            }
+
+            let duplicates: Vec<_> = groups.iter().flat_map(|group| group.duplicates.clone()).collect();
+            println!("{}", format!("🔁 重複した予定（{}組）:", groups.len()).bold());
+            for group in &groups {
+                println!("  {} {}", "残す:".green(), format_compact_event(&group.keep, &self.config.app, false));
+                for duplicate in &group.duplicates {
+                    println!("  {} {}", "削除:".red(), format_compact_event(duplicate, &self.config.app, false));
+                }
+            }
+            let confirmed = assume_yes
+                || Confirm::new()
+                    .with_prompt(format!("重複した{}件の予定を削除しますか？", duplicates.len()))
+                    .default(false)
+                    .interact()?;
+            if !confirmed {
+                println!("削除を取りやめました。");
+                return Ok(());
+            }
+
+            let send_updates = self.config.calendar.delete_send_updates.as_deref();
+            let results = service.delete_events(&duplicates, send_updates).await;
+            let failures = results.iter().filter(|(_, result)| result.is_err()).count();
+            for (event, result) in &results {
+                if let Err(e) = result {
+                    println!("  {} {}: {}", "✗".red(), event.summary.as_deref().unwrap_or("(タイトルなし)"), e);
+                }
+            }
+            if failures == 0 {
+                self.print_success(&format!("重複した{}件の予定を削除しました。", results.len()));
+            } else {
+                self.print_warning(&format!(
+                    "{}件を削除し、{}件は削除できませんでした。",
+                    results.len() - failures,
+                    failures
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 招待された予定に返答
+    async fn calendar_rsvp_command(&mut self, event_id: String, response: String) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            match service.respond_to_invitation(&event_id, &response).await {
+                Ok(_) => {
+                    let label = match response.as_str() {
+                        "accepted" => "参加",
+                        "declined" => "不参加",
+                        _ => "未定",
+                    };
+                    self.print_success(&format!("✅ 招待に「{}」と返答しました (ID: {})", label, event_id));
+                }
+                Err(e) => {
+                    self.print_error("返答エラー", &e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 今日の残りの予定をまとめてずらす
+    async fn calendar_shift_rest_command(&mut self, delta: chrono::Duration) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            println!(
+                "{}",
+                format!("⏩ 今日の残りの予定を{}分ずらしています...", delta.num_minutes()).blue()
+            );
+            match service.shift_remaining_today_events(delta).await {
+                Ok(results) => {
+                    if results.is_empty() {
+                        self.print_warning("ずらす対象の予定はありません。");
+                    }
+                    for (original, result) in results {
+                        let title = original.summary.as_deref().unwrap_or("(タイトルなし)");
+                        match result {
+                            Ok(updated) => {
+                                let format_time = |event: &google_calendar3::api::Event| {
+                                    event
+                                        .start
+                                        .as_ref()
+                                        .and_then(|s| s.date_time)
+                                        .map(|dt| dt.with_timezone(&Tokyo).format("%H:%M").to_string())
+                                        .unwrap_or_default()
+                                };
+                                println!(
+                                    "  {} {}: {} → {}",
+                                    "✓".green(),
+                                    title,
+                                    format_time(&original),
+                                    format_time(&updated)
+                                );
+                            }
+                            Err(e) => {
+                                println!("  {} {}: {}", "✗".red(), title, e);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    self.print_error("エラー", &e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Google Calendarの予定を1行ずつ一覧表示（今日、または今後1週間）
+    async fn list_events_command(
+        &mut self,
+        today_only: bool,
+        limit: usize,
+        details: bool,
+        format: ListFormat,
+        mine: bool,
+    ) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            let now = self.clock.now();
+            let (start, end, title) = if today_only {
+                let (start, end) = day_window(now.with_timezone(&Tokyo).date_naive());
+                (start, end, "📅 今日の予定")
+            } else {
+                (now, now + chrono::Duration::weeks(1), "📅 今後1週間の予定")
+            };
+            let title = if mine { format!("{}（自分が主催）", title) } else { title.to_string() };
+
+            // 自分が主催する予定に絞る場合は、期間内の予定をすべて取得してから絞り込む
+            // 件数だけを表示する場合も、表示件数の上限に関係なくすべて数える
+            let items = if mine {
+                service.my_events_in_period(start, end).await
+            } else if format == ListFormat::Count {
+                service.all_events_in_period(start, end).await
+            } else {
+                service
+                    .get_events_in_period(start, end, limit as i32)
+                    .await
+                    .map(|events| events.items.unwrap_or_default())
+            };
+            match items {
+                Ok(items) => {
+                    if format == ListFormat::Count {
+                        println!("{}", format_event_count(&items));
+                        return Ok(());
+                    }
+                    if format == ListFormat::Markdown {
+                        let shown = &items[..items.len().min(limit)];
+                        print!("{}", format_markdown_table(shown, &self.config.app));
+                        return Ok(());
+                    }
+                    println!("{}", title.bold().blue());
+                    if items.is_empty() {
+                        self.print_warning("予定はありません。");
+                    }
+                    for (i, event) in items.iter().take(limit).enumerate() {
+                        print!("{}", format_event_line(event, i + 1, &self.config.app, details));
+                    }
+                }
+                Err(e) => {
+                    self.print_error("取得エラー", &e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 参加者ごとに、指定した時間帯の空き状況を表示する
+    async fn calendar_availability_command(&mut self, attendees: Vec<String>, start: String, end: String) -> Result<()> {
+        if attendees.is_empty() {
+            anyhow::bail!("参加者のメールアドレスを指定してください");
+        }
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            let start_time = chrono::DateTime::parse_from_rfc3339(&start)
+                .map_err(|_| anyhow::anyhow!("無効な開始時刻フォーマット: {}", start))?
+                .with_timezone(&chrono::Utc);
+            let end_time = chrono::DateTime::parse_from_rfc3339(&end)
+                .map_err(|_| anyhow::anyhow!("無効な終了時刻フォーマット: {}", end))?
+                .with_timezone(&chrono::Utc);
+
+            println!(
+                "{}",
+                format!(
+                    "=== 参加者の空き状況 ({} ～ {}) ===",
+                    start_time.with_timezone(&Tokyo).format("%Y-%m-%d %H:%M"),
+                    end_time.with_timezone(&Tokyo).format("%H:%M")
+                )
+                .bold()
+                .green()
+            );
+            for (attendee, availability) in service.attendee_availability(&attendees, start_time, end_time).await {
+                println!("{}", format_attendee_availability(&attendee, &availability, &self.config.app));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `start`〜`end`が空いているかを表示し、予定が入っていれば重なる予定を一覧にする
+    async fn calendar_freebusy_command(&mut self, start: chrono::DateTime<chrono::Utc>, end: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            let range = format!(
+                "{}〜{}",
+                self.config.app.format_datetime(start.with_timezone(&Tokyo).naive_local()),
+                end.with_timezone(&Tokyo).format("%H:%M")
+            );
+            let busy = service.busy_events(start, end).await?;
+            if busy.is_empty() {
+                self.print_success(&format!("{} は空いています。", range));
+            } else {
+                self.print_warning(&format!("{} は予定が入っています（{}件）:", range, busy.len()));
+                for event in &busy {
+                    println!("  {}", format_compact_event(event, &self.config.app, false));
                 }
             }
         }
@@ -782,22 +2324,45 @@ impl CliApp {
         &mut self,
         duration_minutes: i64,
         days_ahead: i64,
+        date: Option<chrono::NaiveDate>,
+        prefer: Option<SlotPreference>,
     ) -> Result<()> {
         self.ensure_calendar_auth().await?;
 
         if let Some(service) = &self.calendar_service {
-            let now_jst = chrono::Utc::now().with_timezone(&Tokyo);
-            let end_time_jst = now_jst + chrono::Duration::days(days_ahead);
+            // 日付指定があればその日の00:00〜23:59だけを検索する（今日なら現在時刻から）
+            let now = self.clock.now();
+            let (search_start, search_end) = match date {
+                Some(date) => {
+                    let (start, end) = day_window(date);
+                    match upcoming_window(start, end, now) {
+                        Some(window) => window,
+                        None => {
+                            self.print_warning("指定した日付はすでに過ぎています。");
+                            return Ok(());
+                        }
+                    }
+                }
+                None => (now, now + chrono::Duration::days(days_ahead)),
+            };
 
             println!(
                 "{}",
                 format!("🔍 {}分間の空き時間を検索中...", duration_minutes).blue()
             );
             match service
-                .find_free_time(now_jst.with_timezone(&chrono::Utc), end_time_jst.with_timezone(&chrono::Utc), duration_minutes)
+                .find_free_time(
+                    search_start,
+                    search_end,
+                    duration_minutes,
+                    self.config.calendar.free_time_buffer_minutes.unwrap_or(0),
+                )
                 .await
             {
-                Ok(free_slots) => {
+                Ok(mut free_slots) => {
+                    if let Some(prefer) = prefer {
+                        sort_free_slots(&mut free_slots, prefer);
+                    }
                     if free_slots.is_empty() {
                         self.print_warning("指定した期間に空き時間が見つかりませんでした。");
                     } else {
@@ -826,6 +2391,15 @@ impl CliApp {
 
     /// Google Calendarのイベントを表示
     fn display_google_calendar_event(&self, event: &google_calendar3::api::Event, index: usize) {
+        if self.compact {
+            println!(
+                "{:>3}. {}",
+                index,
+                format_compact_event(event, &self.config.app, std::io::IsTerminal::is_terminal(&std::io::stdout()))
+            );
+            return;
+        }
+
         println!("\n--- イベント {} ---", index);
 
         if let Some(id) = &event.id {
@@ -857,7 +2431,12 @@ impl CliApp {
         }
 
         if let Some(location) = &event.location {
-            println!("📍 場所: {}", location.cyan());
+            let hyperlinks = std::io::IsTerminal::is_terminal(&std::io::stdout());
+            println!("📍 場所: {}", format_location(location, hyperlinks).cyan());
+        }
+
+        if event.color_id.is_some() {
+            println!("⭐ 優先度: {:?}", crate::calendar::event_priority(event));
         }
     }
 
@@ -882,7 +2461,8 @@ impl CliApp {
         context
     }
 
-    fn add_event_command(
+    #[allow(clippy::too_many_arguments)]
+    async fn add_event_command(
         &mut self,
         title: String,
         description: Option<String>,
@@ -890,27 +2470,35 @@ impl CliApp {
         end: String,
         location: Option<String>,
         priority_str: Option<String>, // 変数名を変更
+        target: AddTarget,
     ) -> Result<()> {
-        let priority = match priority_str.as_deref() {
-            Some("low") => Some(Priority::Low),
-            Some("medium") => Some(Priority::Medium),
-            Some("high") => Some(Priority::High),
-            Some("urgent") => Some(Priority::Urgent),
-            _ => None, // デフォルト値をNoneにするか、LLMに任せる
-        };
+        // 指定がなければ設定の既定の優先度を使う
+        let priority = priority_str
+            .as_deref()
+            .and_then(Priority::parse)
+            .or_else(|| self.config.app.default_priority());
+        let title = self.config.calendar.decorate_title(&title);
+
+        if target == AddTarget::Calendar {
+            return self
+                .add_calendar_event(title, description, start, end, location, priority)
+                .await;
+        }
 
         let event_data = crate::models::EventData {
-            id: None,
             title: Some(title),
             description,
             start_time: Some(start),
             end_time: Some(end),
             location,
-            attendees: Vec::new(),
             priority,
-            max_results: None,
+            ..Default::default()
         };
 
+        if target == AddTarget::Mirror {
+            return self.add_mirrored_event(event_data).await;
+        }
+
         match self.create_local_event(event_data) {
             Ok(event_id) => {
                 self.print_success("予定を作成しました。");
@@ -918,30 +2506,110 @@ impl CliApp {
                 self.save_schedule()?;
             }
             Err(e) => {
-                self.print_error("エラー", &e);
+                return Err(anyhow::anyhow!("作成エラー: {}", e));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `add`コマンドの予定をGoogle Calendarに作成する
+    async fn add_calendar_event(
+        &mut self,
+        title: String,
+        description: Option<String>,
+        start: String,
+        end: String,
+        location: Option<String>,
+        priority: Option<Priority>,
+    ) -> Result<()> {
+        let start_time = self.parse_datetime(&start)?;
+        let end_time = self.parse_datetime(&end)?;
+        if end_time <= start_time {
+            return Err(anyhow::anyhow!("終了時刻は開始時刻より後である必要があります"));
+        }
+
+        self.ensure_calendar_auth().await?;
+        let service = self.calendar_service.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Google Calendarに接続できません。オフラインで保存する場合は --local を指定してください")
+        })?;
+
+        println!("{}", "📝 Google Calendarにイベントを作成中...".blue());
+        match service
+            .create_event(
+                &title,
+                description.as_deref(),
+                location.as_deref(),
+                start_time,
+                end_time,
+                priority.as_ref(),
+                &[],
+            )
+            .await
+        {
+            Ok(event) => {
+                self.print_success("予定をGoogle Calendarに作成しました。");
+                if let Some(event_id) = &event.id {
+                    println!("イベントID: {}", event_id.cyan());
+                }
+                notify_event_created(self.config.app.webhook_url.as_deref(), &event, &self.config.app).await;
+            }
+            Err(e) => {
+                // スクリプトから失敗を判別できるよう、終了コードを0以外にする
+                return Err(anyhow::anyhow!("作成エラー: {}", e));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Google Calendarの予定を期間を区切って検索
+    async fn search_calendar_events_command(&mut self, query: String, days: i64, count: bool) -> Result<()> {
+        self.ensure_calendar_auth().await?;
+
+        if let Some(service) = &self.calendar_service {
+            match service.search_events(&query, days).await {
+                Ok(items) if count => {
+                    println!("{}", format_event_count(&items));
+                }
+                Ok(items) => {
+                    let events = google_calendar3::api::Events {
+                        items: Some(items),
+                        ..Default::default()
+                    };
+                    let title = format!("🔍 「{}」の検索結果（今後{}日間）", query, days);
+                    self.display_calendar_events(&events, &title, Some(&query));
+                }
+                Err(e) => {
+                    self.print_error("検索エラー", &e);
+                }
             }
         }
 
         Ok(())
     }
 
-    fn search_events_command(&self, query: String) -> Result<()> {
+    fn search_events_command(&self, query: String, count: bool) -> Result<()> {
         let events = self.search_local_events(&query);
 
-        if events.is_empty() {
+        if count {
+            println!("{}件", events.len());
+        } else if events.is_empty() {
             self.print_warning(&format!(
                 "「{}」に一致する予定が見つかりませんでした。",
                 query
             ));
         } else {
             println!("{}", format!("=== 検索結果: {} ===", query).bold().blue());
-            self.display_events_list(events);
+            self.display_events_list(events, Some(&query));
         }
 
         Ok(())
     }
 
-    fn show_statistics(&self) -> Result<()> {
+    async fn show_statistics(&mut self, google: bool) -> Result<()> {
+        use chrono::Datelike;
+
         let stats = self.get_local_statistics();
 
         println!("{}", "=== 予定統計 ===".bold().blue());
@@ -955,6 +2623,82 @@ impl CliApp {
         println!("  高: {}", stats.high_priority.to_string().yellow());
         println!("  緊急: {}", stats.urgent_priority.to_string().red());
 
+        // 今週（月曜〜日曜）の予定が勤務時間に占める割合
+        let today = self.clock.now().with_timezone(&Tokyo).date_naive();
+        let monday = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        let (week_start, _) = day_window(monday);
+        let (_, week_end) = day_window(monday + chrono::Duration::days(6));
+        let busy: Vec<_> = if google {
+            self.ensure_calendar_auth().await?;
+            let service = self
+                .calendar_service
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Google Calendarに接続できません"))?;
+            let events = service.get_events_in_period(week_start, week_end, 250).await?;
+            events.items.unwrap_or_default().iter().filter_map(busy_interval).collect()
+        } else {
+            self.local_schedule
+                .events_in_range(week_start, week_end)
+                .iter()
+                .map(|event| (event.start_time, event.end_time))
+                .collect()
+        };
+        let utilization = compute_utilization(&busy, week_start, week_end, self.config.app.working_hours());
+
+        println!("\n{}", "今週の稼働:".bold());
+        println!("  予定の合計: {}", format_minutes(utilization.booked_minutes).cyan());
+        println!("  勤務時間: {}", format_minutes(utilization.working_minutes));
+        if let Some(percent) = utilization.percent() {
+            println!("  稼働率: {}", format!("{:.0}%", percent).green());
+        }
+
+        Ok(())
+    }
+
+    fn show_usage_metrics(&self, days: Option<i64>) -> Result<()> {
+        let since = days.map(|days| self.clock.now() - chrono::Duration::days(days));
+        let records: Vec<UsageRecord> = self
+            .storage
+            .load_usage_records()?
+            .into_iter()
+            .filter(|record| since.is_none_or(|since| record.timestamp >= since))
+            .collect();
+
+        println!("{}", "=== 利用状況 ===".bold().blue());
+        if records.is_empty() {
+            if self.config.app.usage_metrics.unwrap_or(false) {
+                println!("{}", "記録された依頼がありません。".yellow());
+            } else {
+                println!(
+                    "{}",
+                    "記録された依頼がありません。設定ファイルで app.usage_metrics = true にすると記録を始めます。".yellow()
+                );
+            }
+            return Ok(());
+        }
+
+        let failures = records.iter().filter(|record| !record.success).count();
+        println!("依頼数: {}", records.len().to_string().cyan());
+        println!("失敗: {}", failures.to_string().red());
+        if let (Some(first), Some(last)) = (records.first(), records.last()) {
+            println!(
+                "期間: {} 〜 {}",
+                first.timestamp.with_timezone(&Tokyo).format("%Y/%m/%d %H:%M"),
+                last.timestamp.with_timezone(&Tokyo).format("%Y/%m/%d %H:%M")
+            );
+        }
+
+        println!("\n{}", "アクション別:".bold());
+        for (label, count) in summarize_usage(&records) {
+            println!(
+                "  {}: {}回（失敗 {}回、平均 {}ms）",
+                label,
+                count.total.to_string().cyan(),
+                count.failures,
+                count.average_latency_ms()
+            );
+        }
+
         Ok(())
     }
 
@@ -971,7 +2715,7 @@ impl CliApp {
         Ok(())
     }
 
-    fn restore_command(&self) -> Result<()> {
+    fn restore_command(&self, dry_run: bool) -> Result<()> {
         let backups = self.storage.list_backups()?;
 
         if backups.is_empty() {
@@ -989,13 +2733,22 @@ impl CliApp {
             .items(&backup_names)
             .interact()?;
 
+        if dry_run {
+            let diff = self.storage.preview_replace(&backups[selection])?;
+            self.print_schedule_diff(&diff);
+            return Ok(());
+        }
+
         let confirm = Confirm::new()
             .with_prompt("現在のスケジュールが上書きされます。続行しますか？")
             .interact()?;
 
         if confirm {
-            match self.storage.restore_schedule(&backups[selection]) {
-                Ok(()) => {
+            match self.storage.restore_schedule(&backups[selection], &self.config.app) {
+                Ok(auto_backup) => {
+                    if let Some(backup_path) = auto_backup {
+                        status(&format!("自動バックアップを作成しました: {}", backup_path.display().to_string().cyan()));
+                    }
                     println!("{}", "スケジュールを復元しました。".green());
                     println!("{}", "アプリケーションを再起動してください。".yellow());
                 }
@@ -1008,6 +2761,34 @@ impl CliApp {
         Ok(())
     }
 
+    /// 置き換えた場合の差分を表示する（--dry-run用）
+    fn print_schedule_diff(&self, diff: &crate::models::ScheduleDiff) {
+        println!("{}", "=== 変更内容のプレビュー（書き込みは行いません） ===".bold().blue());
+        if diff.is_empty() {
+            println!("変更はありません。");
+            return;
+        }
+
+        let app_config = &self.config.app;
+        let sections = [
+            ("追加", "+", Color::Green, &diff.added),
+            ("削除", "-", Color::Red, &diff.removed),
+            ("変更", "~", Color::Yellow, &diff.changed),
+        ];
+        for (label, mark, color, events) in sections {
+            println!("{}: {}件", label.bold(), events.len());
+            for event in events {
+                let line = format!(
+                    "  {} {} ({})",
+                    mark,
+                    event.title,
+                    app_config.format_datetime(event.start_time.with_timezone(&Tokyo).naive_local())
+                );
+                println!("{}", line.color(color));
+            }
+        }
+    }
+
     fn export_command(&self, path: String) -> Result<()> {
         let export_path = std::path::Path::new(&path);
 
@@ -1024,14 +2805,21 @@ impl CliApp {
         Ok(())
     }
 
-    fn import_command(&self, path: String) -> Result<()> {
+    fn import_command(&self, path: String, dry_run: bool) -> Result<()> {
         let import_path = std::path::Path::new(&path);
 
+        if dry_run {
+            let diff = self.storage.preview_replace(import_path)?;
+            self.print_schedule_diff(&diff);
+            return Ok(());
+        }
+
         let confirm = Confirm::new()
             .with_prompt("現在のスケジュールが上書きされます。続行しますか？")
             .interact()?;
 
         if confirm {
+            self.auto_backup();
             match self.storage.import_schedule(import_path) {
                 Ok(schedule) => {
                     self.storage.save_schedule(&schedule)?;
@@ -1047,7 +2835,8 @@ impl CliApp {
         Ok(())
     }
 
-    fn display_events_list(&self, events: Vec<&crate::models::Event>) {
+    /// ローカルの予定一覧を表示する（`query`を指定すると一致箇所も表示する）
+    fn display_events_list(&self, events: Vec<&crate::models::Event>, query: Option<&str>) {
         for (i, event) in events.iter().enumerate() {
             let priority_color = match event.priority {
                 Priority::Low => "white",
@@ -1063,14 +2852,15 @@ impl CliApp {
                 format!("[{:?}]", event.priority).color(priority_color)
             );
 
+            let app_config = &self.config.app;
             println!(
                 "   {} ～ {}",
-                event
-                    .start_time
-                    .format("%Y-%m-%d %H:%M")
-                    .to_string()
+                app_config
+                    .format_datetime(event.start_time.with_timezone(&Tokyo).naive_local())
                     .green(),
-                event.end_time.format("%Y-%m-%d %H:%M").to_string().green()
+                app_config
+                    .format_datetime(event.end_time.with_timezone(&Tokyo).naive_local())
+                    .green()
             );
 
             if let Some(ref description) = event.description {
@@ -1085,13 +2875,54 @@ impl CliApp {
                 println!("   👥 {}", event.attendees.join(", ").purple());
             }
 
+            if let Some(found) = query.and_then(|query| local_event_query_match(event, query)) {
+                println!("   {}", format_query_match(&found));
+            }
+
             println!("   ID: {}", event.id.to_string().dimmed());
             println!();
         }
     }
 
     // ヘルパーメソッド
+    /// Google Calendarに作成し、成功した場合のみローカルにも保存する
+    async fn add_mirrored_event(&mut self, event_data: crate::models::EventData) -> Result<()> {
+        let event = self.build_local_event(event_data)?;
+
+        self.ensure_calendar_auth().await?;
+        let service = self.calendar_service.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Google Calendarに接続できません。オフラインで保存する場合は --local を指定してください")
+        })?;
+
+        println!("{}", "📝 Google Calendarにイベントを作成中...".blue());
+        match service
+            .create_event_with_local_mirror(&self.storage, &mut self.local_schedule, event)
+            .await
+        {
+            Ok(created) => {
+                self.print_success("予定をGoogle Calendarに作成し、ローカルにも保存しました。");
+                if let Some(event_id) = &created.id {
+                    println!("イベントID: {}", event_id.cyan());
+                }
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!("作成エラー（ローカルには保存していません）: {}", e));
+            }
+        }
+
+        Ok(())
+    }
+
     fn create_local_event(&mut self, event_data: crate::models::EventData) -> Result<uuid::Uuid> {
+        let event = self.build_local_event(event_data)?;
+        let event_id = event.id;
+        self.local_schedule.add_event(event);
+
+        Ok(event_id)
+    }
+
+    /// 入力内容を検証してローカル用の予定を組み立てる（スケジュールには追加しない）
+    fn build_local_event(&self, event_data: crate::models::EventData) -> Result<crate::models::Event> {
         use crate::models::Event;
 
         let title = event_data
@@ -1122,12 +2953,38 @@ impl CliApp {
         }
 
         let mut event = Event::new(title, start_time, end_time);
+        // 指定がなければ設定の既定の優先度を使う
+        if let Some(priority) = self.config.app.default_priority() {
+            event.priority = priority;
+        }
         event.apply_event_data(event_data, |s| self.parse_datetime(s))?;
 
-        let event_id = event.id;
-        self.local_schedule.add_event(event);
+        Ok(event)
+    }
 
-        Ok(event_id)
+    /// ローカルの予定を完了にする
+    fn complete_event_command(&mut self, id: &str) -> Result<()> {
+        let mut matches = self
+            .local_schedule
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| event.id.to_string().starts_with(id));
+        let index = match (matches.next(), matches.next()) {
+            (Some((index, _)), None) => index,
+            (None, _) => return Err(anyhow::anyhow!("IDが「{}」の予定が見つかりません", id)),
+            (Some(_), Some(_)) => {
+                return Err(anyhow::anyhow!("IDが「{}」で始まる予定が複数あります", id))
+            }
+        };
+
+        self.auto_backup();
+        let event = &mut self.local_schedule.events[index];
+        event.set_status(crate::models::EventStatus::Completed)?;
+        let title = event.title.clone();
+        self.save_schedule()?;
+        self.print_success(&format!("「{}」を完了にしました。", title));
+        Ok(())
     }
 
     fn save_schedule(&self) -> Result<()> {
@@ -1162,6 +3019,20 @@ impl CliApp {
         Ok(())
     }
 
+    /// アップデートで増えた設定項目を確認できるよう、例のファイルだけを作り直す
+    fn config_examples_command(&self) -> Result<()> {
+        let files = self.config_manager.create_example_files()?;
+        self.print_success("サンプルファイルを再生成しました:");
+        for file in files {
+            println!("  {}", file.display().to_string().cyan());
+        }
+        println!(
+            "現在の設定ファイル（{}）は変更していません。新しい項目を追記するには config migrate を使ってください。",
+            self.config_manager.get_config_file_path().display()
+        );
+        Ok(())
+    }
+
     fn config_show_command(&self) -> Result<()> {
         println!("{}", "=== 現在の設定 ===".bold().blue());
 
@@ -1187,7 +3058,74 @@ impl CliApp {
                 "未設定".red()
             }
         );
+        println!(
+            "  APIキーの取得元: {}",
+            self.config.llm.api_key_source.as_deref().unwrap_or("config").cyan()
+        );
+
+        Ok(())
+    }
+
+    /// Gemini APIキーをOSのキーリングに保存
+    fn config_set_key_command(&self) -> Result<()> {
+        let key = Password::new().with_prompt("Gemini APIキー").interact()?;
+        let key = key.trim();
+        if key.is_empty() {
+            println!("{}", "APIキーが空のため、保存をキャンセルしました。".yellow());
+            return Ok(());
+        }
+
+        match OsKeyring.set_secret(GEMINI_API_KEY_ENTRY, key) {
+            Ok(()) => {
+                self.print_success("APIキーをキーリングに保存しました。");
+                if self.config.llm.api_key_source.as_deref() != Some("keyring") {
+                    self.print_warning(
+                        "config.toml の [llm] に api_key_source = \"keyring\" を設定すると、保存したキーが使われます。",
+                    );
+                }
+            }
+            Err(e) => {
+                self.print_error("キーリングへの保存エラー", &e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 優先度を指定せずに作成した予定の優先度を設定ファイルに保存する
+    fn config_set_priority_command(&mut self, priority: String) -> Result<()> {
+        self.config.app.default_priority = Some(priority.clone());
+        match self.config_manager.set_config_value("app", "default_priority", &priority) {
+            Ok(()) => self.print_success(&format!("既定の優先度を {} に設定しました。", priority)),
+            Err(e) => self.print_error("設定の保存エラー", &e),
+        }
+        Ok(())
+    }
 
+    /// 設定ファイルに新しい項目を追記
+    fn config_migrate_command(&self) -> Result<()> {
+        match self.config_manager.migrate_config() {
+            Ok(added) if added.is_empty() => {
+                self.print_success("設定ファイルは最新です。追加する項目はありません。");
+            }
+            Ok(added) => {
+                self.print_success(&format!("{}件の項目をコメント付きで追加しました:", added.len()));
+                for key in added {
+                    println!("  {}", key.cyan());
+                }
+                println!(
+                    "元のファイルは {} に保存しました。",
+                    self.config_manager
+                        .get_config_file_path()
+                        .with_extension("toml.bak")
+                        .display()
+                        .to_string()
+                        .cyan()
+                );
+            }
+            Err(e) => {
+                self.print_error("設定の移行エラー", &e);
+            }
+        }
         Ok(())
     }
 
@@ -1212,8 +3150,12 @@ impl CliApp {
         Ok(())
     }
 
-    fn show_conversation_history(&self) -> Result<()> {
+    fn show_conversation_history(&self, json: bool) -> Result<()> {
         let conversation = self.storage.load_conversation_history()?;
+        if json {
+            print_json(&serde_json::to_value(&conversation)?)?;
+            return Ok(());
+        }
         if conversation.messages.is_empty() {
             println!("会話履歴はありません。");
             return Ok(());
@@ -1242,8 +3184,12 @@ impl CliApp {
         Ok(())
     }
 
-    fn show_conversation_summary(&self) -> Result<()> {
+    fn show_conversation_summary(&self, json: bool) -> Result<()> {
         let conversation = self.storage.load_conversation_history()?;
+        if json {
+            print_json(&serde_json::to_value(conversation.summary(10))?)?;
+            return Ok(());
+        }
         if conversation.messages.is_empty() {
             println!("会話履歴はありません。");
             return Ok(());
@@ -1289,9 +3235,9 @@ impl CliApp {
             },
         };
 
-        let now = chrono::Utc::now();
+        let now = self.clock.now();
         let total_events = schedule.events.len();
-        let upcoming_events = schedule.events.iter().filter(|e| e.start_time > now).count();
+        let upcoming_events = schedule.upcoming(now, usize::MAX).len();
         let past_events = schedule.events.iter().filter(|e| e.end_time < now).count();
 
         let low_priority = schedule.events.iter().filter(|e| matches!(e.priority, crate::models::Priority::Low)).count();
@@ -1313,31 +3259,307 @@ impl CliApp {
 
     /// 直近のイベントを取得
     fn get_local_upcoming_events(&self, limit: usize) -> Vec<&crate::models::Event> {
-        let now = chrono::Utc::now();
-        let mut upcoming_events: Vec<&crate::models::Event> = self.local_schedule.events
-            .iter()
-            .filter(|event| event.start_time > now)
-            .collect();
-        
-        // 開始時刻でソート
-        upcoming_events.sort_by(|a, b| a.start_time.cmp(&b.start_time));
-        
-        // 指定された件数まで取得
-        upcoming_events.into_iter().take(limit).collect()
+        self.local_schedule.upcoming(self.clock.now(), limit)
     }
 
     /// ローカルイベントを検索
     fn search_local_events(&self, query: &str) -> Vec<&crate::models::Event> {
-        let query_lower = query.to_lowercase();
-        
         self.local_schedule.events
             .iter()
-            .filter(|event| {
-                // タイトル、説明、場所で検索
-                event.title.to_lowercase().contains(&query_lower) ||
-                event.description.as_ref().map_or(false, |desc| desc.to_lowercase().contains(&query_lower)) ||
-                event.location.as_ref().map_or(false, |loc| loc.to_lowercase().contains(&query_lower))
-            })
+            // タイトル、説明、場所で検索
+            .filter(|event| local_event_query_match(event, query).is_some())
             .collect()
     }
 }
+
+/// ローカルの予定で検索語に一致した項目を返す
+fn local_event_query_match<'a>(event: &'a crate::models::Event, query: &str) -> Option<QueryMatch<'a>> {
+    find_query_match(
+        [
+            ("タイトル", Some(event.title.as_str())),
+            ("説明", event.description.as_deref()),
+            ("場所", event.location.as_deref()),
+        ],
+        query,
+    )
+}
+
+/// 一致した項目名と、一致箇所を強調した前後の文脈を整形する
+///
+/// 色は`colored`の設定に従うため、NO_COLORやパイプ出力ではそのままの文字列になる。
+fn format_query_match(found: &QueryMatch) -> String {
+    let (before, matched, after) = found.context(20);
+    format!(
+        "🔎 {}に一致: {}{}{}",
+        found.field,
+        before,
+        matched.black().on_yellow().bold(),
+        after
+    )
+}
+
+/// Google Calendarに接続できない場合の取得結果（キャッシュへのフォールバック用）
+async fn calendar_unavailable() -> Result<google_calendar3::api::Events> {
+    Err(anyhow::anyhow!("Google Calendarに接続できません"))
+}
+
+/// 時間制限付きでコマンドを実行する（`limit`がNoneなら制限なし）
+pub async fn run_with_timeout<T>(
+    limit: Option<std::time::Duration>,
+    command: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, command).await.map_err(|_| {
+            anyhow::anyhow!(
+                "コマンドが{}秒以内に完了しなかったため中断しました（app.command_timeout_secs で変更できます）",
+                limit.as_secs()
+            )
+        })?,
+        None => command.await,
+    }
+}
+
+/// `--start`/`--end`の値から開始・終了を決める
+///
+/// `--start`が「10:00-11:00」のような時間範囲なら、同日の開始・終了に分割する。
+/// 範囲と`--end`の両方がある場合は、食い違っていればエラーにする。
+///
+/// 終了時刻がなく`duration`が指定されている場合は、開始時刻に足して終了時刻を求める。
+fn resolve_start_end<F>(
+    start: &str,
+    end: Option<&str>,
+    duration: Option<chrono::Duration>,
+    today: chrono::NaiveDate,
+    parse_datetime: F,
+) -> Result<(String, String)>
+where
+    F: Fn(&str) -> Result<chrono::DateTime<chrono::Utc>>,
+{
+    match (split_time_range(start, today), end, duration) {
+        (Some((range_start, range_end)), Some(end), _) => {
+            if parse_datetime(&range_end)? != parse_datetime(end)? {
+                return Err(anyhow::anyhow!(
+                    "--start の時間範囲の終了（{}）と --end（{}）が一致しません",
+                    range_end,
+                    end
+                ));
+            }
+            Ok((range_start, range_end))
+        }
+        (Some((range_start, range_end)), None, _) => Ok((range_start, range_end)),
+        (None, Some(end), _) => Ok((start.to_string(), end.to_string())),
+        (None, None, Some(duration)) => {
+            let end = parse_datetime(start)? + duration;
+            Ok((start.to_string(), end.to_rfc3339()))
+        }
+        (None, None, None) => Err(anyhow::anyhow!(
+            "--end か --duration を指定するか、--start に「2025-07-01 10:00-11:00」のような時間範囲を指定してください"
+        )),
+    }
+}
+
+/// ページ送り表示を行うか判定する（端末出力かつ件数が1ページを超える場合のみ）
+fn should_page(is_tty: bool, enabled: bool, total: usize, page_size: usize) -> bool {
+    is_tty && enabled && page_size > 0 && total > page_size
+}
+
+/// 次のページへ進むかキー入力を待つ（q / Esc で中断）
+fn wait_for_next_page(shown: usize, total: usize) -> bool {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use crossterm::terminal;
+    use std::io::Write;
+
+    print!(
+        "{}",
+        format!("-- {}/{} 件 (任意のキーで続行、q で終了) --", shown, total).reversed()
+    );
+    let _ = std::io::stdout().flush();
+
+    if terminal::enable_raw_mode().is_err() {
+        println!();
+        return true;
+    }
+    let proceed = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                break !matches!(key.code, KeyCode::Char('q') | KeyCode::Esc);
+            }
+            Ok(_) => continue,
+            Err(_) => break true,
+        }
+    };
+    let _ = terminal::disable_raw_mode();
+
+    // プロンプト行を消去
+    print!("\r\x1b[2K");
+    let _ = std::io::stdout().flush();
+    proceed
+}
+
+/// "+1h"、"30m"、"-15m"、"1h30m" のような時間指定を解析する
+fn parse_shift_duration(value: &str) -> Result<chrono::Duration> {
+    let value = value.trim();
+    let (sign, body) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut total_minutes = 0i64;
+    let mut number = String::new();
+    for c in body.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let amount: i64 = number
+            .parse()
+            .map_err(|_| anyhow::anyhow!("無効な時間指定です: {}", value))?;
+        total_minutes += match c {
+            'h' | 'H' => amount * 60,
+            'm' | 'M' => amount,
+            _ => return Err(anyhow::anyhow!("無効な時間指定です: {}", value)),
+        };
+        number.clear();
+    }
+    if !number.is_empty() {
+        // 単位省略時は分として扱う
+        total_minutes += number
+            .parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("無効な時間指定です: {}", value))?;
+    }
+
+    if total_minutes == 0 {
+        return Err(anyhow::anyhow!("ずらす時間を指定してください: {}", value));
+    }
+
+    Ok(chrono::Duration::minutes(sign * total_minutes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar_option_overrides_mirror_setting() {
+        let target = |args: &[&str], mirror_by_default: bool| {
+            let base = ["schedule-ai", "add", "会議", "--start", "2025-07-01 10:00-11:00"];
+            let matches = Cli::app().get_matches_from_safe([&base, args].concat()).unwrap();
+            add_target(matches.subcommand_matches("add").unwrap(), mirror_by_default)
+        };
+
+        assert_eq!(target(&["--calendar"], true), AddTarget::Calendar);
+        assert_eq!(target(&["--local"], true), AddTarget::Local);
+        assert_eq!(target(&[], true), AddTarget::Mirror);
+        assert_eq!(target(&["--mirror"], false), AddTarget::Mirror);
+        assert_eq!(target(&[], false), AddTarget::Calendar);
+
+        let conflict = Cli::app().get_matches_from_safe([
+            "schedule-ai", "add", "会議", "--start", "2025-07-01 10:00-11:00", "--mirror", "--calendar",
+        ]);
+        assert!(conflict.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fires_for_slow_command() {
+        let slow = async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            Ok(())
+        };
+
+        let result = run_with_timeout(Some(std::time::Duration::from_millis(10)), slow).await;
+
+        assert!(result.unwrap_err().to_string().contains("中断しました"));
+        assert!(run_with_timeout(Some(std::time::Duration::from_secs(5)), async { Ok(1) }).await.is_ok());
+        assert!(run_with_timeout(None, async { Ok(1) }).await.is_ok());
+    }
+
+    #[test]
+    fn test_paging_skipped_when_not_tty() {
+        assert!(!should_page(false, true, 100, 10));
+        assert!(should_page(true, true, 100, 10));
+        assert!(!should_page(true, false, 100, 10));
+        assert!(!should_page(true, true, 10, 10));
+    }
+
+    /// 用意した入力を順に返すプロンプト
+    struct ScriptedPrompt(std::collections::VecDeque<&'static str>);
+
+    impl LinePrompt for ScriptedPrompt {
+        fn ask(&mut self, _prompt: &str) -> Result<String> {
+            self.0
+                .pop_front()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("入力が足りません"))
+        }
+    }
+
+    #[test]
+    fn test_guided_flow_reasks_invalid_input() -> Result<()> {
+        let mut prompt = ScriptedPrompt(
+            [
+                "",
+                "定例",
+                "あとで",
+                "2025-07-01T10:00:00+09:00",
+                "2025-07-01T09:00:00+09:00",
+                "2025-07-01T11:00:00+09:00",
+                "",
+                "最優先",
+                "high",
+                "週次の振り返り",
+            ]
+            .into(),
+        );
+        let parse = |value: &str| Ok(chrono::DateTime::parse_from_rfc3339(value)?.with_timezone(&chrono::Utc));
+
+        let event = prompt_guided_event(&mut prompt, parse)?;
+
+        assert_eq!(
+            event,
+            GuidedEvent {
+                title: "定例".to_string(),
+                start: "2025-07-01T01:00:00+00:00".to_string(),
+                end: "2025-07-01T02:00:00+00:00".to_string(),
+                location: None,
+                priority: Some("high".to_string()),
+                description: Some("週次の振り返り".to_string()),
+            }
+        );
+        assert!(prompt.0.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_duration_computes_end_from_start() -> Result<()> {
+        let today = chrono::NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        let parse = |value: &str| Ok(chrono::DateTime::parse_from_rfc3339(value)?.with_timezone(&chrono::Utc));
+
+        let (start, end) = resolve_start_end(
+            "2025-07-01T15:00:00+09:00",
+            None,
+            Some(chrono::Duration::minutes(30)),
+            today,
+            parse,
+        )?;
+        assert_eq!(start, "2025-07-01T15:00:00+09:00");
+        assert_eq!(parse(&end)?, parse("2025-07-01T15:30:00+09:00")?);
+
+        assert!(resolve_start_end("2025-07-01T15:00:00+09:00", None, None, today, parse).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_range_end_must_agree_with_end_option() -> Result<()> {
+        let today = chrono::NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        let parse = |value: &str| {
+            let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")?;
+            Ok(naive.and_utc())
+        };
+
+        let (start, end) = resolve_start_end("2025-07-01 10:00-11:00", Some("2025-07-01 11:00"), None, today, parse)?;
+        assert_eq!((start.as_str(), end.as_str()), ("2025-07-01 10:00", "2025-07-01 11:00"));
+
+        assert!(resolve_start_end("2025-07-01 10:00-11:00", Some("2025-07-01 12:00"), None, today, parse).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+/// 現在時刻の取得を差し替えるためのモジュール
+use chrono::{DateTime, Utc};
+
+/// 現在時刻の取得元
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// システム時刻をそのまま返す時計
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// 常に同じ時刻を返す時計（テストやデモ用）
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// RFC 3339形式の時刻を解析する（例: 2025-07-01T09:00:00+09:00）
+pub fn parse_fixed_now(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
@@ -0,0 +1,499 @@
+/// 日時表現の解析を行うモジュール
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use std::ops::Range;
+
+/// 時間範囲の区切り文字
+const RANGE_SEPARATORS: [char; 4] = ['-', '〜', '～', '~'];
+
+/// 時刻の前に付く時間帯の表現と、午後側（12時間加算）として扱うかどうか、
+/// 「12時」を翌日の0時として扱うかどうか（「夜12時」は深夜0時）
+const DAYPARTS: [(&str, bool, bool); 5] = [
+    ("午前", false, false),
+    ("午後", true, false),
+    ("朝", false, false),
+    ("夕方", true, false),
+    ("夜", true, true),
+];
+
+/// 「夜12時」で翌日に繰り上げる日付の形式（解析用と出力用）
+const ROLLOVER_DATE_FORMATS: [(&str, &str); 3] = [
+    ("%Y-%m-%d", "%Y-%m-%d"),
+    ("%Y/%m/%d", "%Y/%m/%d"),
+    ("%Y年%m月%d日", "%Y年%-m月%-d日"),
+];
+
+/// 漢数字の時（一〜二十三）
+const KANJI_DIGITS: [char; 9] = ['一', '二', '三', '四', '五', '六', '七', '八', '九'];
+
+/// 日時の末尾に付けられるタイムゾーン略称とUTCからの時差（分）
+///
+/// 略称からは夏時間かどうかが決まるため、PSTとPDTのように別々に持つ。
+/// 複数の地域で使われる略称は一つに決め打ちしている。特にCSTは米国中部標準時として扱うため、
+/// 中国標準時は「+08:00」「UTC+8」のように時差で指定する。
+const ZONE_ABBREVIATIONS: [(&str, i32); 14] = [
+    ("UTC", 0),
+    ("GMT", 0),
+    ("JST", 9 * 60),
+    ("KST", 9 * 60),
+    ("PST", -8 * 60),
+    ("PDT", -7 * 60),
+    ("MST", -7 * 60),
+    ("MDT", -6 * 60),
+    ("CST", -6 * 60),
+    ("CDT", -5 * 60),
+    ("EST", -5 * 60),
+    ("EDT", -4 * 60),
+    ("CET", 60),
+    ("CEST", 2 * 60),
+];
+
+/// タイムゾーンを除いた日時部分として受け付ける形式
+const ZONED_DATETIME_FORMATS: [&str; 7] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%m/%d/%Y %H:%M",
+    "%Y/%m/%d %H:%M",
+    "%Y年%m月%d日 %H:%M",
+];
+
+/// 同じ日の開始・終了時刻の組
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+/// 文字列中の「10:00-11:00」「10時〜11時半」のような時間範囲を探す
+///
+/// 戻り値は見つかった範囲と、その表現が現れるバイト位置。
+/// 時刻には「:」または「時」が必要で、「07-01」のような日付は範囲とみなさない。
+pub fn find_time_range(text: &str) -> Option<(TimeRange, Range<usize>)> {
+    for (begin, c) in text.char_indices() {
+        if !c.is_ascii_digit() {
+            continue;
+        }
+        // 数字の途中からは解析しない
+        if text[..begin].chars().next_back().is_some_and(|p| p.is_ascii_digit() || p == ':') {
+            continue;
+        }
+
+        let Some((start, start_len)) = parse_time_token(&text[begin..]) else {
+            continue;
+        };
+        let rest = &text[begin + start_len..];
+        let after_space = rest.trim_start();
+        let Some(separator) = after_space.chars().next().filter(|c| RANGE_SEPARATORS.contains(c)) else {
+            continue;
+        };
+        let end_part = after_space[separator.len_utf8()..].trim_start();
+        let Some((end, end_len)) = parse_time_token(end_part) else {
+            continue;
+        };
+
+        let end_offset = text.len() - end_part.len() + end_len;
+        return Some((TimeRange { start, end }, begin..end_offset));
+    }
+    None
+}
+
+/// 「2025-07-01 10:00-11:00」のような値を開始・終了の日時文字列に分割する
+///
+/// 日付部分は両方に引き継ぎ（同日とみなす）、日付がない場合は`today`を使う。
+/// 時間範囲が含まれない場合は`None`を返す。
+pub fn split_time_range(value: &str, today: NaiveDate) -> Option<(String, String)> {
+    let (range, position) = find_time_range(value)?;
+    let date_part = value[..position.start].trim();
+    let date_part = if date_part.is_empty() {
+        today.format("%Y-%m-%d").to_string()
+    } else {
+        date_part.to_string()
+    };
+
+    Some((
+        format!("{} {}", date_part, range.start.format("%H:%M")),
+        format!("{} {}", date_part, range.end.format("%H:%M")),
+    ))
+}
+
+/// 「2025-07-02」「7/2」「明日」のような日付を解析する（月日のみの場合は`today`の年）
+pub fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let text = text.trim();
+    match text {
+        "今日" | "today" => return Some(today),
+        "明日" | "tomorrow" => return today.succ_opt(),
+        "明後日" => return today.succ_opt()?.succ_opt(),
+        _ => {}
+    }
+
+    for format in ["%Y-%m-%d", "%Y/%m/%d", "%Y年%m月%d日"] {
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return Some(date);
+        }
+    }
+    for format in ["%m/%d", "%m月%d日"] {
+        let with_year = format!("{}/{}", today.format("%Y"), text);
+        if let Ok(date) = NaiveDate::parse_from_str(&with_year, &format!("%Y/{}", format)) {
+            return Some(date);
+        }
+    }
+    None
+}
+
+/// 「午後3時」「朝十時半」のような時刻表現を「15:00」「10:30」形式に置き換える
+///
+/// 時間帯が付かない「15時」も「15:00」に変換する。日時フォーマットでの解析前に使う。
+/// 「夜12時」は翌日の「00:00」とし、直前に日付があればその日付も翌日に繰り上げる。
+pub fn normalize_japanese_time(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        // 数字の途中からは解析しない
+        let inside_number = result.chars().next_back().is_some_and(|p| p.is_ascii_digit() || p == ':');
+        if !inside_number {
+            if let Some((time, next_day, consumed)) = parse_daypart_time(rest) {
+                if next_day {
+                    advance_trailing_date(&mut result);
+                }
+                result.push_str(&time.format("%H:%M").to_string());
+                rest = &rest[consumed..];
+                continue;
+            }
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+/// 文字列末尾の日付（「2025-07-01 」など）を翌日に書き換える。日付がなければ何もしない
+fn advance_trailing_date(text: &mut String) {
+    let end = text.trim_end().len();
+    let start = text[..end]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    for (parse_format, output_format) in ROLLOVER_DATE_FORMATS {
+        if let Some(next) = NaiveDate::parse_from_str(&text[start..end], parse_format)
+            .ok()
+            .and_then(|date| date.succ_opt())
+        {
+            text.replace_range(start..end, &next.format(output_format).to_string());
+            return;
+        }
+    }
+}
+
+/// 末尾のタイムゾーン指定（「JST」「PST」「UTC+9」「+09:00」など）を切り離す
+///
+/// 戻り値は指定を除いた日時部分と時差。空白で区切られた指定がなければ`None`。
+pub fn split_zone_suffix(text: &str) -> Option<(&str, FixedOffset)> {
+    let (rest, token) = text.trim_end().rsplit_once(char::is_whitespace)?;
+    Some((rest.trim_end(), parse_zone(token)?))
+}
+
+/// 「2025-07-01 10:00 PST」のように末尾にタイムゾーンが付いた日時を解析する
+///
+/// 「10am PST」「15:00 JST」のように時刻だけの場合は、そのタイムゾーンでの`now`の日付とする。
+/// タイムゾーンがない場合や日時部分を解析できない場合は`None`（呼び出し側で既定のタイムゾーンとして扱う）。
+pub fn parse_datetime_with_zone(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let (local, offset) = split_zone_suffix(text)?;
+    let naive = ZONED_DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(local, format).ok())
+        .or_else(|| {
+            let time = parse_clock_time(local)?;
+            Some(now.with_timezone(&offset).date_naive().and_time(time))
+        })?;
+    offset.from_local_datetime(&naive).single().map(|dt| dt.with_timezone(&Utc))
+}
+
+/// 「15:00」「10am」「3:30 pm」のような時刻だけの表現を解析する
+fn parse_clock_time(text: &str) -> Option<NaiveTime> {
+    let lower = text.trim().to_ascii_lowercase();
+    let (body, afternoon) = if let Some(body) = lower.strip_suffix("am") {
+        (body.trim_end(), Some(false))
+    } else if let Some(body) = lower.strip_suffix("pm") {
+        (body.trim_end(), Some(true))
+    } else {
+        (lower.as_str(), None)
+    };
+
+    let mut parts = body.split(':');
+    let hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = match parts.next() {
+        Some(minute) => minute.parse().ok()?,
+        // 「10」だけでは時刻か分からないため、am/pmが付いている場合に限る
+        None if afternoon.is_some() => 0,
+        None => return None,
+    };
+    let second: u32 = match parts.next() {
+        Some(second) => second.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let hour = match afternoon {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(afternoon) => hour % 12 + if afternoon { 12 } else { 0 },
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// タイムゾーン略称、または「+09:00」「+0900」「UTC-5」形式の時差を解析する
+fn parse_zone(token: &str) -> Option<FixedOffset> {
+    let upper = token.to_ascii_uppercase();
+    if let Some((_, minutes)) = ZONE_ABBREVIATIONS.iter().find(|(name, _)| *name == upper) {
+        return FixedOffset::east_opt(minutes * 60);
+    }
+
+    let signed = upper
+        .strip_prefix("UTC")
+        .or_else(|| upper.strip_prefix("GMT"))
+        .unwrap_or(&upper);
+    let (sign, digits) = match signed.chars().next()? {
+        '+' => (1, &signed[1..]),
+        '-' => (-1, &signed[1..]),
+        _ => return None,
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit() || c == ':') {
+        return None;
+    }
+    let (hours, minutes): (i32, i32) = match digits.split_once(':') {
+        Some((hours, minutes)) => (hours.parse().ok()?, minutes.parse().ok()?),
+        None if digits.len() == 4 => (digits[..2].parse().ok()?, digits[2..].parse().ok()?),
+        None if digits.len() <= 2 => (digits.parse().ok()?, 0),
+        None => return None,
+    };
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// 先頭の「(時間帯)N時」を解析し、24時間制の時刻、翌日になるかどうか、消費したバイト数を返す
+fn parse_daypart_time(text: &str) -> Option<(NaiveTime, bool, usize)> {
+    let daypart = DAYPARTS
+        .iter()
+        .find_map(|(word, afternoon, midnight)| text.strip_prefix(word).map(|_| (word.len(), *afternoon, *midnight)));
+    let prefix_len = match daypart {
+        Some((word_len, _, _)) => word_len + (text[word_len..].len() - text[word_len..].trim_start().len()),
+        None => 0,
+    };
+    let body = &text[prefix_len..];
+
+    let (time, len) = if let Some((hour, hour_len)) = parse_kanji_hour(body) {
+        let after_hour = body[hour_len..].strip_prefix('時')?;
+        let (minute, minute_len) = parse_minute_suffix(after_hour)?;
+        (NaiveTime::from_hms_opt(hour, minute, 0)?, hour_len + '時'.len_utf8() + minute_len)
+    } else {
+        let (time, len) = parse_time_token(body)?;
+        // 時間帯のない「10:00」はそのままで解析できる
+        if daypart.is_none() && !body[..len].contains('時') {
+            return None;
+        }
+        (time, len)
+    };
+
+    let next_day = matches!(daypart, Some((_, _, true))) && time.hour() == 12;
+    let hour = match daypart {
+        _ if next_day => 0,
+        Some((_, true, _)) if time.hour() < 12 => time.hour() + 12,
+        Some((_, false, _)) if time.hour() == 12 => 0,
+        _ => time.hour(),
+    };
+    Some((time.with_hour(hour)?, next_day, prefix_len + len))
+}
+
+/// 先頭の漢数字（一〜二十三）を時として解析する
+fn parse_kanji_hour(text: &str) -> Option<(u32, usize)> {
+    let digit = |c: &char| KANJI_DIGITS.iter().position(|k| k == c).map(|i| i as u32 + 1);
+    let chars: Vec<char> = text.chars().take(3).collect();
+    let (value, count) = match chars.as_slice() {
+        [tens, '十', ones, ..] if digit(tens).is_some() && digit(ones).is_some() => {
+            (digit(tens)? * 10 + digit(ones)?, 3)
+        }
+        [tens, '十', ..] if digit(tens).is_some() => (digit(tens)? * 10, 2),
+        ['十', ones, ..] if digit(ones).is_some() => (10 + digit(ones)?, 2),
+        ['十', ..] => (10, 1),
+        [ones, ..] => (digit(ones)?, 1),
+        [] => return None,
+    };
+    // 漢数字はすべて3バイト
+    (value <= 23).then_some((value, count * '十'.len_utf8()))
+}
+
+/// 「時」の後ろの「半」「30分」を解析し、分と消費したバイト数を返す
+fn parse_minute_suffix(after_hour: &str) -> Option<(u32, usize)> {
+    if after_hour.starts_with('半') {
+        return Some((30, '半'.len_utf8()));
+    }
+    let minute_len = after_hour.chars().take_while(|c| c.is_ascii_digit()).count();
+    if minute_len > 0 && minute_len <= 2 && after_hour[minute_len..].starts_with('分') {
+        let minute = after_hour[..minute_len].parse().ok()?;
+        return Some((minute, minute_len + '分'.len_utf8()));
+    }
+    Some((0, 0))
+}
+
+/// 対応している表示言語
+pub const LANGUAGES: [&str; 2] = ["ja", "en"];
+
+/// 曜日の表記（"ja"は「木」、それ以外は「Thu」）
+pub fn weekday_label(weekday: Weekday, language: &str) -> &'static str {
+    let index = weekday.num_days_from_monday() as usize;
+    if language == "ja" {
+        ["月", "火", "水", "木", "金", "土", "日"][index]
+    } else {
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"][index]
+    }
+}
+
+/// 曜日付きの日付表記（"ja"は「7月10日(木)」、それ以外は「Thu, Jul 10」）
+pub fn format_localized_date(date: NaiveDate, language: &str) -> String {
+    let weekday = weekday_label(date.weekday(), language);
+    if language == "ja" {
+        format!("{}月{}日({})", date.month(), date.day(), weekday)
+    } else {
+        format!("{}, {}", weekday, date.format("%b %-d"))
+    }
+}
+
+/// 「10:00」「10時」「10時半」「10時30分」形式の時刻を先頭から解析する
+///
+/// 戻り値は時刻と消費したバイト数。
+fn parse_time_token(text: &str) -> Option<(NaiveTime, usize)> {
+    let hour_len = text.chars().take_while(|c| c.is_ascii_digit()).count();
+    if hour_len == 0 || hour_len > 2 {
+        return None;
+    }
+    let hour: u32 = text[..hour_len].parse().ok()?;
+    let rest = &text[hour_len..];
+
+    if let Some(minute_text) = rest.strip_prefix(':') {
+        let minute_len = minute_text.chars().take_while(|c| c.is_ascii_digit()).count();
+        if minute_len != 2 {
+            return None;
+        }
+        let minute: u32 = minute_text[..2].parse().ok()?;
+        let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+        return Some((time, hour_len + 1 + minute_len));
+    }
+
+    let after_hour = rest.strip_prefix('時')?;
+    let (minute, minute_len) = parse_minute_suffix(after_hour)?;
+
+    let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+    Some((time, hour_len + '時'.len_utf8() + minute_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hm(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_colon_range() {
+        let (range, _) = find_time_range("10:00-11:00").unwrap();
+        assert_eq!(range, TimeRange { start: hm(10, 0), end: hm(11, 0) });
+    }
+
+    #[test]
+    fn test_japanese_range_with_half_hour() {
+        let (range, position) = find_time_range("明日 10時〜11時半 会議").unwrap();
+        assert_eq!(range, TimeRange { start: hm(10, 0), end: hm(11, 30) });
+        assert_eq!(&"明日 10時〜11時半 会議"[position], "10時〜11時半");
+    }
+
+    #[test]
+    fn test_split_keeps_date_for_both_ends() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        assert_eq!(
+            split_time_range("2025-07-02 10:00 ～ 11:15", today),
+            Some(("2025-07-02 10:00".to_string(), "2025-07-02 11:15".to_string()))
+        );
+        assert_eq!(
+            split_time_range("9:30-10:00", today),
+            Some(("2025-07-01 09:30".to_string(), "2025-07-01 10:00".to_string()))
+        );
+        assert_eq!(split_time_range("2025-07-01", today), None);
+    }
+
+    #[test]
+    fn test_parse_date_keywords_and_formats() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        assert_eq!(parse_date("明日", today), NaiveDate::from_ymd_opt(2025, 7, 2));
+        assert_eq!(parse_date("2025-08-10", today), NaiveDate::from_ymd_opt(2025, 8, 10));
+        assert_eq!(parse_date("7/15", today), NaiveDate::from_ymd_opt(2025, 7, 15));
+        assert_eq!(parse_date("来週", today), None);
+    }
+
+    #[test]
+    fn test_normalize_dayparts() {
+        assert_eq!(normalize_japanese_time("午後3時"), "15:00");
+        assert_eq!(normalize_japanese_time("午前12時"), "00:00");
+        assert_eq!(normalize_japanese_time("朝10時"), "10:00");
+        assert_eq!(normalize_japanese_time("夕方6時半"), "18:30");
+        assert_eq!(normalize_japanese_time("夜8時15分"), "20:15");
+        assert_eq!(normalize_japanese_time("午後 3:30"), "15:30");
+    }
+
+    #[test]
+    fn test_midnight_rolls_over_to_next_day() {
+        assert_eq!(normalize_japanese_time("午後12時"), "12:00");
+        assert_eq!(normalize_japanese_time("夜12時"), "00:00");
+        assert_eq!(normalize_japanese_time("2025-07-01 夜12時"), "2025-07-02 00:00");
+        assert_eq!(normalize_japanese_time("2025年7月31日 夜十二時半"), "2025年8月1日 00:30");
+        assert_eq!(normalize_japanese_time("2025/12/31 夜12時"), "2026/01/01 00:00");
+    }
+
+    #[test]
+    fn test_normalize_kanji_hours_and_dates() {
+        assert_eq!(normalize_japanese_time("午後三時"), "15:00");
+        assert_eq!(normalize_japanese_time("朝十時半"), "10:30");
+        assert_eq!(normalize_japanese_time("夜十一時"), "23:00");
+        assert_eq!(normalize_japanese_time("2025年7月1日 15時"), "2025年7月1日 15:00");
+        assert_eq!(normalize_japanese_time("2025-07-01T10:00:00Z"), "2025-07-01T10:00:00Z");
+    }
+
+    #[test]
+    fn test_datetime_with_zone_suffix() {
+        let utc = |hour: u32| Utc.with_ymd_and_hms(2025, 7, 1, hour, 0, 0).unwrap();
+        let now = utc(12);
+
+        assert_eq!(parse_datetime_with_zone("2025-07-01 10:00 PST", now), Some(utc(18)));
+        assert_eq!(parse_datetime_with_zone("2025-07-01 15:00 JST", now), Some(utc(6)));
+        assert_eq!(parse_datetime_with_zone("2025-07-01 10:00 est", now), Some(utc(15)));
+        assert_eq!(parse_datetime_with_zone("2025-07-01 10:00 UTC", now), Some(utc(10)));
+        assert_eq!(parse_datetime_with_zone("2025-07-01 10:00 UTC+9", now), Some(utc(1)));
+        assert_eq!(parse_datetime_with_zone("2025-07-01 10:00 -05:00", now), Some(utc(15)));
+
+        // タイムゾーンがなければ呼び出し側の既定に任せる
+        assert_eq!(parse_datetime_with_zone("2025-07-01 10:00", now), None);
+        assert_eq!(parse_datetime_with_zone("2025-07-01 10:00 会議", now), None);
+
+        // 時刻だけならそのタイムゾーンでの今日の日付とする
+        assert_eq!(parse_datetime_with_zone("10am PST", now), Some(utc(18)));
+        assert_eq!(parse_datetime_with_zone("3:30 pm UTC", now), Some(utc(15) + chrono::Duration::minutes(30)));
+        assert_eq!(parse_datetime_with_zone("15:00 JST", now), Some(utc(6)));
+        assert_eq!(parse_datetime_with_zone("15 JST", now), None);
+        assert_eq!(parse_datetime_with_zone("13pm JST", now), None);
+    }
+
+    #[test]
+    fn test_localized_date_has_weekday() {
+        // 2025-07-10は木曜日
+        let date = NaiveDate::from_ymd_opt(2025, 7, 10).unwrap();
+        assert_eq!(format_localized_date(date, "ja"), "7月10日(木)");
+        assert_eq!(format_localized_date(date, "en"), "Thu, Jul 10");
+        assert_eq!(weekday_label(Weekday::Sun, "ja"), "日");
+    }
+}
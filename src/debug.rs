@@ -1,4 +1,5 @@
 /// デバッグ情報を制御するためのモジュール
+use crate::output::status;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 /// グローバルなデバッグフラグ
@@ -17,34 +18,34 @@ pub fn is_debug_enabled() -> bool {
 /// デバッグ情報を出力する関数
 pub fn debug_print(msg: &str) {
     if is_debug_enabled() {
-        eprintln!("🔍 DEBUG: {}", msg);
+        status(&format!("🔍 DEBUG: {}", msg));
     }
 }
 
 /// エラーデバッグ用の関数
 pub fn debug_error(msg: &str) {
     if is_debug_enabled() {
-        eprintln!("🔍 DEBUG ERROR: {}", msg);
+        status(&format!("🔍 DEBUG ERROR: {}", msg));
     }
 }
 
 /// 成功デバッグ用の関数
 pub fn debug_success(msg: &str) {
     if is_debug_enabled() {
-        eprintln!("🔍 DEBUG SUCCESS: {}", msg);
+        status(&format!("🔍 DEBUG SUCCESS: {}", msg));
     }
 }
 
 /// 警告デバッグ用の関数
 pub fn debug_warn(msg: &str) {
     if is_debug_enabled() {
-        eprintln!("🔍 DEBUG WARN: {}", msg);
+        status(&format!("🔍 DEBUG WARN: {}", msg));
     }
 }
 
 /// セパレーター出力用の関数
 pub fn debug_separator(label: &str) {
     if is_debug_enabled() {
-        eprintln!("🔍 DEBUG: ======== {} ========", label);
+        status(&format!("🔍 DEBUG: ======== {} ========", label));
     }
 }
@@ -0,0 +1,210 @@
+use crate::config::{Config, ConfigManager};
+use crate::llm::{LLMClient, MockLLMClient, LLM};
+use crate::storage::Storage;
+use anyhow::Result;
+use colored::*;
+use schedule_ai_agent::GoogleCalendarClient;
+use std::fs;
+use std::path::Path;
+
+/// 診断項目ごとの結果
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+    pub hint: Option<&'static str>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            name,
+            passed: false,
+            detail: detail.into(),
+            hint: Some(hint),
+        }
+    }
+
+    fn print(&self) {
+        if self.passed {
+            println!("{} {} - {}", "✓".green(), self.name.bold(), self.detail);
+        } else {
+            println!("{} {} - {}", "✗".red(), self.name.bold(), self.detail.red());
+            if let Some(hint) = self.hint {
+                println!("    → {}", hint.yellow());
+            }
+        }
+    }
+}
+
+/// すべての診断を実行して結果を表示する。すべて成功した場合にtrueを返す
+pub async fn run(use_mock_llm: bool) -> Result<bool> {
+    println!("{}", "=== 環境診断 ===".bold().blue());
+
+    let mut results = Vec::new();
+
+    // 設定ファイル
+    let config_manager = ConfigManager::new();
+    let loaded = match &config_manager {
+        Ok(manager) => manager.load_config(),
+        Err(e) => Err(anyhow::anyhow!("{}", e)),
+    };
+    let config = match loaded {
+        Ok(config) => {
+            results.push(CheckResult::pass("設定ファイル", "読み込みと検証に成功しました"));
+            config
+        }
+        Err(e) => {
+            results.push(CheckResult::fail(
+                "設定ファイル",
+                e.to_string(),
+                "`config init` でサンプルを生成し、config.toml の記述を確認してください",
+            ));
+            Config::default()
+        }
+    };
+
+    // LLM接続
+    let llm: Result<Box<dyn LLM>> = if use_mock_llm {
+        Ok(Box::new(MockLLMClient::new()))
+    } else {
+        LLMClient::from_config(&config).map(|client| Box::new(client) as Box<dyn LLM>)
+    };
+    results.push(match llm {
+        Ok(llm) => check_llm(llm.as_ref()).await,
+        Err(e) => CheckResult::fail(
+            "LLM接続",
+            e.to_string(),
+            "GEMINI_API_KEY を設定するか、config.toml の gemini_api_key（api_key_source = \"keyring\" の場合は config set-key）を確認してください",
+        ),
+    });
+
+    // Google Calendar
+    results.push(check_google_calendar(&config).await);
+
+    // ディレクトリの書き込み権限
+    match &config_manager {
+        Ok(manager) => results.push(check_writable("設定ディレクトリ", manager.get_config_directory_path())),
+        Err(e) => results.push(CheckResult::fail(
+            "設定ディレクトリ",
+            e.to_string(),
+            "ホームディレクトリが存在し、書き込み可能か確認してください",
+        )),
+    }
+    match Storage::new() {
+        Ok(storage) => results.push(check_writable("データディレクトリ", storage.get_data_directory_path())),
+        Err(e) => results.push(CheckResult::fail(
+            "データディレクトリ",
+            e.to_string(),
+            "ホームディレクトリが存在し、書き込み可能か確認してください",
+        )),
+    }
+
+    println!();
+    for result in &results {
+        result.print();
+    }
+
+    let failed = results.iter().filter(|r| !r.passed).count();
+    println!();
+    if failed == 0 {
+        println!("{}", "すべてのチェックに成功しました。".green());
+    } else {
+        println!("{}", format!("{}件のチェックに失敗しました。", failed).red());
+    }
+
+    Ok(failed == 0)
+}
+
+/// LLMへの接続を確認する
+pub async fn check_llm(llm: &dyn LLM) -> CheckResult {
+    match llm.test_connection().await {
+        Ok(()) => CheckResult::pass("LLM接続", "接続テストに成功しました"),
+        Err(e) => CheckResult::fail(
+            "LLM接続",
+            e.to_string(),
+            "APIキー・base_url・model の設定とネットワーク接続を確認してください",
+        ),
+    }
+}
+
+/// Google Calendarの認証とプライマリカレンダーへのアクセスを確認する
+async fn check_google_calendar(config: &Config) -> CheckResult {
+    let google_config = match &config.google_calendar {
+        Some(google_config) => google_config,
+        None => {
+            return CheckResult::fail(
+                "Google Calendar",
+                "設定が見つかりません",
+                "config.toml に [google_calendar] セクションを追加してください",
+            )
+        }
+    };
+
+    // 診断中に認証を始めて入力待ちにならないよう、保存済みのトークンだけで確認する
+    let client = match GoogleCalendarClient::new_without_auth_prompt(
+        google_config
+            .client_secret_path
+            .as_deref()
+            .unwrap_or("~/.schedule_ai_agent/client_secret.json"),
+        google_config
+            .token_cache_path
+            .as_deref()
+            .unwrap_or("token_cache.json"),
+    )
+    .await
+    {
+        Ok(client) => client,
+        Err(e) => {
+            return CheckResult::fail(
+                "Google Calendar",
+                e.to_string(),
+                "client_secret.json を配置し、`calendar auth` で認証してください",
+            )
+        }
+    };
+
+    match client.ping().await {
+        Ok(()) => CheckResult::pass("Google Calendar", "プライマリカレンダーにアクセスできました"),
+        Err(e) => CheckResult::fail(
+            "Google Calendar",
+            e.to_string(),
+            "トークンキャッシュを削除して `calendar auth` で再認証してください",
+        ),
+    }
+}
+
+/// ディレクトリに書き込めるか確認する
+fn check_writable(name: &'static str, dir: &Path) -> CheckResult {
+    let probe = dir.join(".doctor_write_test");
+    match fs::write(&probe, b"ok").and_then(|_| fs::remove_file(&probe)) {
+        Ok(()) => CheckResult::pass(name, format!("書き込み可能です ({})", dir.display())),
+        Err(e) => CheckResult::fail(
+            name,
+            format!("書き込みできません ({}): {}", dir.display(), e),
+            "ディレクトリの所有者とパーミッションを確認してください",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_llm_check_passes_with_mock() {
+        let result = check_llm(&MockLLMClient::new()).await;
+
+        assert!(result.passed);
+        assert!(result.hint.is_none());
+    }
+}
@@ -27,14 +27,18 @@ pub struct HistoryCommand;
 
 #[async_trait]
 impl CommandHandler for HistoryCommand {
-    async fn execute(&self, _args: Vec<&str>, scheduler: &mut Scheduler) -> Result<CommandResult> {
+    async fn execute(&self, args: Vec<&str>, scheduler: &mut Scheduler) -> Result<CommandResult> {
+        if args.contains(&"--json") {
+            schedule_ai_agent::output::print_json(&serde_json::to_value(scheduler.conversation_history())?)?;
+            return Ok(CommandResult::Continue);
+        }
         println!("📋 === 会話履歴 ===");
         println!("{}", scheduler.get_conversation_summary());
         Ok(CommandResult::Continue)
     }
 
     fn help(&self) -> &str {
-        "会話履歴を表示します"
+        "会話履歴を表示します。使用法: history [--json]"
     }
 
     fn aliases(&self) -> Vec<&str> {
@@ -99,8 +103,19 @@ pub struct SyncCommand;
 
 #[async_trait]
 impl CommandHandler for SyncCommand {
-    async fn execute(&self, _args: Vec<&str>, scheduler: &mut Scheduler) -> Result<CommandResult> {
-        match scheduler.sync_with_google_calendar().await {
+    async fn execute(&self, args: Vec<&str>, scheduler: &mut Scheduler) -> Result<CommandResult> {
+        let limit = match args.iter().position(|arg| *arg == "--limit") {
+            Some(index) => match args.get(index + 1).and_then(|value| value.parse::<usize>().ok()) {
+                Some(limit) => Some(limit),
+                None => {
+                    eprintln!("❌ {}", "--limit には件数を指定してください".red());
+                    return Ok(CommandResult::Continue);
+                }
+            },
+            None => None,
+        };
+
+        match scheduler.sync_with_google_calendar(limit).await {
             Ok(sync_result) => {
                 println!("🔄 {}", sync_result.green());
             }
@@ -112,7 +127,7 @@ impl CommandHandler for SyncCommand {
     }
 
     fn help(&self) -> &str {
-        "Google Calendarと同期します"
+        "Google Calendarと同期します。使用法: sync [--limit 件数]"
     }
 }
 
@@ -328,3 +343,69 @@ impl Default for InteractiveMode {
         Self::new()
     }
 }
+
+/// 入力から1行だけ読み、エージェントの応答を書き出す（スクリプトからの利用向け）
+///
+/// `json`がtrueの場合は入力と応答をJSONで書き出す。
+pub async fn run_single_message<R: BufRead, W: Write>(
+    scheduler: &mut Scheduler,
+    input: R,
+    out: &mut W,
+    json: bool,
+) -> Result<()> {
+    let message = match input.lines().next() {
+        Some(line) => line?,
+        None => anyhow::bail!("標準入力からメッセージを読み取れませんでした"),
+    };
+    let message = message.trim().to_string();
+
+    let response = scheduler.process_user_input(message.clone()).await?;
+    if json {
+        schedule_ai_agent::output::write_json(
+            out,
+            &serde_json::json!({ "input": message, "response": response }),
+        )?;
+    } else {
+        writeln!(out, "{}", response)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::llm::MockLLMClient;
+    use crate::storage::Storage;
+    use crate::test_support::TempDir;
+
+    #[tokio::test]
+    async fn test_single_message_from_stdin_prints_response() -> Result<()> {
+        let data_dir = TempDir::new();
+        let mut scheduler = Scheduler::from_parts(
+            Arc::new(MockLLMClient::new()),
+            Storage::with_data_dir(data_dir.to_path_buf())?,
+            Config::default(),
+            true,
+        )?;
+
+        let mut out = Vec::new();
+        run_single_message(&mut scheduler, "こんにちは\n".as_bytes(), &mut out, false).await?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            "申し訳ございませんが、その要求を理解できませんでした。\n"
+        );
+
+        let mut out = Vec::new();
+        run_single_message(&mut scheduler, "こんにちは".as_bytes(), &mut out, true).await?;
+        let printed: serde_json::Value = serde_json::from_slice(&out)?;
+        assert_eq!(printed["input"], "こんにちは");
+        assert_eq!(printed["response"], "申し訳ございませんが、その要求を理解できませんでした。");
+
+        // 入力が空なら失敗する
+        assert!(run_single_message(&mut scheduler, "".as_bytes(), &mut Vec::new(), false).await.is_err());
+
+        Ok(())
+    }
+
+}
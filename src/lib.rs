@@ -1,16 +1,158 @@
 // デバッグ用のモジュール
 pub mod debug;
+// 日時表現の解析
+pub mod datetime;
+// 出力先の制御
+pub mod output;
+// 現在時刻の取得元
+pub mod clock;
 
 use google_calendar3::{CalendarHub, oauth2, api::Event, api::Events};
+use google_calendar3::api::{CalendarListEntry, EventReminders, FreeBusyRequest, FreeBusyRequestItem, FreeBusyResponse};
 use hyper_rustls::HttpsConnectorBuilder;
+use oauth2::authenticator_delegate::InstalledFlowDelegate;
 use oauth2::{InstalledFlowAuthenticator, InstalledFlowReturnMethod};
 use chrono::Utc;
 use anyhow::Result;
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+/// OAuth認証で、ブラウザから認可コードを受け取る方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthFlow {
+    /// ローカルの空きポートで受け取る（既定）
+    #[default]
+    Redirect,
+    /// 指定したポートで受け取る
+    RedirectPort(u16),
+    /// 表示されたURLをブラウザで開き、コードを貼り付ける（SSH先などlocalhostに届かない環境向け）
+    Interactive,
+}
+
+/// 設定で指定できる認証方法
+pub const AUTH_FLOWS: [&str; 2] = ["redirect", "interactive"];
+
+impl AuthFlow {
+    /// 設定値（"redirect" / "interactive"）とポート番号から認証方法を決める
+    pub fn from_settings(flow: Option<&str>, port: Option<u16>) -> Option<Self> {
+        match (flow.unwrap_or("redirect"), port) {
+            ("interactive", _) => Some(AuthFlow::Interactive),
+            ("redirect", Some(port)) => Some(AuthFlow::RedirectPort(port)),
+            ("redirect", None) => Some(AuthFlow::Redirect),
+            _ => None,
+        }
+    }
+
+    fn return_method(self) -> InstalledFlowReturnMethod {
+        match self {
+            AuthFlow::Redirect => InstalledFlowReturnMethod::HTTPRedirect,
+            AuthFlow::RedirectPort(port) => InstalledFlowReturnMethod::HTTPPortRedirect(port),
+            AuthFlow::Interactive => InstalledFlowReturnMethod::Interactive,
+        }
+    }
+}
+
+/// 認証ページを既定のブラウザで開く
+fn open_in_browser(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    let status = command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("ブラウザの起動に失敗しました（{}）", status)))
+    }
+}
+
+/// 認可URLの案内文を作る（`open_browser`がtrueなら`launch`でブラウザを開く）
+///
+/// ブラウザが開かない環境でも認証が止まったように見えないよう、URLは必ず単独の行に表示する。
+pub fn auth_url_prompt(url: &str, need_code: bool, open_browser: bool, launch: impl Fn(&str) -> std::io::Result<()>) -> String {
+    let mut prompt = if open_browser && launch(url).is_ok() {
+        "🌐 ブラウザで認証ページを開きました。開かない場合は次のURLをブラウザで開いてください:".to_string()
+    } else {
+        "🌐 次のURLをブラウザで開いて、Googleアカウントで認証してください:".to_string()
+    };
+    prompt.push_str(&format!("\n\n{}\n", url));
+    if need_code {
+        prompt.push_str("\n表示された認可コードを貼り付けてください: ");
+    } else {
+        prompt.push_str("\n認証が完了するまでお待ちください...");
+    }
+    prompt
+}
+
+/// 認可URLを表示し、設定に応じてブラウザで開く
+struct AuthUrlPresenter {
+    open_browser: bool,
+}
+
+impl InstalledFlowDelegate for AuthUrlPresenter {
+    fn present_user_url<'a>(
+        &'a self,
+        url: &'a str,
+        need_code: bool,
+    ) -> Pin<Box<dyn Future<Output = std::result::Result<String, String>> + Send + 'a>> {
+        Box::pin(async move {
+            eprintln!("{}", auth_url_prompt(url, need_code, self.open_browser, open_in_browser));
+            if !need_code {
+                return Ok(String::new());
+            }
+            let mut code = String::new();
+            std::io::stdin()
+                .read_line(&mut code)
+                .map_err(|e| format!("認可コードを読み取れませんでした: {}", e))?;
+            Ok(code.trim().to_string())
+        })
+    }
+}
+
+/// 認証を求められたら操作を待たずに失敗させる（`doctor`などの診断用）
+struct NoAuthPrompt;
+
+impl InstalledFlowDelegate for NoAuthPrompt {
+    fn present_user_url<'a>(
+        &'a self,
+        _url: &'a str,
+        _need_code: bool,
+    ) -> Pin<Box<dyn Future<Output = std::result::Result<String, String>> + Send + 'a>> {
+        Box::pin(async { Err("認証されていません。`calendar auth` で認証してください".to_string()) })
+    }
+}
+
+/// トークンキャッシュを使うOAuth認証器を作成する
+async fn installed_flow_authenticator(
+    secret: oauth2::ApplicationSecret,
+    return_method: InstalledFlowReturnMethod,
+    delegate: Box<dyn InstalledFlowDelegate>,
+    token_cache_path: &str,
+) -> Result<oauth2::authenticator::Authenticator<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>> {
+    Ok(InstalledFlowAuthenticator::builder(secret, return_method)
+        .flow_delegate(delegate)
+        .persist_tokens_to_disk(token_cache_path)
+        .build()
+        .await?)
+}
 
 /// Google Calendar APIクライアント
 pub struct GoogleCalendarClient {
     hub: CalendarHub<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>,
+    /// 通知の指定がない予定を作成するときに付ける通知
+    default_reminders: Option<EventReminders>,
+    /// API呼び出し1回あたりの制限時間（Noneなら制限なし）
+    request_timeout: Option<std::time::Duration>,
 }
 
 impl GoogleCalendarClient {
@@ -36,6 +178,14 @@ impl GoogleCalendarClient {
                 return Ok(config_dir_path);
             }
         }
+
+        // XDG設定ディレクトリ（$XDG_CONFIG_HOME/schedule_ai_agent）で検索
+        if let Some(xdg_dir) = xdg_config_dir() {
+            let xdg_path = xdg_dir.join(client_secret_path);
+            if xdg_path.exists() {
+                return Ok(xdg_path);
+            }
+        }
         
         // プロジェクトルートディレクトリの.schedule_ai_agentディレクトリで検索
         let mut current = std::env::current_dir()?;
@@ -61,13 +211,54 @@ impl GoogleCalendarClient {
             1. {}\n\
             2. カレントディレクトリ\n\
             3. ~/.schedule_ai_agent/\n\
-            4. プロジェクトの.schedule_ai_agentディレクトリ",
-            client_secret_path
+            4. {}\n\
+            5. プロジェクトの.schedule_ai_agentディレクトリ",
+            client_secret_path,
+            xdg_config_dir().map_or_else(
+                || "$XDG_CONFIG_HOME/schedule_ai_agent/".to_string(),
+                |dir| format!("{}/", dir.display())
+            )
         ))
     }
 
     /// 新しいGoogle Calendar クライアントを作成
-    pub async fn new(client_secret_path: &str, token_cache_path: &str) -> Result<Self> {
+    ///
+    /// `open_browser`がfalseの場合、認証が必要になってもブラウザは開かずURLの表示だけを行う。
+    pub async fn new(
+        client_secret_path: &str,
+        token_cache_path: &str,
+        auth_flow: AuthFlow,
+        open_browser: bool,
+    ) -> Result<Self> {
+        Self::build(
+            client_secret_path,
+            token_cache_path,
+            auth_flow.return_method(),
+            Box::new(AuthUrlPresenter { open_browser }),
+        )
+        .await
+    }
+
+    /// 認証済みのトークンだけを使うクライアントを作成する
+    ///
+    /// 未認証やトークンの更新に失敗した場合、ブラウザでの認証を始めずにAPI呼び出しがエラーになる。
+    pub async fn new_without_auth_prompt(client_secret_path: &str, token_cache_path: &str) -> Result<Self> {
+        // 認可コードを受け取る待ち受けを始めないよう、コードの入力を求める方式にして入力前に失敗させる
+        Self::build(
+            client_secret_path,
+            token_cache_path,
+            InstalledFlowReturnMethod::Interactive,
+            Box::new(NoAuthPrompt),
+        )
+        .await
+    }
+
+    async fn build(
+        client_secret_path: &str,
+        token_cache_path: &str,
+        return_method: InstalledFlowReturnMethod,
+        delegate: Box<dyn InstalledFlowDelegate>,
+    ) -> Result<Self> {
         // client_secret.jsonファイルを検索
         let actual_client_secret_path = Self::find_client_secret_file(client_secret_path)?;
         
@@ -85,31 +276,50 @@ impl GoogleCalendarClient {
             .map_err(|e| anyhow::anyhow!("client_secret.json の読み込みに失敗しました: {} (パス: {})", e, actual_client_secret_path.display()))?;
 
         // 認証器を作成
-        let auth = InstalledFlowAuthenticator::builder(
-            secret,
-            InstalledFlowReturnMethod::HTTPRedirect,
-        )
-        .persist_tokens_to_disk(token_cache_path)
-        .build()
-        .await?;
+        let auth = installed_flow_authenticator(secret, return_method, delegate, token_cache_path).await?;
 
         // Calendar APIのハブを作成
         let hub = CalendarHub::new(client, auth);
 
-        Ok(Self { hub })
+        Ok(Self { hub, default_reminders: None, request_timeout: None })
+    }
+
+    /// 通知の指定がない予定を作成するときに付ける通知を設定する
+    pub fn with_default_reminders(mut self, reminders: Option<EventReminders>) -> Self {
+        self.default_reminders = reminders;
+        self
+    }
+
+    /// API呼び出し1回あたりの制限時間を設定する（Noneなら制限なし）
+    pub fn with_request_timeout(mut self, limit: Option<std::time::Duration>) -> Self {
+        self.request_timeout = limit;
+        self
+    }
+
+    /// APIを呼び出す（request_timeoutを超えたら打ち切り、429・503ならRetry-Afterに従って再試行する）
+    async fn call_api<T, F, Fut>(&self, call: F) -> google_calendar3::Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = google_calendar3::Result<T>>,
+    {
+        call_with_retries(self.request_timeout, call).await
     }
 
     /// イベントを取得する
     pub async fn get_events(&self, calendar_id: &str, max_results: i32) -> Result<Events> {
-        let result = self.hub
-            .events()
-            .list(calendar_id)
-            .time_min(Utc::now())
-            .max_results(max_results)
-            .single_events(true)
-            .order_by("startTime")
-            .doit()
-            .await?;
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .list(calendar_id)
+                    .time_min(Utc::now())
+                    .max_results(max_results)
+                    .single_events(true)
+                    .order_by("startTime")
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
 
         Ok(result.1)
     }
@@ -119,6 +329,62 @@ impl GoogleCalendarClient {
         self.get_events("primary", max_results).await
     }
 
+    /// プライマリカレンダーを取得できるか確認する（軽量な疎通確認）
+    ///
+    /// 未認証の場合はここで認証が行われる（`new_without_auth_prompt`で作成した場合はエラーになる）。
+    pub async fn ping(&self) -> Result<()> {
+        // 認証の操作を待つことがあるため、制限時間は設けない
+        self.hub
+            .calendar_list()
+            .get("primary")
+            .doit()
+            .await
+            .map_err(|e| explain_auth_port_error(api_error(e)))?;
+        Ok(())
+    }
+
+    /// 認証済みアカウントのメールアドレス（プライマリカレンダーのID）を取得する
+    pub async fn primary_calendar_email(&self) -> Result<String> {
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .calendar_list()
+                    .get("primary")
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
+
+        result.1.id.ok_or_else(|| anyhow::anyhow!("プライマリカレンダーのIDが取得できません"))
+    }
+
+    /// アカウントから参照できるカレンダーの一覧を取得する
+    pub async fn list_calendars(&self) -> Result<Vec<CalendarListEntry>> {
+        let mut calendars = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let (_, list) = self
+                .call_api(|| {
+                    let mut call = self.hub.calendar_list().list().max_results(250);
+                    if let Some(token) = &page_token {
+                        call = call.page_token(token);
+                    }
+                    call.doit()
+                })
+                .await
+                .map_err(api_error)?;
+            calendars.extend(list.items.unwrap_or_default());
+
+            match list.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        Ok(calendars)
+    }
+
     /// イベントの詳細情報を表示する
     pub fn display_events(&self, events: &Events) {
         println!("取得されたイベント数: {}", events.items.as_ref().map_or(0, |v| v.len()));
@@ -170,12 +436,21 @@ impl GoogleCalendarClient {
     }
 
     /// イベントを作成する
-    pub async fn create_event(&self, calendar_id: &str, event: Event) -> Result<Event> {
-        let result = self.hub
-            .events()
-            .insert(event, calendar_id)
-            .doit()
-            .await?;
+    pub async fn create_event(&self, calendar_id: &str, mut event: Event) -> Result<Event> {
+        apply_default_reminders(&mut event, self.default_reminders.as_ref());
+        let has_attachments = event.attachments.as_ref().is_some_and(|a| !a.is_empty());
+        let is_out_of_office = event.event_type.as_deref() == Some("outOfOffice");
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .insert(event.clone(), calendar_id)
+                    .supports_attachments(true)
+                    .doit()
+            })
+            .await
+            .map_err(|e| explain_attachment_error(api_error(e), has_attachments))
+            .map_err(|e| explain_out_of_office_error(e, is_out_of_office))?;
 
         Ok(result.1)
     }
@@ -186,28 +461,40 @@ impl GoogleCalendarClient {
     }
 
     /// イベントを削除する
-    pub async fn delete_event(&self, calendar_id: &str, event_id: &str) -> Result<()> {
-        self.hub
-            .events()
-            .delete(calendar_id, event_id)
-            .doit()
-            .await?;
+    ///
+    /// `send_updates`には参加者への通知方法（all / externalOnly / none）を指定する。
+    pub async fn delete_event(&self, calendar_id: &str, event_id: &str, send_updates: &str) -> Result<()> {
+        self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .delete(calendar_id, event_id)
+                    .send_updates(send_updates)
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
 
         Ok(())
     }
 
     /// プライマリカレンダーのイベントを削除する
-    pub async fn delete_primary_event(&self, event_id: &str) -> Result<()> {
-        self.delete_event("primary", event_id).await
+    pub async fn delete_primary_event(&self, event_id: &str, send_updates: &str) -> Result<()> {
+        self.delete_event("primary", event_id, send_updates).await
     }
 
     /// イベントを更新する
     pub async fn update_event(&self, calendar_id: &str, event_id: &str, event: Event) -> Result<Event> {
-        let result = self.hub
-            .events()
-            .update(event, calendar_id, event_id)
-            .doit()
-            .await?;
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .update(event.clone(), calendar_id, event_id)
+                    .supports_attachments(true)
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
 
         Ok(result.1)
     }
@@ -217,6 +504,55 @@ impl GoogleCalendarClient {
         self.update_event("primary", event_id, event).await
     }
 
+    /// イベントを部分更新する（指定したフィールドのみ変更）
+    pub async fn patch_event(&self, calendar_id: &str, event_id: &str, event: Event) -> Result<Event> {
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .patch(event.clone(), calendar_id, event_id)
+                    .supports_attachments(true)
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
+
+        Ok(result.1)
+    }
+
+    /// プライマリカレンダーのイベントを部分更新する
+    pub async fn patch_primary_event(&self, event_id: &str, event: Event) -> Result<Event> {
+        self.patch_event("primary", event_id, event).await
+    }
+
+    /// 指定したカレンダー（参加者のメールアドレスなど）の予定が入っている時間帯を取得する
+    ///
+    /// 共有されていないカレンダーは、応答の`errors`に理由が入る。
+    pub async fn query_free_busy(
+        &self,
+        calendar_ids: &[String],
+        time_min: chrono::DateTime<chrono::Utc>,
+        time_max: chrono::DateTime<chrono::Utc>,
+    ) -> Result<FreeBusyResponse> {
+        let request = FreeBusyRequest {
+            items: Some(
+                calendar_ids
+                    .iter()
+                    .map(|id| FreeBusyRequestItem { id: Some(id.clone()) })
+                    .collect(),
+            ),
+            time_min: Some(time_min),
+            time_max: Some(time_max),
+            ..Default::default()
+        };
+        let result = self
+            .call_api(|| self.hub.freebusy().query(request.clone()).doit())
+            .await
+            .map_err(api_error)?;
+
+        Ok(result.1)
+    }
+
     /// 指定した日時範囲のイベントを取得する
     pub async fn get_events_in_range(
         &self,
@@ -225,20 +561,87 @@ impl GoogleCalendarClient {
         time_max: chrono::DateTime<chrono::Utc>,
         max_results: i32,
     ) -> Result<Events> {
-        let result = self.hub
-            .events()
-            .list(calendar_id)
-            .time_min(time_min)
-            .time_max(time_max)
-            .max_results(max_results)
-            .single_events(true)
-            .order_by("startTime")
-            .doit()
-            .await?;
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .list(calendar_id)
+                    .time_min(time_min)
+                    .time_max(time_max)
+                    .max_results(max_results)
+                    .single_events(true)
+                    .order_by("startTime")
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
+
+        Ok(result.1)
+    }
+
+    /// 指定時刻以降のイベントを終了日時の上限なしで取得する
+    pub async fn get_upcoming_events(
+        &self,
+        calendar_id: &str,
+        time_min: chrono::DateTime<chrono::Utc>,
+        max_results: i32,
+    ) -> Result<Events> {
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .list(calendar_id)
+                    .time_min(time_min)
+                    .max_results(max_results)
+                    .single_events(true)
+                    .order_by("startTime")
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
 
         Ok(result.1)
     }
 
+    /// 指定期間のイベントをページングしながらすべて取得する
+    pub async fn get_all_events_in_range(
+        &self,
+        calendar_id: &str,
+        time_min: chrono::DateTime<chrono::Utc>,
+        time_max: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<Event>> {
+        let mut all_events = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let (_, events) = self
+                .call_api(|| {
+                    let mut call = self.hub
+                        .events()
+                        .list(calendar_id)
+                        .time_min(time_min)
+                        .time_max(time_max)
+                        .max_results(250)
+                        .single_events(true)
+                        .order_by("startTime");
+                    if let Some(token) = &page_token {
+                        call = call.page_token(token);
+                    }
+                    call.doit()
+                })
+                .await
+                .map_err(api_error)?;
+            all_events.extend(events.items.unwrap_or_default());
+
+            match events.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        Ok(all_events)
+    }
+
     /// EventDataからGoogle CalendarのEventを作成する
     pub async fn create_event_from_event_data(&self, 
         title: &str,
@@ -316,7 +719,7 @@ impl GoogleCalendarClient {
 
         let mut event = Event::default();
         event.summary = Some(title.to_string());
-        event.description = description.map(|s| s.to_string());
+        event.description = description.map(|s| render_description_template(s, title, start_time, end_time));
         event.location = location.map(|s| s.to_string());
         
         event.start = Some(EventDateTime {
@@ -337,11 +740,15 @@ impl GoogleCalendarClient {
 
     /// 指定されたIDのイベントを取得する
     pub async fn get_event_by_id(&self, calendar_id: &str, event_id: &str) -> Result<Event> {
-        let result = self.hub
-            .events()
-            .get(calendar_id, event_id)
-            .doit()
-            .await?;
+        let result = self
+            .call_api(|| {
+                self.hub
+                    .events()
+                    .get(calendar_id, event_id)
+                    .doit()
+            })
+            .await
+            .map_err(api_error)?;
 
         Ok(result.1)
     }
@@ -352,6 +759,208 @@ impl GoogleCalendarClient {
     }
 }
 
+/// 認証用のローカルポートが使用中で失敗した場合に、別の認証方法を案内する
+pub fn explain_auth_port_error(error: anyhow::Error) -> anyhow::Error {
+    let message = format!("{:#}", error);
+    let lower = message.to_lowercase();
+    if lower.contains("address already in use") || lower.contains("addrinuse") || lower.contains("os error 98") || lower.contains("os error 10048") {
+        anyhow::anyhow!(
+            "認証用のポートが使用中のため認証できませんでした。config.toml の [google_calendar] で \
+            redirect_port を変更するか、auth_flow = \"interactive\" を指定してください: {}",
+            message
+        )
+    } else {
+        error
+    }
+}
+
+/// Google APIのエラーからHTTPステータス・理由・メッセージを取り出して1行にまとめる
+///
+/// スコープや権限の問題を調べられるよう、Googleが返したエラー本文の内容をそのまま残す。
+pub fn describe_api_error(error: &google_calendar3::Error) -> String {
+    match error {
+        google_calendar3::Error::BadRequest(body) => {
+            let detail = &body["error"];
+            let mut parts = Vec::new();
+            if let Some(code) = detail["code"].as_i64() {
+                parts.push(format!("HTTP {}", code));
+            }
+            if let Some(status) = detail["status"].as_str() {
+                parts.push(status.to_string());
+            }
+            if let Some(reason) = detail["errors"][0]["reason"].as_str() {
+                parts.push(format!("reason={}", reason));
+            }
+            match (parts.is_empty(), detail["message"].as_str()) {
+                (true, _) => body.to_string(),
+                (false, Some(message)) => format!("{}: {}", parts.join(" "), message),
+                (false, None) => parts.join(" "),
+            }
+        }
+        google_calendar3::Error::Failure(response) => format!("HTTP {}", response.status()),
+        other => format!("{:?}", other),
+    }
+}
+
+/// `call`を`limit`の時間内に制限する（Noneなら制限なし）
+///
+/// 時間切れは通信のタイムアウトとして、他のAPIエラーと同じように扱う。
+async fn with_timeout<T>(
+    limit: Option<std::time::Duration>,
+    call: impl Future<Output = google_calendar3::Result<T>>,
+) -> google_calendar3::Result<T> {
+    match limit {
+        Some(limit) => match tokio::time::timeout(limit, call).await {
+            Ok(result) => result,
+            Err(_) => Err(google_calendar3::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!(
+                    "Google Calendar APIが{}秒以内に応答しませんでした（app.request_timeout_secs で変更できます）",
+                    limit.as_secs()
+                ),
+            ))),
+        },
+        None => call.await,
+    }
+}
+
+/// レート制限（429）や一時的な障害（503）で再試行する最大回数
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Retry-Afterで待つ時間の上限
+pub const MAX_RETRY_AFTER: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Retry-Afterが指定されていない場合の待ち時間
+pub const DEFAULT_RETRY_AFTER: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Retry-Afterヘッダーの値（秒数またはHTTP日付）から待ち時間を求める（上限はMAX_RETRY_AFTER）
+pub fn retry_after_delay(value: &str, now: chrono::DateTime<Utc>) -> Option<std::time::Duration> {
+    let value = value.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(seconds) => std::time::Duration::from_secs(seconds),
+        Err(_) => {
+            let retry_at = chrono::DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+            (retry_at - now).to_std().unwrap_or(std::time::Duration::ZERO)
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+/// 再試行すべきAPIエラー（429・503）であれば、再試行までの待ち時間を返す
+///
+/// Googleがエラー本文をJSONで返した場合はヘッダーが残らないため、DEFAULT_RETRY_AFTERだけ待つ。
+fn api_retry_delay(error: &google_calendar3::Error, now: chrono::DateTime<Utc>) -> Option<std::time::Duration> {
+    match error {
+        google_calendar3::Error::Failure(response) if matches!(response.status().as_u16(), 429 | 503) => Some(
+            response
+                .headers()
+                .get(hyper::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| retry_after_delay(value, now))
+                .unwrap_or(DEFAULT_RETRY_AFTER),
+        ),
+        google_calendar3::Error::BadRequest(body) if matches!(body["error"]["code"].as_i64(), Some(429 | 503)) => {
+            Some(DEFAULT_RETRY_AFTER)
+        }
+        _ => None,
+    }
+}
+
+/// `call`を呼び出し、レート制限や一時的な障害ならRetry-Afterの指示どおり待ってから再試行する
+async fn call_with_retries<T, F, Fut>(limit: Option<std::time::Duration>, call: F) -> google_calendar3::Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = google_calendar3::Result<T>>,
+{
+    let mut retries = 0;
+    loop {
+        let result = with_timeout(limit, call()).await;
+        let delay = match &result {
+            Err(error) if retries < MAX_RATE_LIMIT_RETRIES => api_retry_delay(error, Utc::now()),
+            _ => None,
+        };
+        let Some(delay) = delay else {
+            return result;
+        };
+
+        retries += 1;
+        output::status(&format!(
+            "Google Calendar APIのレート制限に達しました。{}秒後に再試行します（{}/{}）",
+            delay.as_secs(),
+            retries,
+            MAX_RATE_LIMIT_RETRIES
+        ));
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Google APIのエラーをanyhowのエラーにする（デバッグモードでは元のエラーの詳細を記録する）
+///
+/// 再試行しても解消しなかったレート制限・一時的な障害は、時間をおいてやり直すよう案内する。
+fn api_error(error: google_calendar3::Error) -> anyhow::Error {
+    debug::debug_error(&format!("Google APIエラー: {}", describe_api_error(&error)));
+    if api_retry_delay(&error, Utc::now()).is_some() {
+        let message = format!(
+            "Google Calendar APIが混み合っているため完了できませんでした（{}）。しばらく待ってからやり直してください",
+            describe_api_error(&error)
+        );
+        return anyhow::Error::from(error).context(message);
+    }
+    error.into()
+}
+
+/// 添付ファイルのアクセス権が原因と思われるエラーを分かりやすいメッセージにする
+fn explain_attachment_error(error: anyhow::Error, has_attachments: bool) -> anyhow::Error {
+    let message = error.to_string();
+    let lower = message.to_lowercase();
+    if has_attachments && (lower.contains("403") || lower.contains("forbidden") || lower.contains("permission")) {
+        anyhow::anyhow!(
+            "添付ファイルにアクセスできません。Google Driveの共有設定とアクセス権を確認してください: {}",
+            message
+        )
+    } else {
+        error
+    }
+}
+
+/// 不在の予定を作成できなかった場合に、対応していないカレンダーである可能性を説明する
+///
+/// 不在の予定はGoogle Workspaceのプライマリカレンダーでのみ作成できる。
+fn explain_out_of_office_error(error: anyhow::Error, is_out_of_office: bool) -> anyhow::Error {
+    let message = error.to_string();
+    let lower = message.to_lowercase();
+    if is_out_of_office && (lower.contains("eventtype") || lower.contains("outofoffice") || lower.contains("400") || lower.contains("invalid")) {
+        anyhow::anyhow!(
+            "このカレンダーでは不在の予定を作成できません（Google Workspaceのプライマリカレンダーが必要です）: {}",
+            message
+        )
+    } else {
+        error
+    }
+}
+
+/// 説明文中の`{{date}}`・`{{start}}`・`{{end}}`・`{{title}}`を予定の値で置き換える
+///
+/// 日時は日本時間で展開する。`{{`を含まない説明文はそのまま返す。
+pub fn render_description_template(
+    template: &str,
+    title: &str,
+    start_time: chrono::DateTime<Utc>,
+    end_time: chrono::DateTime<Utc>,
+) -> String {
+    if !template.contains("{{") {
+        return template.to_string();
+    }
+
+    let start_jst = start_time.with_timezone(&chrono_tz::Asia::Tokyo);
+    let end_jst = end_time.with_timezone(&chrono_tz::Asia::Tokyo);
+    template
+        .replace("{{date}}", &start_jst.format("%Y-%m-%d").to_string())
+        .replace("{{start}}", &start_jst.format("%H:%M").to_string())
+        .replace("{{end}}", &end_jst.format("%H:%M").to_string())
+        .replace("{{title}}", title)
+}
+
 /// イベント作成用のビルダーパターン
 pub struct EventBuilder {
     event: Event,
@@ -403,6 +1012,77 @@ impl EventBuilder {
         self
     }
 
+    /// 参加者を設定（メールアドレスの一覧。空なら参加者なし）
+    pub fn attendees(mut self, emails: &[String]) -> Self {
+        use google_calendar3::api::EventAttendee;
+        if !emails.is_empty() {
+            self.event.attendees = Some(
+                emails
+                    .iter()
+                    .map(|email| EventAttendee {
+                        email: Some(email.clone()),
+                        ..Default::default()
+                    })
+                    .collect(),
+            );
+        }
+        self
+    }
+
+    /// イベントの色を設定（Google Calendarの色ID "1"〜"11"）
+    pub fn color_id(mut self, color_id: &str) -> Self {
+        self.event.color_id = Some(color_id.to_string());
+        self
+    }
+
+    /// 予定の表示方法を設定（"opaque": 予定あり / "transparent": 空き時間）
+    pub fn transparency(mut self, transparency: &str) -> Self {
+        self.event.transparency = Some(transparency.to_string());
+        self
+    }
+
+    /// 予定の公開範囲を設定（"default" / "private" / "public"）
+    pub fn visibility(mut self, visibility: &str) -> Self {
+        self.event.visibility = Some(visibility.to_string());
+        self
+    }
+
+    /// 不在（outOfOffice）の予定にする。期間中の招待は自動で辞退される
+    pub fn out_of_office(mut self, decline_message: Option<&str>) -> Self {
+        use google_calendar3::api::EventOutOfOfficeProperties;
+        self.event.event_type = Some("outOfOffice".to_string());
+        self.event.out_of_office_properties = Some(EventOutOfOfficeProperties {
+            auto_decline_mode: Some("declineAllConflictingInvitations".to_string()),
+            decline_message: decline_message.map(|message| message.to_string()),
+        });
+        self
+    }
+
+    /// 資料のURLを添付する（タイトルにはURLの末尾を使う）
+    pub fn attachments(mut self, urls: &[String]) -> Self {
+        use google_calendar3::api::EventAttachment;
+        if urls.is_empty() {
+            return self;
+        }
+        let attachments = urls
+            .iter()
+            .map(|url| EventAttachment {
+                file_url: Some(url.clone()),
+                title: Some(
+                    url.trim_end_matches('/')
+                        .rsplit('/')
+                        .next()
+                        .filter(|name| !name.is_empty())
+                        .unwrap_or(url)
+                        .to_string(),
+                ),
+                ..Default::default()
+            })
+            .collect();
+        self.event.attachments = Some(attachments);
+        self
+    }
+
     /// イベントを構築
     pub fn build(self) -> Event {
         self.event
@@ -414,3 +1094,318 @@ impl Default for EventBuilder {
         Self::new()
     }
 }
+
+/// XDG Base Directoryに従った設定ディレクトリ
+///
+/// `$XDG_CONFIG_HOME`が未設定（または相対パス）の場合は`~/.config`を使う。
+fn xdg_config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(base.join("schedule_ai_agent"))
+}
+
+/// 通知の指定がない予定に既定の通知を付ける（予定ごとの指定と不在の予定はそのまま）
+fn apply_default_reminders(event: &mut Event, defaults: Option<&EventReminders>) {
+    if event.reminders.is_some() || event.event_type.as_deref() == Some("outOfOffice") {
+        return;
+    }
+    event.reminders = defaults.cloned();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_after_accepts_seconds_and_http_date() {
+        let now = chrono::DateTime::parse_from_rfc3339("2025-07-01T00:00:00Z").unwrap().with_timezone(&Utc);
+
+        assert_eq!(retry_after_delay("2", now), Some(std::time::Duration::from_secs(2)));
+        assert_eq!(
+            retry_after_delay("Tue, 01 Jul 2025 00:00:05 GMT", now),
+            Some(std::time::Duration::from_secs(5))
+        );
+        // 過去の日時なら待たない、長すぎる指定は上限で打ち切る
+        assert_eq!(retry_after_delay("Mon, 30 Jun 2025 23:59:00 GMT", now), Some(std::time::Duration::ZERO));
+        assert_eq!(retry_after_delay("3600", now), Some(MAX_RETRY_AFTER));
+        assert_eq!(retry_after_delay("soon", now), None);
+    }
+
+    #[test]
+    fn test_calendar_rate_limit_is_retried_after_delay() {
+        // 再試行の案内は状態表示に書き出すため、出力先を切り替えるテストと並行しないようにする
+        let _guard = crate::output::TEST_LOG_TARGET_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let rate_limited = || {
+            let response = hyper::Response::builder()
+                .status(429)
+                .header(hyper::header::RETRY_AFTER, "0")
+                .body(hyper::Body::empty())
+                .unwrap();
+            google_calendar3::Error::Failure(response)
+        };
+        let now = Utc::now();
+        assert_eq!(api_retry_delay(&rate_limited(), now), Some(std::time::Duration::ZERO));
+        let unavailable = google_calendar3::Error::BadRequest(serde_json::json!({ "error": { "code": 503 } }));
+        assert_eq!(api_retry_delay(&unavailable, now), Some(DEFAULT_RETRY_AFTER));
+        let forbidden = google_calendar3::Error::BadRequest(serde_json::json!({ "error": { "code": 403 } }));
+        assert_eq!(api_retry_delay(&forbidden, now), None);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            // 2回レート制限に達したあと成功する
+            let calls = std::cell::Cell::new(0);
+            let result = call_with_retries(None, || {
+                calls.set(calls.get() + 1);
+                let attempt = calls.get();
+                async move {
+                    if attempt < 3 {
+                        Err(rate_limited())
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            })
+            .await;
+            assert_eq!(result.unwrap(), 3);
+
+            // 再試行の上限を超えたら諦める
+            calls.set(0);
+            let result: google_calendar3::Result<()> = call_with_retries(None, || {
+                calls.set(calls.get() + 1);
+                async { Err(rate_limited()) }
+            })
+            .await;
+            assert!(result.is_err());
+            assert_eq!(calls.get(), MAX_RATE_LIMIT_RETRIES + 1);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fires_for_slow_request() {
+        let slow = async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            Ok(())
+        };
+
+        let result = with_timeout(Some(std::time::Duration::from_millis(10)), slow).await;
+
+        assert!(result.unwrap_err().to_string().contains("応答しませんでした"));
+        assert_eq!(with_timeout(Some(std::time::Duration::from_secs(5)), async { Ok(1) }).await.unwrap(), 1);
+        assert_eq!(with_timeout(None, async { Ok(2) }).await.unwrap(), 2);
+    }
+
+    #[test]
+    fn test_auth_flow_from_settings() {
+        assert_eq!(AuthFlow::from_settings(None, None), Some(AuthFlow::Redirect));
+        assert_eq!(AuthFlow::from_settings(Some("redirect"), Some(8085)), Some(AuthFlow::RedirectPort(8085)));
+        assert_eq!(AuthFlow::from_settings(Some("interactive"), Some(8085)), Some(AuthFlow::Interactive));
+        assert_eq!(AuthFlow::from_settings(Some("browser"), None), None);
+
+        let in_use = std::io::Error::from(std::io::ErrorKind::AddrInUse);
+        let explained = explain_auth_port_error(anyhow::anyhow!("{} (os error 98)", in_use));
+        assert!(explained.to_string().contains("auth_flow = \"interactive\""));
+        assert!(!explain_auth_port_error(anyhow::anyhow!("invalid_grant")).to_string().contains("auth_flow"));
+    }
+
+    #[test]
+    fn test_auth_url_is_printed_when_browser_launch_is_disabled() {
+        let url = "https://accounts.google.com/o/oauth2/auth?client_id=abc";
+        let launched = std::cell::Cell::new(false);
+        let launch = |_: &str| {
+            launched.set(true);
+            Ok(())
+        };
+
+        let prompt = auth_url_prompt(url, false, false, launch);
+        assert!(!launched.get());
+        assert!(prompt.contains(&format!("\n{}\n", url)));
+        assert!(prompt.contains("次のURLをブラウザで開いて"));
+
+        // ブラウザを開けなかった場合もURLを表示する
+        let prompt = auth_url_prompt(url, true, true, |_| Err(std::io::Error::other("no browser")));
+        assert!(prompt.contains(&format!("\n{}\n", url)));
+        assert!(prompt.contains("認可コードを貼り付けて"));
+
+        let prompt = auth_url_prompt(url, false, true, launch);
+        assert!(launched.get());
+        assert!(prompt.contains("ブラウザで認証ページを開きました"));
+    }
+
+    #[test]
+    fn test_api_error_details_are_logged_in_debug_mode() {
+        use crate::output::{set_log_target, LogTarget, TEST_LOG_TARGET_LOCK};
+
+        let _guard = TEST_LOG_TARGET_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let log_path = std::env::temp_dir().join(format!("schedule_ai_agent_test_{}.log", uuid::Uuid::new_v4()));
+        set_log_target(LogTarget::File(log_path.clone()));
+        let body = serde_json::json!({
+            "error": {
+                "code": 403,
+                "status": "PERMISSION_DENIED",
+                "message": "Request had insufficient authentication scopes.",
+                "errors": [{ "reason": "insufficientPermissions", "domain": "global" }]
+            }
+        });
+
+        // デバッグモードでなければ記録しない（切り替えはロックを持っている間だけ行い、元に戻す）
+        let was_debug = debug::is_debug_enabled();
+        debug::set_debug_mode(false);
+        api_error(google_calendar3::Error::BadRequest(body.clone()));
+        debug::set_debug_mode(true);
+        let error = api_error(google_calendar3::Error::BadRequest(body));
+        debug::set_debug_mode(was_debug);
+        set_log_target(LogTarget::Stderr);
+
+        let logged = std::fs::read_to_string(&log_path).unwrap();
+        let api_lines: Vec<&str> = logged.lines().filter(|line| line.contains("Google APIエラー")).collect();
+        assert_eq!(api_lines.len(), 1);
+        assert!(api_lines[0].contains(
+            "HTTP 403 PERMISSION_DENIED reason=insufficientPermissions: Request had insufficient authentication scopes."
+        ));
+        assert!(error.to_string().contains("Bad Request"));
+        let _ = std::fs::remove_file(log_path);
+    }
+
+    #[tokio::test]
+    async fn test_authenticator_without_prompt_fails_instead_of_waiting() {
+        let dir = std::env::temp_dir().join(format!("schedule_ai_agent_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let secret = oauth2::ApplicationSecret {
+            client_id: "client-id".to_string(),
+            client_secret: "client-secret".to_string(),
+            auth_uri: "https://accounts.google.com/o/oauth2/auth".to_string(),
+            token_uri: "https://oauth2.googleapis.com/token".to_string(),
+            redirect_uris: vec!["http://localhost".to_string()],
+            ..Default::default()
+        };
+        let token_cache = dir.join("token_cache.json");
+
+        let auth = installed_flow_authenticator(
+            secret,
+            InstalledFlowReturnMethod::Interactive,
+            Box::new(NoAuthPrompt),
+            token_cache.to_str().unwrap(),
+        )
+        .await
+        .unwrap();
+        let token = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            auth.token(&["https://www.googleapis.com/auth/calendar"]),
+        )
+        .await
+        .expect("認証の入力待ちにならないはず");
+
+        assert!(token.is_err());
+        assert!(!token_cache.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// XDG_CONFIG_HOMEを書き換えるテスト同士が並行して動かないようにするロック
+    static XDG_CONFIG_HOME_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn test_client_secret_found_in_xdg_config_dir() {
+        let _guard = XDG_CONFIG_HOME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let xdg_home = std::env::temp_dir().join(format!("schedule_ai_agent_xdg_{}", uuid::Uuid::new_v4()));
+        let config_dir = xdg_home.join("schedule_ai_agent");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        let file_name = format!("client_secret_{}.json", uuid::Uuid::new_v4());
+        std::fs::write(config_dir.join(&file_name), "{}").unwrap();
+
+        let previous = std::env::var_os("XDG_CONFIG_HOME");
+        std::env::set_var("XDG_CONFIG_HOME", &xdg_home);
+        let found = GoogleCalendarClient::find_client_secret_file(&file_name);
+        match previous {
+            Some(value) => std::env::set_var("XDG_CONFIG_HOME", value),
+            None => std::env::remove_var("XDG_CONFIG_HOME"),
+        }
+        let _ = std::fs::remove_dir_all(&xdg_home);
+
+        assert_eq!(found.unwrap(), config_dir.join(&file_name));
+    }
+
+    #[test]
+    fn test_description_template_placeholders() {
+        use chrono::TimeZone;
+
+        let start = chrono_tz::Asia::Tokyo
+            .with_ymd_and_hms(2025, 7, 1, 10, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        let end = start + chrono::Duration::hours(1);
+
+        assert_eq!(
+            render_description_template("{{date}} {{start}}-{{end}} の{{title}}、議題: ", "定例", start, end),
+            "2025-07-01 10:00-11:00 の定例、議題: "
+        );
+        assert_eq!(render_description_template("議題なし", "定例", start, end), "議題なし");
+    }
+
+    #[test]
+    fn test_builder_sets_transparency_and_visibility() {
+        let event = EventBuilder::new()
+            .summary("集中作業")
+            .transparency("transparent")
+            .visibility("private")
+            .build();
+
+        assert_eq!(event.transparency.as_deref(), Some("transparent"));
+        assert_eq!(event.visibility.as_deref(), Some("private"));
+    }
+
+    #[test]
+    fn test_builder_sets_attachments() {
+        let event = EventBuilder::new()
+            .summary("定例")
+            .attachments(&["https://docs.google.com/document/d/agenda/".to_string()])
+            .build();
+
+        let attachments = event.attachments.unwrap();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].file_url.as_deref(), Some("https://docs.google.com/document/d/agenda/"));
+        assert_eq!(attachments[0].title.as_deref(), Some("agenda"));
+        assert!(EventBuilder::new().attachments(&[]).build().attachments.is_none());
+    }
+
+    #[test]
+    fn test_default_reminders_apply_only_without_overrides() {
+        use google_calendar3::api::EventReminder;
+
+        let reminders = |minutes: &[i32]| EventReminders {
+            use_default: Some(false),
+            overrides: Some(
+                minutes
+                    .iter()
+                    .map(|&minutes| EventReminder { method: Some("popup".to_string()), minutes: Some(minutes) })
+                    .collect(),
+            ),
+        };
+        let defaults = reminders(&[10, 60]);
+        let minutes = |event: &Event| {
+            event
+                .reminders
+                .as_ref()
+                .map(|reminders| reminders.overrides.iter().flatten().filter_map(|o| o.minutes).collect::<Vec<_>>())
+        };
+
+        let mut event = EventBuilder::new().summary("定例").build();
+        apply_default_reminders(&mut event, Some(&defaults));
+        assert_eq!(minutes(&event), Some(vec![10, 60]));
+
+        // 予定ごとに指定された通知は既定値で置き換えない
+        let mut event = EventBuilder::new().summary("定例").build();
+        event.reminders = Some(reminders(&[5]));
+        apply_default_reminders(&mut event, Some(&defaults));
+        assert_eq!(minutes(&event), Some(vec![5]));
+
+        let mut event = EventBuilder::new().out_of_office(None).build();
+        apply_default_reminders(&mut event, Some(&defaults));
+        assert!(event.reminders.is_none());
+
+        let mut event = EventBuilder::new().summary("定例").build();
+        apply_default_reminders(&mut event, None);
+        assert!(event.reminders.is_none());
+    }
+}
@@ -1,24 +1,112 @@
 use crate::config::Config;
-use crate::models::{ActionType, EventData, LLMRequest, LLMResponse, MissingEventData, Priority};
+use crate::models::{ActionType, EventData, LLMRequest, LLMResponse, MissingEventData, Priority, SchedulerError};
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use chrono_tz::Asia::Tokyo;
+use schedule_ai_agent::clock::Clock;
+use schedule_ai_agent::datetime::find_time_range;
+use schedule_ai_agent::output::status;
+use schedule_ai_agent::{retry_after_delay, DEFAULT_RETRY_AFTER, MAX_RATE_LIMIT_RETRIES};
 use serde_json::{Value, json};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
 use std::env; // 追加
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, RwLock};
+
+/// 既定で使うGeminiのモデル
+const DEFAULT_MODEL: &str = "gemini-2.5-flash";
+
+/// 動作を確認しているGeminiのモデル（これ以外を指定すると警告する）
+pub const KNOWN_MODELS: &[&str] = &[
+    "gemini-2.5-flash",
+    "gemini-2.5-pro",
+    "gemini-2.5-flash-lite",
+    "gemini-2.0-flash",
+];
+
+/// 既知のモデル名か
+pub fn is_known_model(model: &str) -> bool {
+    KNOWN_MODELS.contains(&model)
+}
+
+/// 同じ入力に対するLLMの応答を保持するキャッシュ（最近使ったものを残す）
+pub struct ResponseCache {
+    capacity: usize,
+    entries: VecDeque<(u64, LLMResponse)>,
+}
+
+impl ResponseCache {
+    /// `capacity`件まで保持するキャッシュ（0ならキャッシュしない）
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// 入力（空白と大文字小文字を正規化）とコンテキストから求めるキー
+    pub fn key(input: &str, context: &str) -> u64 {
+        let normalized = input.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let mut hasher = DefaultHasher::new();
+        normalized.hash(&mut hasher);
+        context.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// キャッシュされた応答（見つかれば最近使ったものとして扱う）
+    pub fn get(&mut self, key: u64) -> Option<LLMResponse> {
+        let index = self.entries.iter().position(|(entry_key, _)| *entry_key == key)?;
+        let entry = self.entries.remove(index)?;
+        let response = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(response)
+    }
+
+    /// 応答を保存する（上限を超えたら最も古く使われたものを捨てる）
+    pub fn insert(&mut self, key: u64, response: LLMResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(entry_key, _)| *entry_key != key);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, response));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
 
 #[async_trait] // 追加
 pub trait LLM: Send + Sync {
     async fn process_request(&self, request: LLMRequest) -> Result<LLMResponse>;
     async fn test_connection(&self) -> Result<()>;
+
+    /// 現在使っているモデル名（モデルの概念がないクライアントはNone）
+    fn model(&self) -> Option<String> {
+        None
+    }
+
+    /// 以降のリクエストで使うモデルを切り替える
+    fn set_model(&self, _model: &str) -> Result<()> {
+        Err(anyhow!("このLLMクライアントはモデルの切り替えに対応していません"))
+    }
 }
 
 pub struct LLMClient {
     api_key: String,
     base_url: String,
-    model: String,
+    /// 実行中に`set_model`で切り替えられるよう共有参照越しに書き換え可能にしておく
+    model: RwLock<String>,
     temperature: f32,
     max_tokens: u32,
+    clock: Arc<dyn Clock>,
+    /// 1回の通信の制限時間（Noneなら制限なし）
+    request_timeout: Option<std::time::Duration>,
 }
 
 impl LLMClient {
@@ -42,7 +130,7 @@ impl LLMClient {
         let model = llm_config
             .model
             .clone()
-            .unwrap_or_else(|| "gemini-2.5-flash".to_string());
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
 
         let temperature = llm_config.temperature.unwrap_or(0.7);
         let max_tokens = llm_config.max_tokens.unwrap_or(1000);
@@ -50,51 +138,87 @@ impl LLMClient {
         Ok(Self {
             api_key,
             base_url,
-            model,
+            model: RwLock::new(model),
             temperature,
             max_tokens,
+            clock: config.app.clock(),
+            request_timeout: config.app.request_timeout(),
         })
     }
+
+    /// 通信せずに応答の解析だけを試すためのクライアント（日時の補完には`clock`を使う）
+    #[cfg(test)]
+    pub(crate) fn for_parsing(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: String::new(),
+            model: RwLock::new(String::new()),
+            temperature: 0.0,
+            max_tokens: 0,
+            clock,
+            request_timeout: None,
+        }
+    }
+
+    /// 通信のエラーを返す（制限時間を超えた場合は設定の案内を添える）
+    fn request_error(&self, error: reqwest::Error) -> anyhow::Error {
+        match self.request_timeout {
+            Some(limit) if error.is_timeout() => anyhow!(
+                "Gemini APIが{}秒以内に応答しませんでした（app.request_timeout_secs で変更できます）",
+                limit.as_secs()
+            ),
+            _ => SchedulerError::from(error).into(),
+        }
+    }
 }
 
 #[async_trait]
 impl LLM for LLMClient {
     async fn process_request(&self, request: LLMRequest) -> Result<LLMResponse> {
-        let system_prompt = self.create_system_prompt();
-        let user_message = self.create_user_message(&request);
-
-        let client = reqwest::Client::new();
+        let mut builder = reqwest::Client::builder();
+        if let Some(limit) = self.request_timeout {
+            builder = builder.timeout(limit);
+        }
+        let client = builder.build().map_err(SchedulerError::from)?;
         let request_url = format!(
             "{}/models/{}:generateContent?key={}",
-            self.base_url, self.model, self.api_key
+            self.base_url,
+            self.current_model(),
+            self.api_key
         );
 
-        let payload = json!({
-            "contents": [
-                {
-                    "role": "user",
-                    "parts": [
-                        {
-                            "text": format!("{}\n\n{}", system_prompt, user_message)
-                        }
-                    ]
-                }
-            ],
-            "generationConfig": {
-                "temperature": self.temperature,
-                "maxOutputTokens": self.max_tokens
-            }
-        });
+        let payload = self.build_payload(&request);
 
-        let request_builder = client.post(&request_url);
+        // レート制限に達した場合はRetry-Afterの指示どおり待ってから再試行する
+        let mut retries = 0;
+        let response = loop {
+            let response = client
+                .post(&request_url)
+                .json(&payload)
+                .send()
+                .await
+                .map_err(|e| self.request_error(e))?;
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS || retries >= MAX_RATE_LIMIT_RETRIES {
+                break response.error_for_status().map_err(SchedulerError::from)?;
+            }
 
-        let response = request_builder
-            .json(&payload)
-            .send()
-            .await?
-            .error_for_status()?;
+            retries += 1;
+            let delay = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| retry_after_delay(value, Utc::now()))
+                .unwrap_or(DEFAULT_RETRY_AFTER);
+            status(&format!(
+                "Gemini APIのレート制限に達しました。{}秒後に再試行します（{}/{}）",
+                delay.as_secs(),
+                retries,
+                MAX_RATE_LIMIT_RETRIES
+            ));
+            tokio::time::sleep(delay).await;
+        };
 
-        let response_json: Value = response.json().await?;
+        let response_json: Value = response.json().await.map_err(|e| self.request_error(e))?;
 
         let content = response_json["candidates"][0]["content"]["parts"][0]["text"]
             .as_str()
@@ -136,6 +260,7 @@ impl LLM for LLMClient {
                 updated_conversation: Some(updated_conversation),
                 start_time: None, // 開始時刻はまだ不明
                 end_time: None,   // 終了時刻はまだ不明
+                follow_up_actions: Vec::new(),
             });
         }
 
@@ -148,6 +273,7 @@ impl LLM for LLMClient {
             user_input: "こんにちは".to_string(),
             context: None,
             conversation_history: None,
+            temperature: None,
         };
 
         match self.process_request(test_request).await {
@@ -161,9 +287,49 @@ impl LLM for LLMClient {
             }
         }
     }
+
+    fn model(&self) -> Option<String> {
+        Some(self.current_model())
+    }
+
+    fn set_model(&self, model: &str) -> Result<()> {
+        let model = model.trim();
+        if model.is_empty() {
+            return Err(anyhow!("モデル名を指定してください"));
+        }
+        *self.model.write().map_err(|_| anyhow!("モデル設定のロックに失敗しました"))? = model.to_string();
+        Ok(())
+    }
 }
 
 impl LLMClient {
+    fn current_model(&self) -> String {
+        self.model.read().map(|model| model.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Gemini APIへ送るリクエスト本文を組み立てる
+    fn build_payload(&self, request: &LLMRequest) -> Value {
+        let system_prompt = self.create_system_prompt();
+        let user_message = self.create_user_message(request);
+
+        json!({
+            "contents": [
+                {
+                    "role": "user",
+                    "parts": [
+                        {
+                            "text": format!("{}\n\n{}", system_prompt, user_message)
+                        }
+                    ]
+                }
+            ],
+            "generationConfig": {
+                "temperature": request.temperature.unwrap_or(self.temperature),
+                "maxOutputTokens": self.max_tokens
+            }
+        })
+    }
+
     fn create_system_prompt(&self) -> String {
         r#"
 あなたは予定管理AIエージェントです。ユーザーの自然言語入力を解析して、適切なアクションを決定してください。
@@ -176,10 +342,26 @@ impl LLMClient {
 - UPDATE_EVENT: 既存の予定を更新
 - DELETE_EVENT: 予定を削除
 - GET_EVENT_DETAILS: 予定の詳細を取得(予定を詳しく教えてなどとリクエストされた場合)
-- LIST_EVENTS: 予定を簡単に取得
+- LIST_EVENTS: 予定を簡単に取得(「これからの予定」のように期限を区切らない場合はall_upcomingをtrueに設定。「今日の残りの予定」の場合はstart_timeを現在時刻、end_timeを今日の23:59に設定)
 - SEARCH_EVENTS: 予定をタイトル名を基準に検索
+- FIND_NEXT_EVENT: 指定した予定の次回を1件だけ取得(次の○○はいつ？などと聞かれた場合。titleに予定名のキーワードを設定)
+- FIND_FREE_TIME: 空き時間を検索(start_time/end_timeに検索する期間を設定。「明日だけ」のように日付を指定された場合はその日の00:00〜23:59。必要な長さが分かればduration_minutesに分単位で設定)
+- SUMMARIZE_EVENTS: 期間内の予定を要約(今週の予定をまとめて、などとリクエストされた場合。start_time/end_timeに対象期間を設定)
+- OUT_OF_OFFICE: 不在の予定を作成(休暇・出張などで期間中の招待を自動で辞退したい場合。start_time/end_timeに不在の期間、descriptionに辞退時のメッセージを設定)
+- RESCHEDULE_EVENT: 既存の予定を同じ長さのまま次の空き時間へ移動(「会議を空いてる時間に移して」のように移動先の時刻が指定されていない場合。titleに予定名を設定)
 - GENERAL_RESPONSE: 一般的な応答
 
+予定の公開範囲と空き状況:
+- 「非公開で」「プライベートで」などと指定された場合は visibility を "private" に、「公開で」の場合は "public" に設定してください
+- 「集中時間」「空き時間として」「ブロックしないで」など、他の人の予定を妨げない指定の場合は transparency を "transparent" に設定してください
+- 「資料のリンクを添付」などとURLが示された場合は、そのURLを attachments に設定してください
+- 「田中さんを追加して」「佐藤さんを外して」のように既存の予定の参加者を増減する場合は UPDATE_EVENT とし、attendees ではなく add_attendees / remove_attendees に設定してください（追加する参加者はメールアドレスで指定してください）
+- 「説明に議題を追加して」「メモを書き足して」のように既存の予定の説明に追記する場合は UPDATE_EVENT とし、追記する文章を append_description に設定してください（description は説明を丸ごと置き換える場合にだけ使ってください）
+- 「仕事カレンダーの予定を見せて」のようにカレンダーを名前で指定された場合は、そのカレンダーの名前を calendar に設定してください（指定がない場合はnull）
+- 「10分前と1時間前に通知して」のように既存の予定の通知を変更する場合は UPDATE_EVENT とし、reminders に開始の何分前かを数値のリストで設定してください（「通知なしにして」の場合は空のリスト）
+- 予定を作成するときに「30分前に通知して」のように通知が指定された場合も、reminders に設定してください（指定がなければnull）
+- 「会議を作って、今週の予定も見せて」のように1つの依頼に複数の操作が含まれる場合は、最初の操作を action に、続く操作を follow_up_actions に順番どおり設定してください（各要素は action・event_data・response_text を持つオブジェクト、続く操作がない場合は省略）
+
 応答は以下のJSON形式で返してください。
 
 ```json
@@ -192,10 +374,21 @@ impl LLMClient {
         "end_time": "終了時刻（ISO 8601形式、不明な場合はnull）",
         "location": "場所（オプション、不明な場合はnull）",
         "attendees": ["参加者のリスト"],
-        "priority": "Low/Medium/High/Urgent（不明な場合はnull）"
+        "priority": "Low/Medium/High/Urgent（不明な場合はnull）",
+        "transparency": "opaque/transparent（指定がない場合はnull）",
+        "visibility": "default/private/public（指定がない場合はnull）",
+        "duration_minutes": "空き時間検索で必要な長さ（分、指定がない場合はnull）",
+        "all_upcoming": "今後の予定をすべて対象にする場合はtrue（それ以外はnull）",
+        "attachments": ["添付する資料のURLのリスト（「資料のリンクを添付」などと言われた場合）"],
+        "add_attendees": ["既存の予定に追加する参加者のメールアドレス"],
+        "remove_attendees": ["既存の予定から外す参加者の名前またはメールアドレス"],
+        "append_description": "既存の予定の説明に追記する文章（追記しない場合はnull）",
+        "calendar": "予定を参照するカレンダーの名前（「仕事カレンダー」などと指定された場合、それ以外はnull）",
+        "reminders": ["通知を変更する場合、開始の何分前に通知するか（数値、変更しない場合はnull）"]
     },
     "response_text": "ユーザーへの応答メッセージ",
-    "missing_data": "不足している情報の種類（例: Title, StartTime, EndTime, All, またはnull）"
+    "missing_data": "不足している情報の種類（例: Title, StartTime, EndTime, All, またはnull）",
+    "follow_up_actions": [{"action": "続けて実行するアクションタイプ", "event_data": {}, "response_text": ""}]
 }
 ```
 
@@ -253,7 +446,16 @@ impl LLMClient {
             }
         }
 
-        let now_jst = Utc::now().with_timezone(&Tokyo);
+        // 「10:00-11:00」のような時間範囲は解析結果を補足として渡す
+        if let Some((range, _)) = find_time_range(&request.user_input) {
+            message.push_str(&format!(
+                "\n\n入力中の時間範囲: 開始 {} / 終了 {}（同じ日の予定として扱ってください）",
+                range.start.format("%H:%M"),
+                range.end.format("%H:%M")
+            ));
+        }
+
+        let now_jst = self.clock.now().with_timezone(&Tokyo);
         message.push_str(&format!(
             "\n\n現在の日時: {} (JST)",
             now_jst.format("%Y-%m-%d %H:%M:%S")
@@ -263,21 +465,51 @@ impl LLMClient {
     }
 
     fn parse_llm_response(&self, content: &str, request: &LLMRequest) -> Result<LLMResponse> {
-        // contentの最初の7文字（```json）と最後尾の3文字（```）が存在すれば削除
-        let mut content = content.trim();
-        if content.starts_with("```json") {
-            content = &content[7..];
-            content = content.trim_start();
-        }
-        if content.ends_with("```") {
-            content = &content[..content.len() - 3];
-            content = content.trim_end();
-        }
+        // 前後の説明文やコードフェンスを除き、最初のJSONオブジェクトだけを取り出す
+        let content = extract_json_object(content).unwrap_or(content.trim());
 
         // JSON形式での応答を期待
         let response_json: Value = serde_json::from_str(content)
             .map_err(|e| anyhow!("Failed to parse LLM response: {}\nResponse: {}", e, content))?;
 
+        let mut response = self.parse_action_object(&response_json)?;
+        if response_json["response_text"].as_str().is_none() {
+            response.response_text = "No response text provided".to_string();
+        }
+
+        // 「予定を作って一覧も見せて」のように続けて実行するアクション
+        // 読めないものがあっても最初のアクションは活かし、そのアクションだけを読み飛ばす
+        if let Some(follow_ups) = response_json["follow_up_actions"].as_array() {
+            response.follow_up_actions = follow_ups
+                .iter()
+                .filter_map(|follow_up| match self.parse_action_object(follow_up) {
+                    Ok(follow_up) => Some(follow_up),
+                    Err(e) => {
+                        status(&format!("⚠️ 解釈できない後続のアクションを読み飛ばしました: {}", e));
+                        None
+                    }
+                })
+                .collect();
+        }
+
+        // 会話履歴を更新
+        let mut updated_conversation = request.conversation_history.clone().unwrap_or_else(|| {
+            use crate::models::ConversationHistory;
+            ConversationHistory::new()
+        });
+
+        // ユーザーメッセージを追加
+        updated_conversation.add_user_message(request.user_input.clone(), None);
+        
+        // アシスタントメッセージを追加
+        updated_conversation.add_assistant_message(response.response_text.clone(), None);
+
+        response.updated_conversation = Some(updated_conversation);
+        Ok(response)
+    }
+
+    /// action・event_data・response_text を持つJSONオブジェクトを1件分のアクションとして読む
+    fn parse_action_object(&self, response_json: &Value) -> Result<LLMResponse> {
         let action_str = response_json["action"]
             .as_str()
             .ok_or_else(|| anyhow!("Action type is missing in the response"))?;
@@ -301,7 +533,7 @@ impl LLMClient {
 
         let response_text = response_json["response_text"]
             .as_str()
-            .unwrap_or("No response text provided")
+            .unwrap_or_default()
             .to_string();
 
         // 開始時間と終了時間をパース
@@ -325,26 +557,15 @@ impl LLMClient {
             None
         };
 
-        // 会話履歴を更新
-        let mut updated_conversation = request.conversation_history.clone().unwrap_or_else(|| {
-            use crate::models::ConversationHistory;
-            ConversationHistory::new()
-        });
-        
-        // ユーザーメッセージを追加
-        updated_conversation.add_user_message(request.user_input.clone(), None);
-        
-        // アシスタントメッセージを追加
-        updated_conversation.add_assistant_message(response_text.clone(), None);
-
         Ok(LLMResponse {
             action,
             event_data,
             response_text,
             missing_data,
-            updated_conversation: Some(updated_conversation),
+            updated_conversation: None,
             start_time,
             end_time,
+            follow_up_actions: Vec::new(),
         })
     }
 
@@ -356,6 +577,11 @@ impl LLMClient {
             "LIST_EVENTS" => Ok(ActionType::ListEvents),
             "SEARCH_EVENTS" => Ok(ActionType::SearchEvents),
             "GET_EVENT_DETAILS" => Ok(ActionType::GetEventDetails),
+            "SUMMARIZE_EVENTS" => Ok(ActionType::SummarizeEvents),
+            "FIND_NEXT_EVENT" => Ok(ActionType::FindNextEvent),
+            "FIND_FREE_TIME" => Ok(ActionType::FindFreeTime),
+            "OUT_OF_OFFICE" => Ok(ActionType::OutOfOffice),
+            "RESCHEDULE_EVENT" => Ok(ActionType::RescheduleEvent),
             "GENERAL_RESPONSE" => Ok(ActionType::GeneralResponse),
             _ => Ok(ActionType::GeneralResponse), // 未知のアクションタイプはGeneralResponseとして扱う
         }
@@ -369,14 +595,13 @@ impl LLMClient {
         let description = data["description"].as_str().map(|s| s.to_string());
         let location = data["location"].as_str().map(|s| s.to_string());
 
-        let attendees = if let Some(arr) = data["attendees"].as_array() {
-            arr.iter()
-                .filter_map(|v| v.as_str())
-                .map(|s| s.to_string())
-                .collect()
-        } else {
-            Vec::new()
+        let string_list = |value: &Value| -> Vec<String> {
+            value
+                .as_array()
+                .map(|arr| arr.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect())
+                .unwrap_or_default()
         };
+        let attendees = string_list(&data["attendees"]);
 
         let priority = match data["priority"].as_str() {
             Some("Low") => Some(Priority::Low),
@@ -386,6 +611,28 @@ impl LLMClient {
             _ => None,
         };
 
+        // 資料のURL（http/httpsのみ）
+        let attachments = data["attachments"]
+            .as_array()
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str())
+                    .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+                    .map(|url| url.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let transparency = match data["transparency"].as_str() {
+            Some(value @ ("opaque" | "transparent")) => Some(value.to_string()),
+            _ => None,
+        };
+
+        let visibility = match data["visibility"].as_str() {
+            Some(value @ ("default" | "private" | "public")) => Some(value.to_string()),
+            _ => None,
+        };
+
         Ok(EventData {
             id: None,
             title,
@@ -396,6 +643,28 @@ impl LLMClient {
             attendees,
             priority,
             max_results: None,
+            transparency,
+            visibility,
+            duration_minutes: data["duration_minutes"].as_i64().filter(|minutes| *minutes > 0),
+            all_upcoming: data["all_upcoming"].as_bool(),
+            attachments,
+            add_attendees: string_list(&data["add_attendees"]),
+            remove_attendees: string_list(&data["remove_attendees"]),
+            append_description: data["append_description"]
+                .as_str()
+                .filter(|text| !text.trim().is_empty())
+                .map(|text| text.to_string()),
+            calendar: data["calendar"]
+                .as_str()
+                .filter(|name| !name.trim().is_empty())
+                .map(|name| name.to_string()),
+            reminders: data["reminders"].as_array().map(|minutes| {
+                minutes
+                    .iter()
+                    .filter_map(|m| m.as_i64())
+                    .filter(|m| *m >= 0)
+                    .collect()
+            }),
         })
     }
 
@@ -484,16 +753,15 @@ impl LLM for MockLLMClient {
                     description: Some("LLMで解析された予定".to_string()),
                     start_time: Some(start_time.format("%Y-%m-%dT%H:%M:%SZ").to_string()), // 仮の時刻
                     end_time: Some(end_time.format("%Y-%m-%dT%H:%M:%SZ").to_string()), // 仮の時刻
-                    location: None,
-                    attendees: Vec::new(),
                     priority: Some(Priority::Medium),
-                    max_results: None,
+                    ..Default::default()
                 }),
                 response_text: "新しい予定を作成しました。".to_string(),
                 missing_data: None,
                 updated_conversation: None,
                 start_time: Some(start_time),
                 end_time: Some(end_time),
+                follow_up_actions: Vec::new(),
             })
         } else if input.contains("一覧") || input.contains("リスト") {
             Ok(LLMResponse {
@@ -504,6 +772,7 @@ impl LLM for MockLLMClient {
                 updated_conversation: None,
                 start_time: None,
                 end_time: None,
+                follow_up_actions: Vec::new(),
             })
         } else {
             Ok(LLMResponse {
@@ -514,6 +783,7 @@ impl LLM for MockLLMClient {
                 updated_conversation: None,
                 start_time: None,
                 end_time: None,
+                follow_up_actions: Vec::new(),
             })
         }
     }
@@ -526,6 +796,73 @@ impl LLM for MockLLMClient {
     }
 }
 
+/// テキスト中で最初に現れる、JSONとして解釈できる`{...}`を返す
+///
+/// 文字列リテラル内の括弧やエスケープは無視して対応を数える。
+/// 対応の取れた範囲がJSONとして不正な場合は、次の`{`から探し直す。
+fn extract_json_object(text: &str) -> Option<&str> {
+    let mut search_from = 0;
+    while let Some(offset) = text[search_from..].find('{') {
+        let start = search_from + offset;
+        if let Some(end) = find_matching_brace(&text[start..]) {
+            let candidate = &text[start..start + end];
+            if serde_json::from_str::<Value>(candidate).is_ok() {
+                return Some(candidate);
+            }
+        }
+        search_from = start + 1;
+    }
+    None
+}
+
+/// 入力から依頼の種類（create / update / delete / list）を大まかに判定する
+///
+/// LLMに送る前の温度の切り替えに使うだけなので、判定できない場合はNoneを返す。
+pub fn intent_category(user_input: &str) -> Option<&'static str> {
+    const CATEGORIES: [(&str, &[&str]); 4] = [
+        ("delete", &["削除", "消して", "消す", "取り消", "キャンセル", "delete", "remove", "cancel"]),
+        ("update", &["変更", "移動", "ずらし", "更新", "延長", "update", "move", "reschedule"]),
+        ("create", &["追加", "入れて", "作成", "登録", "予約", "add", "create"]),
+        ("list", &["一覧", "予定は", "教えて", "見せて", "表示", "list", "show"]),
+    ];
+    let input = user_input.to_lowercase();
+    CATEGORIES
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|keyword| input.contains(keyword)))
+        .map(|(category, _)| *category)
+}
+
+/// `{`で始まる文字列について、対応する`}`までのバイト長を返す
+fn find_matching_brace(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -539,6 +876,7 @@ mod tests {
             user_input,
             context: None,
             conversation_history: None,
+            temperature: None,
         };
 
         let response = mock_llm.process_request(request).await?;
@@ -552,4 +890,239 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_extract_json_from_messy_wrappers() {
+        let object = r#"{"action": "GENERAL_RESPONSE", "response_text": "了解 {です}\"", "event_data": null}"#;
+        let wrappers = [
+            format!("```json\n{}\n```", object),
+            format!("```JSON\n{}\n```", object),
+            format!("はい、以下の通りです。\n{}", object),
+            format!("{}\n\n以上が結果です。何か{{あれば}}どうぞ。", object),
+            format!("説明 {{不正な括弧}} の後に\n```Json\n{}\n```\n補足", object),
+        ];
+
+        for wrapped in &wrappers {
+            assert_eq!(extract_json_object(wrapped), Some(object), "{}", wrapped);
+        }
+        assert_eq!(extract_json_object("JSONはありません"), None);
+    }
+
+    #[tokio::test]
+    async fn test_set_model_switches_the_request_endpoint() -> Result<()> {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let text = r#"{"action": "GENERAL_RESPONSE", "response_text": "こんにちは"}"#;
+        Mock::given(method("POST"))
+            .and(path("/models/gemini-2.5-pro:generateContent"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "candidates": [{ "content": { "parts": [{ "text": text }] } }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = LLMClient {
+            api_key: "test".to_string(),
+            base_url: server.uri(),
+            model: RwLock::new(DEFAULT_MODEL.to_string()),
+            temperature: 0.7,
+            max_tokens: 1000,
+            clock: Arc::new(schedule_ai_agent::clock::SystemClock),
+            request_timeout: None,
+        };
+        client.set_model("gemini-2.5-pro")?;
+        assert_eq!(client.model().as_deref(), Some("gemini-2.5-pro"));
+
+        let request = LLMRequest {
+            user_input: "こんにちは".to_string(),
+            context: None,
+            conversation_history: None,
+            temperature: None,
+        };
+        client.process_request(request).await?;
+        server.verify().await;
+
+        assert!(client.set_model("  ").is_err());
+        assert!(is_known_model("gemini-2.5-flash"));
+        assert!(!is_known_model("gemini-9-ultra"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_response_with_leading_prose() -> Result<()> {
+        let client = LLMClient::for_parsing(Arc::new(schedule_ai_agent::clock::SystemClock));
+        let request = LLMRequest {
+            user_input: "こんにちは".to_string(),
+            context: None,
+            conversation_history: None,
+            temperature: None,
+        };
+        let content = "Sure! Here is the JSON:\n```JSON\n{\"action\": \"GENERAL_RESPONSE\", \"response_text\": \"こんにちは\"}\n```\nHope this helps.";
+
+        let response = client.parse_llm_response(content, &request)?;
+
+        assert_eq!(response.action, ActionType::GeneralResponse);
+        assert_eq!(response.response_text, "こんにちは");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_response_with_follow_up_actions() -> Result<()> {
+        let client = LLMClient::for_parsing(Arc::new(schedule_ai_agent::clock::SystemClock));
+        let request = LLMRequest {
+            user_input: "会議を作って今週の予定も見せて".to_string(),
+            context: None,
+            conversation_history: None,
+            temperature: None,
+        };
+        let content = r#"{"action": "CREATE_EVENT", "event_data": {"title": "会議"}, "response_text": "作成します",
+            "follow_up_actions": [{"action": "LIST_EVENTS", "event_data": {"start_time": "2025-07-01T00:00:00+09:00"}}]}"#;
+
+        let response = client.parse_llm_response(content, &request)?;
+
+        assert_eq!(response.action, ActionType::CreateEvent);
+        assert_eq!(response.follow_up_actions.len(), 1);
+        let follow_up = &response.follow_up_actions[0];
+        assert_eq!(follow_up.action, ActionType::ListEvents);
+        assert!(follow_up.start_time.is_some());
+        assert!(follow_up.response_text.is_empty());
+
+        // アクションのない後続は読み飛ばし、最初のアクションと他の後続は残す
+        let partial = client.parse_llm_response(
+            r#"{"action": "LIST_EVENTS", "follow_up_actions": [{"event_data": {}}, {"action": "FIND_FREE_TIME"}]}"#,
+            &request,
+        )?;
+        assert_eq!(partial.action, ActionType::ListEvents);
+        assert_eq!(partial.follow_up_actions.len(), 1);
+        assert_eq!(partial.follow_up_actions[0].action, ActionType::FindFreeTime);
+
+        // 従来の単一アクションの形式もそのまま読める
+        let single = client.parse_llm_response(r#"{"action": "LIST_EVENTS"}"#, &request)?;
+        assert!(single.follow_up_actions.is_empty());
+        assert_eq!(single.response_text, "No response text provided");
+        Ok(())
+    }
+
+    fn parse(content: &str) -> Result<LLMResponse> {
+        let request = LLMRequest {
+            user_input: "テスト".to_string(),
+            context: None,
+            conversation_history: None,
+            temperature: None,
+        };
+        LLMClient::for_parsing(Arc::new(schedule_ai_agent::clock::SystemClock)).parse_llm_response(content, &request)
+    }
+
+    #[test]
+    fn test_parse_every_action_type() -> Result<()> {
+        let cases = [
+            ("CREATE_EVENT", ActionType::CreateEvent),
+            ("UPDATE_EVENT", ActionType::UpdateEvent),
+            ("DELETE_EVENT", ActionType::DeleteEvent),
+            ("LIST_EVENTS", ActionType::ListEvents),
+            ("SEARCH_EVENTS", ActionType::SearchEvents),
+            ("GET_EVENT_DETAILS", ActionType::GetEventDetails),
+            ("SUMMARIZE_EVENTS", ActionType::SummarizeEvents),
+            ("FIND_NEXT_EVENT", ActionType::FindNextEvent),
+            ("FIND_FREE_TIME", ActionType::FindFreeTime),
+            ("OUT_OF_OFFICE", ActionType::OutOfOffice),
+            ("RESCHEDULE_EVENT", ActionType::RescheduleEvent),
+            ("GENERAL_RESPONSE", ActionType::GeneralResponse),
+            // 大文字・小文字は区別せず、未知のアクションは一般的な応答として扱う
+            ("list_events", ActionType::ListEvents),
+            ("MAKE_COFFEE", ActionType::GeneralResponse),
+        ];
+        for (name, expected) in cases {
+            let response = parse(&format!(r#"{{"action": "{}", "response_text": "ok"}}"#, name))?;
+            assert_eq!(response.action, expected, "{}", name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_missing_data_and_event_fields() -> Result<()> {
+        let cases = [
+            (r#""Title""#, Some(MissingEventData::Title)),
+            (r#""StartTime""#, Some(MissingEventData::StartTime)),
+            (r#""EndTime""#, Some(MissingEventData::EndTime)),
+            (r#""All""#, Some(MissingEventData::All)),
+            ("null", None),
+            (r#""Location""#, None),
+        ];
+        for (value, expected) in cases {
+            let response = parse(&format!(r#"{{"action": "CREATE_EVENT", "missing_data": {}}}"#, value))?;
+            assert_eq!(
+                format!("{:?}", response.missing_data),
+                format!("{:?}", expected),
+                "{}",
+                value
+            );
+        }
+
+        let response = parse(
+            r#"{"action": "CREATE_EVENT", "response_text": "作成します", "event_data": {
+                "title": "設計レビュー", "start_time": "2025-07-01 10:00", "end_time": "2025-07-01T11:00:00+09:00",
+                "priority": "High", "transparency": "busy", "visibility": "private", "duration_minutes": -30,
+                "attachments": ["https://example.com/doc", "file:///etc/passwd"], "reminders": [10, -5, "x"],
+                "calendar": "  ", "attendees": ["a@example.com", 1]}}"#,
+        )?;
+        let data = response.event_data.unwrap();
+        assert_eq!(data.title.as_deref(), Some("設計レビュー"));
+        assert!(matches!(data.priority, Some(Priority::High)));
+        assert_eq!(data.transparency, None);
+        assert_eq!(data.visibility.as_deref(), Some("private"));
+        assert_eq!(data.duration_minutes, None);
+        assert_eq!(data.attachments, vec!["https://example.com/doc".to_string()]);
+        assert_eq!(data.reminders, Some(vec![10]));
+        assert_eq!(data.calendar, None);
+        assert_eq!(data.attendees, vec!["a@example.com".to_string()]);
+        // タイムゾーンのない日時は日本時間として読む
+        let start = DateTime::parse_from_rfc3339("2025-07-01T01:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(response.start_time, Some(start));
+        assert_eq!(response.end_time, Some(start + chrono::Duration::hours(1)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_responses_are_rejected() {
+        assert!(parse("").is_err());
+        assert!(parse("了解しました").is_err());
+        assert!(parse(r#"{"action": "CREATE_EVENT", "response_text": }"#).is_err());
+        assert!(parse(r#"{"response_text": "アクションがありません"}"#).is_err());
+        assert!(parse(r#"{"action": 3}"#).is_err());
+
+        // event_data が null でも予定の情報なしとして読める
+        let response = parse(r#"{"action": "GENERAL_RESPONSE", "event_data": null, "response_text": "こんにちは"}"#).unwrap();
+        assert!(response.event_data.unwrap().title.is_none());
+        assert!(response.start_time.is_none());
+    }
+
+    #[test]
+    fn test_create_prompt_uses_zero_temperature() {
+        let config = Config::default();
+        let client = LLMClient {
+            api_key: String::new(),
+            base_url: String::new(),
+            model: RwLock::new(String::new()),
+            temperature: 0.7,
+            max_tokens: 1000,
+            clock: Arc::new(schedule_ai_agent::clock::SystemClock),
+            request_timeout: None,
+        };
+        let request = |user_input: &str| LLMRequest {
+            user_input: user_input.to_string(),
+            context: None,
+            conversation_history: None,
+            temperature: config.llm.temperature_for(intent_category(user_input)),
+        };
+
+        let payload = client.build_payload(&request("明日10時から会議を追加して"));
+        assert_eq!(payload["generationConfig"]["temperature"], 0.0);
+
+        let payload = client.build_payload(&request("おすすめの過ごし方を考えて"));
+        assert_eq!(payload["generationConfig"]["temperature"].as_f64(), Some(0.7f32 as f64));
+    }
 }
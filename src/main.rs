@@ -1,46 +1,85 @@
 mod calendar;
 mod cli;
 mod config;
+mod doctor;
 mod interactive;
 mod llm;
 mod models;
 mod scheduler;
 mod storage;
 mod tui;
+mod webhook;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+mod test_support;
 
 use anyhow::Result;
-use cli::{Cli, CliApp};
-use config::ConfigManager;
+use cli::{run_with_timeout, Cli, CliApp};
+use config::{ensure_app_directory, Config, ConfigManager};
 use llm::{LLMClient, MockLLMClient, LLM};
+use schedule_ai_agent::output::{log_target, set_log_target, status, LogTarget};
 use scheduler::Scheduler;
 use std::sync::Arc;
 use tui::ChatApp;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    println!("🏁 プログラム開始");
-    
     let cli = Cli::parse();
+
+    // 設定ファイルを読む前の出力もコマンドラインで指定した出力先へ送る
+    if let Some(target) = &cli.log_output {
+        set_log_target(LogTarget::parse(target));
+    }
+    status("🏁 プログラム開始");
+
+    // 設定・データの保存先を最初に用意し、作れない場合は対処を示して終了する
+    // （doctorは保存先の問題も含めて診断するため対象外）
+    if cli.matches.subcommand_name() != Some("doctor") {
+        ensure_app_directory()?;
+    }
     
     let use_mock_llm = cli.mock_llm;
     let verbose = cli.verbose;
 
     // TUIモードの場合
     if cli.matches.subcommand_name().is_none() || cli.matches.subcommand_name() == Some("tui") {
-        return tui_mode(use_mock_llm).await;
+        return tui_mode(use_mock_llm, cli.start_empty, cli.log_output.as_deref()).await;
+    }
+
+    // 1件のメッセージだけを処理するモード（スクリプトからの利用向け）
+    if let Some(chat_matches) = cli.matches.subcommand_matches("chat") {
+        return chat_mode(
+            use_mock_llm,
+            cli.start_empty,
+            cli.log_output.as_deref(),
+            chat_matches.is_present("json"),
+        )
+        .await;
+    }
+
+    // doctorは設定の読み込みに失敗しても診断を続けるためCliAppを経由しない
+    if cli.matches.subcommand_name() == Some("doctor") {
+        if !doctor::run(use_mock_llm).await? {
+            std::process::exit(1);
+        }
+        return Ok(());
     }
 
     // その他のコマンドは従来のCLIAppを使用
-    let mut app = CliApp::new(verbose).await?;
-    app.run(cli).await?;
+    let dry_run = ["import", "restore"]
+        .iter()
+        .any(|name| cli.matches.subcommand_matches(name).is_some_and(|m| m.is_present("dry-run")));
+    let mut app = CliApp::new(verbose, cli.log_output.as_deref(), dry_run).await?;
+    // 時間切れの場合はエラーとして終了コードを0以外にする
+    let limit = app.command_timeout(&cli);
+    run_with_timeout(limit, app.run(cli)).await?;
 
     Ok(())
 }
 
-async fn tui_mode(use_mock_llm: bool) -> Result<()> {
+async fn tui_mode(use_mock_llm: bool, start_empty: bool, log_output: Option<&str>) -> Result<()> {
     
     let config_manager = ConfigManager::new()?;
     let config = config_manager.load_config()?;
@@ -49,29 +88,66 @@ async fn tui_mode(use_mock_llm: bool) -> Result<()> {
     if let Some(debug_mode) = config.app.debug_mode {
         schedule_ai_agent::debug::set_debug_mode(debug_mode);
     }
+    if let Some(target) = log_output.or(config.app.log_output.as_deref()) {
+        set_log_target(LogTarget::parse(target));
+    }
+    // 端末への出力は画面を崩すため、TUIではログをファイルに書き出す
+    if !matches!(log_target(), LogTarget::File(_)) {
+        set_log_target(LogTarget::File(
+            config_manager.get_config_directory_path().join("tui.log"),
+        ));
+    }
 
+    let scheduler = build_scheduler(&config, use_mock_llm, start_empty).await?;
+
+    // TUIアプリケーションを起動
+    let mut app = ChatApp::new(scheduler);
+    app.run().await?;
+
+    Ok(())
+}
+
+async fn chat_mode(use_mock_llm: bool, start_empty: bool, log_output: Option<&str>, json: bool) -> Result<()> {
+    let config = ConfigManager::new()?.load_config()?;
+    if let Some(debug_mode) = config.app.debug_mode {
+        schedule_ai_agent::debug::set_debug_mode(debug_mode);
+    }
+    // 応答だけを標準出力に書き出すため、状態表示は既定どおり標準エラー出力などへ送る
+    if let Some(target) = log_output.or(config.app.log_output.as_deref()) {
+        set_log_target(LogTarget::parse(target));
+    }
+
+    let mut scheduler = build_scheduler(&config, use_mock_llm, start_empty).await?;
+    interactive::run_single_message(
+        &mut scheduler,
+        std::io::stdin().lock(),
+        &mut std::io::stdout().lock(),
+        json,
+    )
+    .await
+}
+
+/// LLMとの接続を確かめ、Google Calendarが使えれば連携したスケジューラーを作る
+///
+/// `start_empty`の場合は保存済みの会話履歴を読み込まない。
+async fn build_scheduler(config: &Config, use_mock_llm: bool, start_empty: bool) -> Result<Scheduler> {
     let llm: Arc<dyn LLM> = if use_mock_llm {
         Arc::new(MockLLMClient::new())
     } else {
-        Arc::new(LLMClient::from_config(&config)?)
+        Arc::new(LLMClient::from_config(config)?)
     };
 
     // LLMとの接続テスト
     llm.test_connection().await?;
 
     // Google Calendar設定の確認
-    let scheduler = match Scheduler::new_with_calendar(
+    match Scheduler::new_with_calendar(
         llm.clone(),
         "client_secret.json",
-        "token_cache.json"
+        "token_cache.json",
+        !start_empty,
     ).await {
-        Ok(scheduler) => scheduler,
-        Err(_) => Scheduler::new(llm)?,
-    };
-
-    // TUIアプリケーションを起動
-    let mut app = ChatApp::new(scheduler);
-    app.run().await?;
-
-    Ok(())
+        Ok(scheduler) => Ok(scheduler),
+        Err(_) => Scheduler::new_with_history(llm, !start_empty),
+    }
 }
@@ -16,9 +16,11 @@ pub struct Event {
     pub status: EventStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub google_event_id: Option<String>, // Google Calendarと同時に作成した場合の対応するイベントID
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Priority {
     Low,
     Medium,
@@ -26,7 +28,23 @@ pub enum Priority {
     Urgent,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 設定やコマンドで指定できる優先度の名前
+pub const PRIORITY_NAMES: [&str; 4] = ["low", "medium", "high", "urgent"];
+
+impl Priority {
+    /// "low" / "medium" / "high" / "urgent"（大文字・小文字は区別しない）から優先度を求める
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "low" => Some(Priority::Low),
+            "medium" => Some(Priority::Medium),
+            "high" => Some(Priority::High),
+            "urgent" => Some(Priority::Urgent),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventStatus {
     Scheduled,
     InProgress,
@@ -34,16 +52,44 @@ pub enum EventStatus {
     Cancelled,
 }
 
+impl EventStatus {
+    /// `next`へ移れるか（完了・キャンセル済みからは移れず、実行中から予定へは戻れない）
+    pub fn can_transition_to(self, next: EventStatus) -> bool {
+        use EventStatus::*;
+        matches!(
+            (self, next),
+            (Scheduled, InProgress | Completed | Cancelled) | (InProgress, Completed | Cancelled)
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schedule {
     pub events: Vec<Event>,
 }
 
+/// 2つのスケジュールの差分（取り込み側から見た追加・削除・変更）
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleDiff {
+    pub added: Vec<Event>,
+    pub removed: Vec<Event>,
+    /// 内容が変わる予定（取り込み後の内容）
+    pub changed: Vec<Event>,
+}
+
+impl ScheduleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMRequest {
     pub user_input: String,
     pub context: Option<String>,
     pub conversation_history: Option<ConversationHistory>,
+    /// 指定した場合は設定の温度より優先して使う
+    pub temperature: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +101,9 @@ pub struct LLMResponse {
     pub updated_conversation: Option<ConversationHistory>, // 更新された会話履歴
     pub start_time: Option<DateTime<Utc>>, // 開始
     pub end_time: Option<DateTime<Utc>>,     // 終了
+    /// 同じ依頼の中で続けて実行するアクション（「予定を作って一覧も見せて」など）
+    #[serde(default)]
+    pub follow_up_actions: Vec<LLMResponse>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -65,10 +114,35 @@ pub enum ActionType {
     ListEvents,
     SearchEvents,
     GetEventDetails,
+    SummarizeEvents,
+    FindNextEvent,
+    FindFreeTime,
+    OutOfOffice,
+    RescheduleEvent,
     GeneralResponse,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ActionType {
+    /// 解釈の表示に使う日本語の名前
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActionType::CreateEvent => "予定作成",
+            ActionType::UpdateEvent => "予定変更",
+            ActionType::DeleteEvent => "予定削除",
+            ActionType::ListEvents => "予定一覧",
+            ActionType::SearchEvents => "予定検索",
+            ActionType::GetEventDetails => "予定の詳細",
+            ActionType::SummarizeEvents => "予定の要約",
+            ActionType::FindNextEvent => "次の予定",
+            ActionType::FindFreeTime => "空き時間検索",
+            ActionType::OutOfOffice => "不在設定",
+            ActionType::RescheduleEvent => "予定の移動",
+            ActionType::GeneralResponse => "一般的な応答",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EventData {
     pub id: Option<String>, // Google CalendarのイベントID（更新や削除時に使用）
     pub title: Option<String>,
@@ -79,6 +153,19 @@ pub struct EventData {
     pub attendees: Vec<String>,
     pub priority: Option<Priority>,
     pub max_results: Option<i32>,
+    pub transparency: Option<String>, // "opaque"（予定あり）/ "transparent"（空き時間）
+    pub visibility: Option<String>,   // "default" / "private" / "public"
+    pub duration_minutes: Option<i64>, // 空き時間検索で必要な長さ（分）
+    pub all_upcoming: Option<bool>,    // 期限なしで今後の予定をすべて対象にする
+    #[serde(default)]
+    pub attachments: Vec<String>,      // 添付する資料のURL
+    #[serde(default)]
+    pub add_attendees: Vec<String>,    // 既存の参加者リストに追加する参加者
+    #[serde(default)]
+    pub remove_attendees: Vec<String>, // 既存の参加者リストから外す参加者
+    pub append_description: Option<String>, // 既存の説明の末尾に追記する文章（descriptionは置き換え）
+    pub calendar: Option<String>,           // 予定を参照するカレンダーの名前（未指定ならプライマリ）
+    pub reminders: Option<Vec<i64>>,   // 通知のタイミング（開始の何分前か）
 }
 
 #[derive(Error, Debug)]
@@ -89,6 +176,27 @@ pub enum SchedulerError {
     ParseError(String),
     #[error("IO Error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("API Error: {0}")]
+    ApiError(String),
+    #[error("Network Error: {0}")]
+    NetworkError(String),
+    #[error("キャンセルしました")]
+    Cancelled,
+}
+
+impl SchedulerError {
+    /// エラーの種類に応じてユーザーに示すヒント
+    pub fn hint(&self) -> &'static str {
+        match self {
+            SchedulerError::NetworkError(_) => "ネットワーク接続を確認してください",
+            SchedulerError::ApiError(_) => "APIキーや利用上限を確認してください",
+            SchedulerError::ValidationError(_) | SchedulerError::ParseError(_) => {
+                "入力内容を見直してもう一度試してください"
+            }
+            SchedulerError::IoError(_) => "ファイルの権限や空き容量を確認してください",
+            SchedulerError::Cancelled => "入力内容はそのまま残しています",
+        }
+    }
 }
 
 impl From<chrono::ParseError> for SchedulerError {
@@ -97,6 +205,17 @@ impl From<chrono::ParseError> for SchedulerError {
     }
 }
 
+impl From<reqwest::Error> for SchedulerError {
+    fn from(err: reqwest::Error) -> Self {
+        // 接続・タイムアウトなど通信そのものの失敗とAPI側の応答エラーを区別する
+        if err.is_connect() || err.is_timeout() || err.is_request() {
+            SchedulerError::NetworkError(err.to_string())
+        } else {
+            SchedulerError::ApiError(err.to_string())
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MissingEventData {
     Title,
@@ -124,8 +243,37 @@ impl Event {
             status: EventStatus::Scheduled,
             created_at: now,
             updated_at: now,
+            google_event_id: None,
         }
     }
+    /// 作成・更新日時を除いた内容が同じか
+    fn same_content(&self, other: &Event) -> bool {
+        self.title == other.title
+            && self.description == other.description
+            && self.start_time == other.start_time
+            && self.end_time == other.end_time
+            && self.location == other.location
+            && self.attendees == other.attendees
+            && self.priority == other.priority
+            && self.status == other.status
+    }
+
+    /// 状態を変更する（不正な遷移はエラー、同じ状態なら何もしない）
+    pub fn set_status(&mut self, status: EventStatus) -> Result<(), SchedulerError> {
+        if self.status == status {
+            return Ok(());
+        }
+        if !self.status.can_transition_to(status) {
+            return Err(SchedulerError::ValidationError(format!(
+                "予定の状態を{:?}から{:?}に変更することはできません",
+                self.status, status
+            )));
+        }
+        self.status = status;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+
     // EventDataを適用する新しいメソッド
     pub fn apply_event_data(&mut self, event_data: EventData, parse_datetime: impl Fn(&str) -> Result<DateTime<Utc>, SchedulerError>) -> Result<(), SchedulerError> {
         if let Some(title) = event_data.title {
@@ -178,14 +326,60 @@ impl Schedule {
     }
 
 
+    /// `incoming`で置き換えた場合の差分を求める
+    ///
+    /// 予定はIDで対応付け、IDが一致しない場合はタイトルと開始時刻が同じものを同じ予定とみなす。
+    pub fn diff(&self, incoming: &Schedule) -> ScheduleDiff {
+        fn find<'a>(events: &'a [Event], target: &Event) -> Option<&'a Event> {
+            events.iter().find(|event| event.id == target.id).or_else(|| {
+                events
+                    .iter()
+                    .find(|event| event.title == target.title && event.start_time == target.start_time)
+            })
+        }
+
+        let mut diff = ScheduleDiff::default();
+        for event in &incoming.events {
+            match find(&self.events, event) {
+                None => diff.added.push(event.clone()),
+                Some(current) if !current.same_content(event) => diff.changed.push(event.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = self
+            .events
+            .iter()
+            .filter(|event| find(&incoming.events, event).is_none())
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// `start`から`end`までの間に一部でもかかる予定を開始時刻順に返す
+    pub fn events_in_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Event> {
+        let mut events: Vec<&Event> = self
+            .events
+            .iter()
+            .filter(|event| event.start_time < end && event.end_time > start)
+            .collect();
+        events.sort_by_key(|event| event.start_time);
+        events
+    }
+
+    /// `now`より後に始まる予定を開始時刻順に最大`limit`件返す
+    pub fn upcoming(&self, now: DateTime<Utc>, limit: usize) -> Vec<&Event> {
+        let mut events: Vec<&Event> = self.events.iter().filter(|event| event.start_time > now).collect();
+        events.sort_by_key(|event| event.start_time);
+        events.truncate(limit);
+        events
+    }
+
     // 重複チェック
     pub fn has_conflict(&self, start: &DateTime<Utc>, end: &DateTime<Utc>) -> bool {
         self.events.iter().any(|event| {
             start < &event.end_time && end > &event.start_time
         })
     }
-
-    // 特定のイベントを除外して重複チェック
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -195,6 +389,66 @@ pub struct ConversationHistory {
     pub updated_at: DateTime<Utc>,
 }
 
+/// 会話履歴の要約（`conversation summary --format json`で出力する）
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationSummary<'a> {
+    pub total_messages: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub recent_messages: &'a [ConversationMessage],
+}
+
+/// 利用状況ログの1行（入力や応答の内容は記録しない）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Utc>,
+    /// LLMから応答を得られなかった場合はNone
+    pub action: Option<ActionType>,
+    pub success: bool,
+    pub latency_ms: u64,
+}
+
+/// アクションごとの利用回数と平均応答時間
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageCount {
+    pub total: usize,
+    pub failures: usize,
+    pub total_latency_ms: u64,
+}
+
+impl UsageCount {
+    pub fn average_latency_ms(&self) -> u64 {
+        if self.total == 0 {
+            0
+        } else {
+            self.total_latency_ms / self.total as u64
+        }
+    }
+}
+
+/// 利用状況ログをアクションごとに集計する（回数の多い順）
+pub fn summarize_usage(records: &[UsageRecord]) -> Vec<(&'static str, UsageCount)> {
+    let mut counts: Vec<(&'static str, UsageCount)> = Vec::new();
+    for record in records {
+        let label = record.action.as_ref().map_or("不明（LLMエラー）", ActionType::label);
+        let index = match counts.iter().position(|(name, _)| *name == label) {
+            Some(index) => index,
+            None => {
+                counts.push((label, UsageCount::default()));
+                counts.len() - 1
+            }
+        };
+        let count = &mut counts[index].1;
+        count.total += 1;
+        if !record.success {
+            count.failures += 1;
+        }
+        count.total_latency_ms += record.latency_ms;
+    }
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(count.total));
+    counts
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationMessage {
     pub id: Uuid,
@@ -250,6 +504,16 @@ impl ConversationHistory {
         &self.messages[start..]
     }
 
+    /// 件数・期間と直近`recent`件のメッセージをまとめた要約
+    pub fn summary(&self, recent: usize) -> ConversationSummary<'_> {
+        ConversationSummary {
+            total_messages: self.messages.len(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            recent_messages: self.get_recent_messages(recent),
+        }
+    }
+
     pub fn get_context_string(&self, max_messages: Option<usize>) -> String {
         let messages = if let Some(max) = max_messages {
             self.get_recent_messages(max)
@@ -275,4 +539,103 @@ impl ConversationHistory {
         self.messages.clear();
         self.updated_at = Utc::now();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions() {
+        let now = Utc::now();
+        let mut event = Event::new("定例".to_string(), now, now + chrono::Duration::hours(1));
+        let created = event.updated_at;
+
+        event.set_status(EventStatus::InProgress).unwrap();
+        assert!(event.updated_at >= created);
+        assert!(event.set_status(EventStatus::Scheduled).is_err());
+        event.set_status(EventStatus::Completed).unwrap();
+        assert_eq!(event.status, EventStatus::Completed);
+
+        for next in [EventStatus::Scheduled, EventStatus::InProgress, EventStatus::Cancelled] {
+            assert!(event.set_status(next).is_err());
+        }
+        assert!(event.set_status(EventStatus::Completed).is_ok());
+
+        let mut cancelled = Event::new("休暇".to_string(), now, now + chrono::Duration::hours(1));
+        cancelled.set_status(EventStatus::Cancelled).unwrap();
+        assert!(cancelled.set_status(EventStatus::Completed).is_err());
+    }
+
+    #[test]
+    fn test_events_in_range_and_upcoming() {
+        let base = Utc::now();
+        let hours = chrono::Duration::hours;
+        let mut schedule = Schedule::new();
+        // 追加順と開始時刻の順をあえてずらす
+        schedule.add_event(Event::new("来週".to_string(), base + hours(24 * 7), base + hours(24 * 7 + 1)));
+        schedule.add_event(Event::new("明日".to_string(), base + hours(24), base + hours(25)));
+        schedule.add_event(Event::new("昨日".to_string(), base - hours(24), base - hours(23)));
+        schedule.add_event(Event::new("進行中".to_string(), base - hours(1), base + hours(1)));
+
+        let titles = |events: Vec<&Event>| events.iter().map(|event| event.title.clone()).collect::<Vec<_>>();
+
+        assert_eq!(titles(schedule.events_in_range(base, base + hours(48))), ["進行中", "明日"]);
+        assert_eq!(titles(schedule.events_in_range(base - hours(48), base + hours(24 * 8))), ["昨日", "進行中", "明日", "来週"]);
+        // 終了時刻ちょうどから始まる範囲には含めない
+        assert!(schedule.events_in_range(base - hours(23), base - hours(22)).is_empty());
+
+        assert_eq!(titles(schedule.upcoming(base, 10)), ["明日", "来週"]);
+        assert_eq!(titles(schedule.upcoming(base, 1)), ["明日"]);
+    }
+
+    #[test]
+    fn test_conversation_history_and_summary_serialize_to_json() {
+        let mut history = ConversationHistory::new();
+        history.add_user_message("明日の予定は？".to_string(), None);
+        history.add_assistant_message("明日は会議が1件あります。".to_string(), None);
+
+        let json: serde_json::Value = serde_json::to_value(&history).unwrap();
+        let messages = json["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["role"], "User");
+        assert_eq!(messages[1]["role"], "Assistant");
+        assert_eq!(messages[0]["content"], "明日の予定は？");
+        assert!(DateTime::parse_from_rfc3339(messages[0]["timestamp"].as_str().unwrap()).is_ok());
+
+        let summary = serde_json::to_value(history.summary(1)).unwrap();
+        assert_eq!(summary["total_messages"], 2);
+        assert_eq!(summary["recent_messages"].as_array().unwrap().len(), 1);
+        assert_eq!(summary["recent_messages"][0]["role"], "Assistant");
+        assert!(DateTime::parse_from_rfc3339(summary["updated_at"].as_str().unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_api_response_maps_to_api_error() {
+        // 通信はせず、手元で組み立てた応答の解析失敗を使う
+        let err = reqwest::Response::from(hyper::Response::new("not json"))
+            .json::<serde_json::Value>()
+            .await
+            .expect_err("JSONの解析は失敗するはず");
+
+        let mapped = SchedulerError::from(err);
+        assert!(matches!(mapped, SchedulerError::ApiError(_)));
+        assert_eq!(mapped.hint(), "APIキーや利用上限を確認してください");
+    }
+
+    #[tokio::test]
+    async fn test_connection_failure_maps_to_network_error() {
+        // 空いているポートを確保してすぐ閉じ、そこへの接続失敗を使う（外部への通信はしない）
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let err = reqwest::Client::new()
+            .get(format!("http://127.0.0.1:{}/", port))
+            .send()
+            .await
+            .expect_err("接続は失敗するはず");
+        assert!(err.is_connect());
+
+        let mapped = SchedulerError::from(err);
+        assert!(matches!(mapped, SchedulerError::NetworkError(_)));
+        assert_eq!(mapped.hint(), "ネットワーク接続を確認してください");
+    }
+}
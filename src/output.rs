@@ -0,0 +1,113 @@
+/// 出力先を制御するためのモジュール
+///
+/// コマンドの結果は標準出力へ、状態表示やログは設定された出力先へ分けて書き出す。
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// 状態表示・ログの出力先
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogTarget {
+    Stdout,
+    Stderr,
+    File(PathBuf),
+}
+
+impl LogTarget {
+    /// "stdout"・"stderr"、またはファイルパスを出力先として解釈する
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "stdout" => LogTarget::Stdout,
+            "stderr" | "" => LogTarget::Stderr,
+            path => LogTarget::File(PathBuf::from(path)),
+        }
+    }
+}
+
+/// グローバルな出力先（既定は標準エラー出力）
+static LOG_TARGET: Mutex<LogTarget> = Mutex::new(LogTarget::Stderr);
+
+/// 出力先を切り替えるテスト同士が並行して動かないようにするロック
+#[cfg(test)]
+pub(crate) static TEST_LOG_TARGET_LOCK: Mutex<()> = Mutex::new(());
+
+/// 状態表示・ログの出力先を設定
+pub fn set_log_target(target: LogTarget) {
+    *LOG_TARGET.lock().unwrap_or_else(|e| e.into_inner()) = target;
+}
+
+/// 現在の出力先を取得
+pub fn log_target() -> LogTarget {
+    LOG_TARGET.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// 状態表示を出力先へ書き出す（ファイルに書けない場合は標準エラー出力へ）
+pub fn status(msg: &str) {
+    match log_target() {
+        LogTarget::Stdout => println!("{}", msg),
+        LogTarget::Stderr => eprintln!("{}", msg),
+        LogTarget::File(path) => {
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| writeln!(file, "{}", msg));
+            if written.is_err() {
+                eprintln!("{}", msg);
+            }
+        }
+    }
+}
+
+/// JSONの結果だけを書き出す（装飾や状態表示は含めない）
+pub fn write_json<W: Write>(out: &mut W, value: &serde_json::Value) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, value)?;
+    writeln!(out)
+}
+
+/// JSONの結果を標準出力へ書き出す
+pub fn print_json(value: &serde_json::Value) -> io::Result<()> {
+    write_json(&mut io::stdout().lock(), value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_target() {
+        assert_eq!(LogTarget::parse("stdout"), LogTarget::Stdout);
+        assert_eq!(LogTarget::parse("stderr"), LogTarget::Stderr);
+        assert_eq!(
+            LogTarget::parse("/tmp/schedule.log"),
+            LogTarget::File(PathBuf::from("/tmp/schedule.log"))
+        );
+    }
+
+    #[test]
+    fn test_status_lines_are_appended_to_log_file() {
+        let _guard = TEST_LOG_TARGET_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let log_path = std::env::temp_dir()
+            .join(format!("schedule_ai_agent_test_{}.log", uuid::Uuid::new_v4()));
+        set_log_target(LogTarget::File(log_path.clone()));
+        assert_eq!(log_target(), LogTarget::File(log_path.clone()));
+
+        status("🏁 プログラム開始");
+        status("スケジュールを保存");
+        set_log_target(LogTarget::Stderr);
+
+        let logged = std::fs::read_to_string(&log_path).unwrap();
+        assert_eq!(logged, "🏁 プログラム開始\nスケジュールを保存\n");
+        let _ = std::fs::remove_file(log_path);
+    }
+
+    #[test]
+    fn test_json_output_is_only_the_value() {
+        let mut stdout = Vec::new();
+        write_json(&mut stdout, &serde_json::json!({ "title": "会議" })).unwrap();
+
+        let printed = String::from_utf8(stdout).unwrap();
+        assert_eq!(printed, "{\n  \"title\": \"会議\"\n}\n");
+    }
+}
@@ -1,14 +1,27 @@
-use crate::llm::LLM;
+use crate::llm::{intent_category, is_known_model, ResponseCache, KNOWN_MODELS, LLM};
 use crate::models::{
-    ActionType, ConversationHistory, EventData, LLMRequest, LLMResponse, SchedulerError
+    ActionType, ConversationHistory, EventData, LLMRequest, LLMResponse, Priority, SchedulerError, UsageRecord
+};
+use crate::calendar::{
+    append_description, apply_attendee_changes, check_attendee_availability, conflicting_events, conflicting_events_excluding, create_out_of_office, day_window,
+    delete_event_with_notice, deletion_needs_confirmation,
+    event_priority, event_start, find_free_slots_with_buffer, map_url, format_event_time_range, format_reminders, find_next_event,
+    nearest_free_slots, priority_color_id, reminder_overrides, reschedule_to_next_free_slot, resolve_calendar_id, search_events, unavailable_attendee_lines, upcoming_window, CalendarBackend,
 };
 use crate::storage::Storage;
-use crate::config::Config;
-use schedule_ai_agent::GoogleCalendarClient;
+use crate::webhook::notify_event_created;
+use crate::config::{AppConfig, Config, ConfigManager};
+use schedule_ai_agent::clock::Clock;
+use schedule_ai_agent::datetime::{normalize_japanese_time, parse_datetime_with_zone};
+use schedule_ai_agent::output::status;
+use schedule_ai_agent::{render_description_template, EventBuilder, GoogleCalendarClient};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use chrono_tz::Asia::Tokyo;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub struct Scheduler {
     conversation_history: ConversationHistory,
@@ -16,14 +29,42 @@ pub struct Scheduler {
     storage: Storage,
     calendar_client: Option<GoogleCalendarClient>,
     config: Config,
+    clock: Arc<dyn Clock>,
+    /// 開始時刻が過去のため確認待ちになっている予定
+    pending_past_event: Option<EventData>,
+    /// 参加者に予定が入っているため確認待ちになっている予定
+    pending_busy_attendees: Option<EventData>,
+    /// 優先度が高いなどの理由で、削除の確認待ちになっている予定
+    pending_deletion: Option<google_calendar3::api::Event>,
+    /// 確認を待つ間、実行を保留している後続のアクション
+    pending_follow_ups: Vec<LLMResponse>,
+    /// 同じ入力に対するLLMの応答
+    response_cache: ResponseCache,
+    /// カレンダー名から解決したカレンダーID
+    calendar_ids: HashMap<String, String>,
+    /// 会話履歴を保存先に書き出すか（--start-emptyで開始した場合は履歴を消去するまで書き出さない）
+    persist_history: bool,
+    /// 処理中の依頼を取りやめるよう求められたか（LLMの応答を待つ間と、受け取った直後にだけ確かめる）
+    cancel_requested: Arc<AtomicBool>,
 }
 
 impl Scheduler {
     pub fn new(llm: Arc<dyn LLM>) -> Result<Self> {
-        let storage = Storage::new()?;
-        let conversation_history = storage.load_conversation_history()?;
-        let config = Config::default();
-        
+        Self::new_with_history(llm, true)
+    }
+
+    /// `load_history`がfalseなら保存済みの会話履歴を読み込まず、空の履歴で開始する（--start-empty）
+    pub fn new_with_history(llm: Arc<dyn LLM>, load_history: bool) -> Result<Self> {
+        let config = ConfigManager::new()
+            .and_then(|manager| manager.load_config())
+            .unwrap_or_default();
+        Self::from_parts(llm, Storage::new()?, config, load_history)
+    }
+
+    /// 保存先と設定を指定してカレンダー未連携のスケジューラーを作る
+    pub fn from_parts(llm: Arc<dyn LLM>, storage: Storage, config: Config, load_history: bool) -> Result<Self> {
+        let conversation_history = initial_history(&storage, load_history)?;
+
         // デバッグモードを設定
         if let Some(debug_mode) = config.app.debug_mode {
             schedule_ai_agent::debug::set_debug_mode(debug_mode);
@@ -34,58 +75,191 @@ impl Scheduler {
             llm,
             storage,
             calendar_client: None,
+            clock: config.app.clock(),
+            response_cache: ResponseCache::new(config.llm.response_cache_size.unwrap_or(32)),
+            calendar_ids: HashMap::new(),
             config,
+            pending_past_event: None,
+            pending_busy_attendees: None,
+            pending_deletion: None,
+            pending_follow_ups: Vec::new(),
+            persist_history: load_history,
+            cancel_requested: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    pub async fn new_with_calendar(
+        llm: Arc<dyn LLM>,
+        client_secret_path: &str,
+        token_cache_path: &str,
+        load_history: bool,
+    ) -> Result<Self> {
+        let config = ConfigManager::new()
+            .and_then(|manager| manager.load_config())
+            .unwrap_or_default();
+        let auth_flow = config.google_calendar.as_ref().map(|gc| gc.auth_flow()).unwrap_or_default();
+        let open_browser = config.google_calendar.as_ref().is_none_or(|gc| gc.open_browser());
+        let calendar_client = GoogleCalendarClient::new(client_secret_path, token_cache_path, auth_flow, open_browser)
+            .await?
+            .with_default_reminders(config.calendar.default_reminders.as_deref().map(reminder_overrides))
+            .with_request_timeout(config.app.request_timeout());
 
-    pub async fn new_with_calendar(llm: Arc<dyn LLM>, client_secret_path: &str, token_cache_path: &str) -> Result<Self> {
-        let storage = Storage::new()?;
-        let conversation_history = storage.load_conversation_history()?;
-        let config = Config::default();
-        
-        // デバッグモードを設定
-        if let Some(debug_mode) = config.app.debug_mode {
-            schedule_ai_agent::debug::set_debug_mode(debug_mode);
-        }
-        
-        let calendar_client = GoogleCalendarClient::new(client_secret_path, token_cache_path).await?;
+        let mut scheduler = Self::from_parts(llm, Storage::new()?, config, load_history)?;
+        scheduler.calendar_client = Some(calendar_client);
+        Ok(scheduler)
+    }
 
-        Ok(Self {
-            conversation_history,
-            llm,
-            storage,
-            calendar_client: Some(calendar_client),
-            config,
-        })
+    /// 処理中の依頼を取りやめるためのフラグ（trueにすると、LLMの応答を待つ間なら中断する）
+    ///
+    /// 会話履歴の保存やカレンダーの変更を始めた後は、最後まで処理する。
+    pub fn cancel_handle(&self) -> Arc<AtomicBool> {
+        self.cancel_requested.clone()
+    }
+
+    /// 現在時刻の取得元を差し替える
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
     }
 
     pub async fn process_user_input(&mut self, user_input: String) -> Result<String> {
         if schedule_ai_agent::debug::is_debug_enabled() {
-            eprintln!("🔍 DEBUG: ======== USER INPUT PROCESSING ========");
-            eprintln!("🔍 DEBUG: process_user_input が呼ばれました: '{}'", user_input);
+            status("🔍 DEBUG: ======== USER INPUT PROCESSING ========");
+            status(&format!("🔍 DEBUG: process_user_input が呼ばれました: '{}'", user_input));
         }
 
-        // llmへのリクエストを作成
-        let request = LLMRequest {
-            user_input: user_input.clone(),
-            context: Some(self.create_context()),
-            conversation_history: Some(self.conversation_history.clone()),
-        };
+        // 空白のみの入力はLLMに送らない
+        if user_input.trim().is_empty() {
+            return Ok("入力が空です".to_string());
+        }
+        let started = std::time::Instant::now();
+        // 確認の返事を待つ間に保留していた後続のアクション（確認以外の入力なら取りやめる）
+        let pending_follow_ups = std::mem::take(&mut self.pending_follow_ups);
 
-        if schedule_ai_agent::debug::is_debug_enabled() {
-            eprintln!("🔍 DEBUG: LLMリクエストを作成しました");
+        // 過去の日時の予定を作成するかの確認に答えた場合（作成する前に参加者の空き状況も確かめる）
+        // 確認以外の入力なら作成を取りやめ、通常どおり処理する
+        if let Some(event_data) = self.pending_past_event.take() {
+            if let Some(confirmed) = confirmation_answer(&user_input) {
+                return self
+                    .answer_pending_creation(event_data, confirmed, true, pending_follow_ups, started)
+                    .await;
+            }
         }
 
-        // llmにリクエストを送信
-        // llmからの応答を待機
-        let response = self.llm.process_request(request).await?;
+        // 参加者に予定が入っている予定を作成するかの確認に答えた場合
+        if let Some(event_data) = self.pending_busy_attendees.take() {
+            if let Some(confirmed) = confirmation_answer(&user_input) {
+                return self
+                    .answer_pending_creation(event_data, confirmed, false, pending_follow_ups, started)
+                    .await;
+            }
+        }
+
+        // 予定を削除するかの確認に答えた場合
+        if let Some(event) = self.pending_deletion.take() {
+            match confirmation_answer(&user_input) {
+                Some(true) => {
+                    let result = self.delete_confirmed_event(&event).await;
+                    self.record_usage(Some(ActionType::DeleteEvent), result.is_ok(), started);
+                    return match result {
+                        Ok(()) => {
+                            let message = format!("予定「{}」を削除しました。", event.summary.unwrap_or_default());
+                            self.run_follow_up_actions(message, &pending_follow_ups, started).await
+                        }
+                        Err(e) => Ok(note_skipped_follow_ups(
+                            format!("❌ エラーが発生しました: {}", e),
+                            pending_follow_ups.len(),
+                        )),
+                    };
+                }
+                Some(false) => {
+                    self.record_usage(Some(ActionType::DeleteEvent), true, started);
+                    return Ok(note_skipped_follow_ups(
+                        "予定の削除を取りやめました。".to_string(),
+                        pending_follow_ups.len(),
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        // 同じ入力（同じ日・同じ連携状態）への応答がキャッシュにあればLLMに問い合わせない
+        let context = self.create_context();
+        let cache_key = ResponseCache::key(
+            &user_input,
+            &format!("{}{}", context, self.clock.now().with_timezone(&Tokyo).date_naive()),
+        );
+        let response = if let Some(cached) = self.response_cache.get(cache_key) {
+            if schedule_ai_agent::debug::is_debug_enabled() {
+                status("🔍 DEBUG: キャッシュした応答を使用します");
+            }
+            self.conversation_history.add_user_message(user_input.clone(), None);
+            self.conversation_history
+                .add_assistant_message(cached.response_text.clone(), None);
+            self.save_conversation_history()?;
+            cached
+        } else {
+            // llmへのリクエストを作成
+            let request = LLMRequest {
+                user_input: user_input.clone(),
+                context: Some(context),
+                conversation_history: Some(self.conversation_history.clone()),
+                temperature: self.config.llm.temperature_for(intent_category(&user_input)),
+            };
+
+            if schedule_ai_agent::debug::is_debug_enabled() {
+                status("🔍 DEBUG: LLMリクエストを作成しました");
+            }
+
+            // llmにリクエストを送信
+            // llmからの応答を待機（待つ間に取りやめを求められたら、何も変更せずに中断する）
+            let response = tokio::select! {
+                biased;
+                _ = wait_for_cancel(&self.cancel_requested) => None,
+                result = self.llm.process_request(request) => Some(result),
+            };
+            let response = match response {
+                Some(Ok(response)) => response,
+                Some(Err(e)) => {
+                    self.record_usage(None, false, started);
+                    return Err(e);
+                }
+                None => return Err(SchedulerError::Cancelled.into()),
+            };
+            // 履歴の保存やカレンダーの変更を始める前の、取りやめられる最後の時点
+            if self.cancel_requested.load(Ordering::SeqCst) {
+                return Err(SchedulerError::Cancelled.into());
+            }
+            // 不足情報を尋ねる応答は会話の流れに依存するためキャッシュしない
+            if response.missing_data.is_none() {
+                self.response_cache.insert(
+                    cache_key,
+                    LLMResponse {
+                        updated_conversation: None,
+                        ..response.clone()
+                    },
+                );
+            }
+            response
+        };
 
         if schedule_ai_agent::debug::is_debug_enabled() {
-            eprintln!("🔍 DEBUG: LLMからレスポンスを受信: action={:?}, response_text='{}'", 
-                     response.action, response.response_text);
+            status(&format!("🔍 DEBUG: LLMからレスポンスを受信: action={:?}, response_text='{}'", 
+                     response.action, response.response_text));
         }
 
+        // 実行前に解釈を示し、結果の先頭に添える
+        let explanation = self
+            .config
+            .app
+            .explain
+            .unwrap_or(false)
+            .then(|| self.explain_response(&response));
+        let explained = |msg: String| match &explanation {
+            Some(explanation) => format!("{}\n\n{}", explanation, msg),
+            None => msg,
+        };
+
         // 会話履歴を更新
         if let Some(updated_conversation) = response.updated_conversation.clone() {
             self.conversation_history = updated_conversation;
@@ -93,76 +267,52 @@ impl Scheduler {
         }
 
         // アクションに基づいて処理を実行
-        let result = match response.action {
-            ActionType::CreateEvent => {
-                if let Some(event_data) = response.event_data {
-                    self.create_event_from_data(event_data).await
-                } else {
-                    Ok("イベントデータが不足しています。".to_string())
-                }
-            }
-            ActionType::UpdateEvent => {
-                Ok("予定の更新は現在サポートされていません。予定を削除してから新しく作成してください。".to_string())
-            }
-            ActionType::DeleteEvent => {
-                if let Some(event_data) = response.event_data {
-                self.delete_event(event_data).await
-                    .map(|_| "予定を削除しました。".to_string())
-                    .map_err(|e| anyhow::anyhow!(e))
-                } else {
-                    Ok("イベントデータが不足しています。".to_string())
-                }
-            }
-            ActionType::ListEvents => {
-                self.get_list_events(&response).await
-            }
-            ActionType::SearchEvents => {
-                Ok("ローカルスケジュールは削除されました。Google Calendarから予定を検索してください。".to_string())
-            }
-            ActionType::GetEventDetails => {
-                Ok("ローカルスケジュールは削除されました。Google Calendarから予定の詳細を確認してください。".to_string())
-            }
-            ActionType::GeneralResponse => {
-                Ok(response.response_text.clone())
+        let result = match self.execute_action(&response).await {
+            // 過去の日時の予定や重要な予定の削除は、確認の返事を待つ
+            Ok(confirmation) if self.awaiting_confirmation() => {
+                self.record_usage(Some(response.action.clone()), true, started);
+                let confirmation = self.hold_follow_up_actions(confirmation, &response.follow_up_actions);
+                return Ok(explained(confirmation));
             }
+            result => result,
         };
 
+        self.record_usage(Some(response.action.clone()), result.is_ok(), started);
+
         // 成功時はresponse_textがあればそれを、なければ処理結果を返す
         match result {
             Ok(msg) => {
                 if schedule_ai_agent::debug::is_debug_enabled() {
-                    eprintln!("🔍 DEBUG: 処理結果を取得: '{}'", msg);
+                    status(&format!("🔍 DEBUG: 処理結果を取得: '{}'", msg));
                 }
-                // ListEventsアクションの場合は、結果を優先して返す
-                let final_result = match response.action {
-                    ActionType::ListEvents => {
-                        if schedule_ai_agent::debug::is_debug_enabled() {
-                            eprintln!("🔍 DEBUG: ListEventsアクション - 結果を優先");
-                        }
-                        msg
-                    },
-                    _ => {
-                        if !response.response_text.is_empty() {
-                            if schedule_ai_agent::debug::is_debug_enabled() {
-                                eprintln!("🔍 DEBUG: response_textを使用: '{}'", response.response_text);
-                            }
-                            response.response_text
-                        } else {
-                            if schedule_ai_agent::debug::is_debug_enabled() {
-                                eprintln!("🔍 DEBUG: 処理結果を使用: '{}'", msg);
-                            }
-                            msg
-                        }
+                // ListEvents・SearchEvents・SummarizeEventsアクションの場合は、結果を優先して返す
+                let final_result = if shows_result(&response.action) {
+                    if schedule_ai_agent::debug::is_debug_enabled() {
+                        status("🔍 DEBUG: ListEventsアクション - 結果を優先");
+                    }
+                    msg
+                } else if !response.response_text.is_empty() {
+                    if schedule_ai_agent::debug::is_debug_enabled() {
+                        status(&format!("🔍 DEBUG: response_textを使用: '{}'", response.response_text));
+                    }
+                    response.response_text
+                } else {
+                    if schedule_ai_agent::debug::is_debug_enabled() {
+                        status(&format!("🔍 DEBUG: 処理結果を使用: '{}'", msg));
                     }
+                    msg
                 };
+                let final_result = self
+                    .run_follow_up_actions(final_result, &response.follow_up_actions, started)
+                    .await?;
                 if schedule_ai_agent::debug::is_debug_enabled() {
-                    eprintln!("🔍 DEBUG SUCCESS: 最終結果: '{}'", final_result);
+                    status(&format!("🔍 DEBUG SUCCESS: 最終結果: '{}'", final_result));
                 }
-                Ok(final_result)
+                Ok(explained(final_result))
             }
             Err(e) => {
                 if schedule_ai_agent::debug::is_debug_enabled() {
-                    eprintln!("🔍 DEBUG ERROR: エラーが発生: {:?}", e);
+                    status(&format!("🔍 DEBUG ERROR: エラーが発生: {:?}", e));
                 }
                 // AIの応答メッセージとエラーメッセージを組み合わせる
                 let combined_msg = if !response.response_text.is_empty() {
@@ -170,21 +320,221 @@ impl Scheduler {
                 } else {
                     format!("❌ エラーが発生しました: {}", e)
                 };
+                let combined_msg = note_skipped_follow_ups(combined_msg, response.follow_up_actions.len());
                 
                 // エラーメッセージを会話履歴に追加（失敗しても処理を続行）
                 let _ = self.conversation_history.add_assistant_message(combined_msg.clone(), None);
                 let _ = self.save_conversation_history();
-                Ok(combined_msg)
+                Ok(explained(combined_msg))
+            }
+        }
+    }
+
+    /// LLMの応答が示すアクションを1件実行し、結果のメッセージを返す
+    ///
+    /// 確認が必要な場合は pending_past_event / pending_deletion を設定し、確認の文面を返す。
+    async fn execute_action(&mut self, response: &LLMResponse) -> Result<String> {
+        // カレンダーを変更する依頼の後は、古い状態を前提にした応答を使わない
+        if modifies_calendar(&response.action) {
+            self.response_cache.clear();
+        }
+
+        match response.action {
+            ActionType::CreateEvent => {
+                if let Some(event_data) = response.event_data.clone() {
+                    if let Some(warning) = self.hold_past_event(&event_data) {
+                        return Ok(warning);
+                    }
+                    if let Some(warning) = self.hold_busy_attendees(&event_data).await {
+                        return Ok(warning);
+                    }
+                    self.create_event_from_data(event_data).await
+                } else {
+                    Ok("イベントデータが不足しています。".to_string())
+                }
+            }
+            ActionType::UpdateEvent => {
+                if let Some(event_data) = response.event_data.clone() {
+                    self.update_event(event_data).await
+                        .map_err(|e| anyhow::anyhow!(e))
+                } else {
+                    Ok("イベントデータが不足しています。".to_string())
+                }
+            }
+            ActionType::DeleteEvent => {
+                if let Some(event_data) = response.event_data.clone() {
+                    match self.delete_event(event_data).await {
+                        Ok(Some(confirmation)) => Ok(confirmation),
+                        Ok(None) => Ok("予定を削除しました。".to_string()),
+                        Err(e) => Err(anyhow::anyhow!(e)),
+                    }
+                } else {
+                    Ok("イベントデータが不足しています。".to_string())
+                }
+            }
+            ActionType::ListEvents => {
+                self.get_list_events(response).await
+            }
+            ActionType::SearchEvents => {
+                self.search_calendar_events(response).await
+            }
+            ActionType::GetEventDetails => {
+                if let Some(event_data) = response.event_data.clone() {
+                    self.get_event_details(event_data).await
+                        .map_err(|e| anyhow::anyhow!(e))
+                } else {
+                    Ok("イベントデータが不足しています。".to_string())
+                }
+            }
+            ActionType::FindNextEvent => {
+                self.find_next_calendar_event(response).await
+            }
+            ActionType::FindFreeTime => {
+                self.find_free_time_from_response(response).await
+            }
+            ActionType::SummarizeEvents => {
+                let (start, end) = self.get_query_time_range(response);
+                match &self.calendar_client {
+                    Some(client) => self.summarize_events(client, start, end).await,
+                    None => Err(anyhow::anyhow!("Google Calendarクライアントが設定されていません")),
+                }
+            }
+            ActionType::OutOfOffice => {
+                self.create_out_of_office_from_response(response).await
+            }
+            ActionType::RescheduleEvent => {
+                if let Some(event_data) = response.event_data.clone() {
+                    self.reschedule_event(event_data).await
+                        .map_err(|e| anyhow::anyhow!(e))
+                } else {
+                    Ok("イベントデータが不足しています。".to_string())
+                }
+            }
+            ActionType::GeneralResponse => {
+                Ok(response.response_text.clone())
+            }
+        }
+    }
+
+    /// 同じ依頼に含まれる後続のアクションを順に実行し、結果を`message`の後ろに並べる
+    ///
+    /// 各結果は会話履歴と利用状況ログにも残す。確認が必要になった場合は残りを確認の後まで保留し、
+    /// 失敗した場合は残りを実行せずにその旨を添える。
+    async fn run_follow_up_actions(
+        &mut self,
+        mut message: String,
+        follow_ups: &[LLMResponse],
+        started: std::time::Instant,
+    ) -> Result<String> {
+        for (i, follow_up) in follow_ups.iter().enumerate() {
+            let result = self.execute_action(follow_up).await;
+            self.record_usage(Some(follow_up.action.clone()), result.is_ok(), started);
+            let text = match result {
+                Ok(result) if self.awaiting_confirmation() => {
+                    let text = self.hold_follow_up_actions(result, &follow_ups[i + 1..]);
+                    self.conversation_history.add_assistant_message(text.clone(), None);
+                    message.push_str(&format!("\n\n{}", text));
+                    break;
+                }
+                Ok(result) if shows_result(&follow_up.action) || follow_up.response_text.is_empty() => result,
+                Ok(_) => follow_up.response_text.clone(),
+                Err(e) => {
+                    let text = note_skipped_follow_ups(
+                        format!("❌ エラーが発生しました: {}", e),
+                        follow_ups.len() - i - 1,
+                    );
+                    self.conversation_history.add_assistant_message(text.clone(), None);
+                    message.push_str(&format!("\n\n{}", text));
+                    break;
+                }
+            };
+            self.conversation_history.add_assistant_message(text.clone(), None);
+            message.push_str(&format!("\n\n{}", text));
+        }
+        if !follow_ups.is_empty() {
+            self.save_conversation_history()?;
+        }
+        Ok(message)
+    }
+
+    /// 確認の返事を待つ間、`follow_ups`を保留し、確認の文面にその旨を添える
+    fn hold_follow_up_actions(&mut self, confirmation: String, follow_ups: &[LLMResponse]) -> String {
+        self.pending_follow_ups = follow_ups.to_vec();
+        if follow_ups.is_empty() {
+            return confirmation;
+        }
+        format!(
+            "{}\n（確認の後に、続く{}件の操作を実行します）",
+            confirmation,
+            follow_ups.len()
+        )
+    }
+
+    /// 過去の日時や参加者の予定を理由にした作成、予定の削除について、確認の返事を待っているか
+    fn awaiting_confirmation(&self) -> bool {
+        self.pending_past_event.is_some() || self.pending_busy_attendees.is_some() || self.pending_deletion.is_some()
+    }
+
+    /// 保留していた予定の作成について、確認の返事に応じて作成するか取りやめる
+    ///
+    /// `check_attendees`なら、作成する前に参加者の空き状況も確かめる。
+    async fn answer_pending_creation(
+        &mut self,
+        event_data: EventData,
+        confirmed: bool,
+        check_attendees: bool,
+        follow_ups: Vec<LLMResponse>,
+        started: std::time::Instant,
+    ) -> Result<String> {
+        if !confirmed {
+            self.record_usage(Some(ActionType::CreateEvent), true, started);
+            return Ok(note_skipped_follow_ups("予定の作成を取りやめました。".to_string(), follow_ups.len()));
+        }
+        if check_attendees {
+            if let Some(warning) = self.hold_busy_attendees(&event_data).await {
+                self.record_usage(Some(ActionType::CreateEvent), true, started);
+                return Ok(self.hold_follow_up_actions(warning, &follow_ups));
             }
         }
+        let result = self.create_event_from_data(event_data).await;
+        self.record_usage(Some(ActionType::CreateEvent), result.is_ok(), started);
+        match result {
+            Ok(message) => self.run_follow_up_actions(message, &follow_ups, started).await,
+            Err(e) => Ok(note_skipped_follow_ups(format!("❌ エラーが発生しました: {}", e), follow_ups.len())),
+        }
+    }
+
+    /// 利用状況ログ（app.usage_metrics）に依頼1件分を記録する
+    ///
+    /// 入力や応答の内容は記録しない。記録に失敗しても依頼の処理は続ける。
+    fn record_usage(&self, action: Option<ActionType>, success: bool, started: std::time::Instant) {
+        if !self.config.app.usage_metrics.unwrap_or(false) {
+            return;
+        }
+        let record = UsageRecord {
+            timestamp: self.clock.now(),
+            action,
+            success,
+            latency_ms: started.elapsed().as_millis() as u64,
+        };
+        if let Err(e) = self.storage.append_usage_record(&record) {
+            status(&format!("⚠️ 利用状況ログに記録できませんでした: {}", e));
+        }
     }
 
+    /// 会話履歴を消去する（--start-emptyで開始した場合も、以降の履歴は保存先に書き出す）
     pub fn clear_conversation_history(&mut self) -> Result<()> {
         self.conversation_history.clear();
         self.storage.clear_conversation_history()?;
+        self.persist_history = true;
         Ok(())
     }
 
+    /// 現在の会話履歴
+    pub fn conversation_history(&self) -> &ConversationHistory {
+        &self.conversation_history
+    }
+
     pub fn get_conversation_summary(&self) -> String {
         let messages = &self.conversation_history.messages;
         
@@ -232,10 +582,122 @@ impl Scheduler {
         summary
     }
 
+    /// Google Calendarと連携しているかどうか
+    pub fn is_calendar_connected(&self) -> bool {
+        self.calendar_client.is_some()
+    }
+
+    /// 今日これから始まる予定の件数（未連携や取得失敗時はNone）
+    pub async fn remaining_today_event_count(&self) -> Option<usize> {
+        let calendar_client = self.calendar_client.as_ref()?;
+        let now = self.clock.now();
+        let (_, end_of_day) = day_window(now.with_timezone(&Tokyo).date_naive());
+        let events = calendar_client
+            .get_events_in_range("primary", now, end_of_day, 250)
+            .await
+            .ok()?;
+        Some(
+            events
+                .items
+                .unwrap_or_default()
+                .iter()
+                .filter(|event| event_start(event).is_some_and(|start| start >= now))
+                .count(),
+        )
+    }
+
+    /// 開始時に今日の予定を表示するか（app.show_agenda_on_start）
+    pub fn show_agenda_on_start(&self) -> bool {
+        self.config.app.show_agenda_on_start.unwrap_or(false)
+    }
+
+    /// TUIで応答を待つ間に表示する文言（app.thinking_message）
+    pub fn thinking_message(&self) -> String {
+        self.config
+            .app
+            .thinking_message
+            .clone()
+            .filter(|message| !message.trim().is_empty())
+            .unwrap_or_else(|| "考え中です...".to_string())
+    }
+
+    /// 今日の予定を1件1行で返す（未連携や取得失敗時はNone）
+    pub async fn todays_agenda(&self) -> Option<Vec<String>> {
+        let calendar_client = self.calendar_client.as_ref()?;
+        let (start_of_day, end_of_day) = day_window(self.clock.now().with_timezone(&Tokyo).date_naive());
+        let events = calendar_client
+            .get_events_in_range("primary", start_of_day, end_of_day, 250)
+            .await
+            .ok()?;
+        Some(
+            events
+                .items
+                .unwrap_or_default()
+                .iter()
+                .enumerate()
+                .map(|(i, event)| format_event_line(event, i + 1, &self.config.app, false))
+                .collect(),
+        )
+    }
+
+    /// LLMの応答をどう解釈したかを「解釈: 予定作成 / 2025-07-01 15:00-16:00 / 会議」の形で返す
+    fn explain_response(&self, response: &LLMResponse) -> String {
+        let mut parts = vec![response.action.label().to_string()];
+        let event_data = response.event_data.as_ref();
+
+        // 日時は日本時間に直して表示する（解釈できなければそのまま）
+        let format_time = |value: &str| match self.parse_datetime(value) {
+            Ok(datetime) => datetime.with_timezone(&Tokyo).format("%Y-%m-%d %H:%M").to_string(),
+            Err(_) => value.to_string(),
+        };
+        let start = event_data
+            .and_then(|data| data.start_time.clone())
+            .or_else(|| response.start_time.map(|start| start.to_rfc3339()));
+        let end = event_data
+            .and_then(|data| data.end_time.clone())
+            .or_else(|| response.end_time.map(|end| end.to_rfc3339()));
+        match (start.as_deref().map(format_time), end.as_deref().map(format_time)) {
+            (Some(start), Some(end)) => {
+                // 同じ日なら終了側は時刻だけにする
+                let end = match (start.split_once(' '), end.split_once(' ')) {
+                    (Some((start_date, _)), Some((end_date, end_time))) if start_date == end_date => end_time.to_string(),
+                    _ => end,
+                };
+                parts.push(format!("{}-{}", start, end));
+            }
+            (Some(start), None) => parts.push(format!("{}-", start)),
+            (None, Some(end)) => parts.push(format!("-{}", end)),
+            (None, None) => {}
+        }
+
+        if let Some(data) = event_data {
+            if let Some(title) = data.title.as_deref().filter(|title| !title.is_empty()) {
+                parts.push(title.to_string());
+            }
+            if let Some(location) = data.location.as_deref().filter(|location| !location.is_empty()) {
+                parts.push(format!("場所: {}", location));
+            }
+            if !data.attendees.is_empty() {
+                parts.push(format!("参加者: {}", data.attendees.join(", ")));
+            }
+        }
+
+        format!("🔎 解釈: {}", parts.join(" / "))
+    }
+
+    /// 会話履歴を保存先に書き出す（--start-emptyのセッションでは既存の履歴を残すため何もしない）
     fn save_conversation_history(&self) -> Result<()> {
+        if !self.persist_history {
+            return Ok(());
+        }
         self.storage.save_conversation_history(&self.conversation_history)
     }
 
+    /// 終了時などに、現在の会話履歴を保存先へ書き出す
+    pub fn flush_conversation_history(&self) -> Result<()> {
+        self.save_conversation_history()
+    }
+
     fn create_context(&self) -> String {
         if self.calendar_client.is_some() {
             "Google Calendar連携が有効です。\n".to_string()
@@ -245,44 +707,66 @@ impl Scheduler {
     }
 
     async fn get_list_events(&mut self, response: &LLMResponse) -> Result<String> {
-        let (query_start, query_end) = self.get_query_time_range(&response);
-        let query_range_str = format!(
-            "📅 {}から{}までの予定",
-            query_start.format("%Y年%m月%d日 %H:%M"),
-            query_end.format("%Y年%m月%d日 %H:%M")
-        );
+        let (query_start, query_end) = self.get_list_time_range(response);
+
+        // 「仕事カレンダーの予定」のようにカレンダーを名前で指定された場合
+        let calendar_name = response.event_data.as_ref().and_then(|data| data.calendar.clone());
+        let calendar_id = match &calendar_name {
+            Some(name) if self.calendar_client.is_some() => match self.resolve_calendar(name).await {
+                Ok(calendar_id) => calendar_id,
+                Err(e) => return Ok(format!("⚠️ {}", e)),
+            },
+            _ => "primary".to_string(),
+        };
+
+        let query_range_str = match query_end {
+            Some(query_end) => format!(
+                "📅 {}から{}までの予定",
+                query_start.format("%Y年%m月%d日 %H:%M"),
+                query_end.format("%Y年%m月%d日 %H:%M")
+            ),
+            None => format!("📅 {}以降のすべての予定", query_start.format("%Y年%m月%d日 %H:%M")),
+        };
+        let query_range_str = match &calendar_name {
+            Some(name) => format!("{}（{}）", query_range_str, name),
+            None => query_range_str,
+        };
 
         // デバッグ: LLMレスポンスの情報を確認
         if schedule_ai_agent::debug::is_debug_enabled() {
-            eprintln!("🔍 DEBUG: LLMレスポンス確認:");
-            eprintln!("🔍 DEBUG: • アクション: {:?}", response.action);
-            eprintln!("🔍 DEBUG: • レスポンステキスト: '{}'", response.response_text);
-            eprintln!("🔍 DEBUG: • 開始時刻: {:?}", response.start_time);
-            eprintln!("🔍 DEBUG: • 終了時刻: {:?}", response.end_time);
+            status("🔍 DEBUG: LLMレスポンス確認:");
+            status(&format!("🔍 DEBUG: • アクション: {:?}", response.action));
+            status(&format!("🔍 DEBUG: • レスポンステキスト: '{}'", response.response_text));
+            status(&format!("🔍 DEBUG: • 開始時刻: {:?}", response.start_time));
+            status(&format!("🔍 DEBUG: • 終了時刻: {:?}", response.end_time));
         }
 
         // Google Calendarから予定を取得
         match &self.calendar_client {
             Some(google_calendar) => {
-                match google_calendar.get_events_in_range("primary", query_start, query_end, 50).await {
+                let events = match query_end {
+                    Some(query_end) => google_calendar.get_events_in_range(&calendar_id, query_start, query_end, 50).await,
+                    None => google_calendar.get_upcoming_events(&calendar_id, query_start, 50).await,
+                };
+                match events {
                     Ok(events) => {
                         let formatted_events = self.format_calendar_events(&events, &query_range_str);
                         
                         // デバッグ情報を追加
                         let event_count = events.items.as_ref().map(|items| items.len()).unwrap_or(0);
                         if schedule_ai_agent::debug::is_debug_enabled() {
-                            eprintln!("🔍 DEBUG: 検索結果: {} 件のイベントが見つかりました", event_count);
-                            eprintln!("🔍 DEBUG: 時間範囲: {} - {}", 
+                            status(&format!("🔍 DEBUG: 検索結果: {} 件のイベントが見つかりました", event_count));
+                            status(&format!("🔍 DEBUG: 時間範囲: {} - {}", 
                                 query_start.format("%Y-%m-%d %H:%M"),
-                                query_end.format("%Y-%m-%d %H:%M")
-                            );
+                                query_end.map(|end| end.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default()
+                            ));
                         }
                         
                         Ok(formatted_events)
                     }
                     Err(e) => {
                         if schedule_ai_agent::debug::is_debug_enabled() {
-                            eprintln!("🔍 DEBUG ERROR: Google Calendar取得エラー: {}", e);
+                            status(&format!("🔍 DEBUG ERROR: Google Calendar取得エラー: {}", e));
                         }
                         Ok(format!("❌ Google Calendar取得エラー: {}", e))
                     }
@@ -290,13 +774,27 @@ impl Scheduler {
             }
             None => {
                 if schedule_ai_agent::debug::is_debug_enabled() {
-                    eprintln!("🔍 DEBUG WARN: Google Calendarが設定されていません");
+                    status("🔍 DEBUG WARN: Google Calendarが設定されていません");
                 }
                 Ok("⚠️ Google Calendarが設定されていません。".to_string())
             }
         }
     }
     
+    /// カレンダー名をIDに解決する（一度解決した名前は覚えておく）
+    async fn resolve_calendar(&mut self, name: &str) -> Result<String> {
+        if let Some(calendar_id) = self.calendar_ids.get(name) {
+            return Ok(calendar_id.clone());
+        }
+        let calendar_client = self
+            .calendar_client
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Google Calendarクライアントが設定されていません"))?;
+        let calendar_id = resolve_calendar_id(calendar_client, name).await?;
+        self.calendar_ids.insert(name.to_string(), calendar_id.clone());
+        Ok(calendar_id)
+    }
+
     // カレンダー関連のコマンド実装 
 
     /// Google Calendarイベントをフォーマットして文字列で返す
@@ -317,48 +815,203 @@ impl Scheduler {
 
     /// Google Calendarのイベントをフォーマットして文字列で返す
     fn format_google_calendar_event(&self, event: &google_calendar3::api::Event, index: usize) -> String {
-        let mut result = format!("{}. ", index);
+        let show_description = self.config.app.list_show_description.unwrap_or(false);
+        format_event_line(event, index, &self.config.app, show_description)
+    }
 
-        // タイトル（必須項目として最初に表示）
-        if let Some(summary) = &event.summary {
-            result.push_str(&format!("📝 {}", summary));
-        } else {
-            result.push_str("📝 (タイトルなし)");
+    /// Google Calendarの予定をキーワードで検索する
+    async fn search_calendar_events(&self, response: &LLMResponse) -> Result<String> {
+        let query = response
+            .event_data
+            .as_ref()
+            .and_then(|data| data.title.clone())
+            .ok_or_else(|| SchedulerError::ValidationError("検索キーワードが必要です".to_string()))?;
+        let (start, end) = self.get_query_time_range(response);
+
+        let client = self
+            .calendar_client
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Google Calendarクライアントが設定されていません"))?;
+        let events = search_events(client, &query, start, end).await?;
+        if events.is_empty() {
+            return Ok(format!("🔍 「{}」に一致する予定は見つかりませんでした。", query));
         }
 
-        // 開始・終了時刻を1行にまとめる
-        let mut time_info = String::new();
-        if let Some(start) = &event.start {
-            if let Some(date_time) = &start.date_time {
-                let start_jst = date_time.with_timezone(&Tokyo);
-                time_info.push_str(&format!("{}", start_jst.format("%m/%d %H:%M")));
-            } else if let Some(date) = &start.date {
-                time_info.push_str(&format!("{}", date.format("%m/%d")));
-            }
+        let mut result = format!("🔍 「{}」の検索結果（{}件）\n", query, events.len());
+        for (i, event) in events.iter().enumerate() {
+            result.push_str(&self.format_google_calendar_event(event, i + 1));
         }
+        Ok(result)
+    }
 
-        if let Some(end) = &event.end {
-            if let Some(date_time) = &end.date_time {
-                let end_jst = date_time.with_timezone(&Tokyo);
-                time_info.push_str(&format!("-{}", end_jst.format("%H:%M")));
-            } else if let Some(date) = &end.date {
-                if !time_info.is_empty() {
-                    time_info.push_str(&format!("-{}", date));
-                }
-            }
+    /// 不在の予定を作成する（辞退時のメッセージには説明文を使う）
+    async fn create_out_of_office_from_response(&self, response: &LLMResponse) -> Result<String> {
+        let event_data = response
+            .event_data
+            .as_ref()
+            .ok_or_else(|| SchedulerError::ValidationError("不在の期間が必要です".to_string()))?;
+        let start_time = event_data
+            .start_time
+            .as_deref()
+            .ok_or_else(|| SchedulerError::ValidationError("開始時刻が必要です".to_string()))?;
+        let end_time = event_data
+            .end_time
+            .as_deref()
+            .ok_or_else(|| SchedulerError::ValidationError("終了時刻が必要です".to_string()))?;
+        let start_time = self.parse_datetime(start_time)?;
+        let end_time = self.parse_datetime(end_time)?;
+
+        let client = self
+            .calendar_client
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Google Calendarクライアントが設定されていません"))?;
+        create_out_of_office(client, start_time, end_time, event_data.description.as_deref()).await?;
+        Ok(format!(
+            "{}から{}まで不在に設定しました。期間中の招待は自動で辞退されます。",
+            start_time.with_timezone(&Tokyo).format("%m/%d %H:%M"),
+            end_time.with_timezone(&Tokyo).format("%m/%d %H:%M")
+        ))
+    }
+
+    /// キーワードに一致する次回の予定を1件だけ返す
+    async fn find_next_calendar_event(&self, response: &LLMResponse) -> Result<String> {
+        let query = response
+            .event_data
+            .as_ref()
+            .and_then(|data| data.title.clone())
+            .ok_or_else(|| SchedulerError::ValidationError("予定名が必要です".to_string()))?;
+
+        let client = self
+            .calendar_client
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Google Calendarクライアントが設定されていません"))?;
+        match find_next_event(client, &query, self.clock.now(), chrono::Duration::days(90)).await? {
+            Some(event) => Ok(format!(
+                "次の「{}」はこちらです:\n{}",
+                query,
+                self.format_google_calendar_event(&event, 1)
+            )),
+            None => Ok(format!("「{}」の予定は見つかりません", query)),
         }
+    }
+
+    /// 指定された期間（未指定なら今日）のうち、現在時刻以降の空き時間を返す
+    async fn find_free_time_from_response(&self, response: &LLMResponse) -> Result<String> {
+        let now = self.clock.now();
+        let (start, end) = match (response.start_time, response.end_time) {
+            (Some(start), Some(end)) if end > start => (start, end),
+            _ => day_window(now.with_timezone(&Tokyo).date_naive()),
+        };
+        let Some((start, end)) = upcoming_window(start, end, now) else {
+            return Ok("指定した期間はすでに過ぎています".to_string());
+        };
+        let minutes = response
+            .event_data
+            .as_ref()
+            .and_then(|data| data.duration_minutes)
+            .unwrap_or(60);
 
-        if !time_info.is_empty() {
-            result.push_str(&format!(" 🕐 {}", time_info));
+        let client = self
+            .calendar_client
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Google Calendarクライアントが設定されていません"))?;
+        let events = client.list_primary_events(start, end).await?;
+        let buffer = chrono::Duration::minutes(self.config.calendar.free_time_buffer_minutes.unwrap_or(0));
+        let slots = find_free_slots_with_buffer(&events, start, end, chrono::Duration::minutes(minutes), buffer);
+        if slots.is_empty() {
+            return Ok(format!("{}分以上の空き時間は見つかりませんでした", minutes));
         }
 
-        // 場所（ある場合のみ）
-        if let Some(location) = &event.location {
-            result.push_str(&format!(" 📍 {}", location));
+        let lines: Vec<String> = slots.iter().map(|slot| self.format_free_slot(slot)).collect();
+        Ok(format!("{}分以上の空き時間:\n{}", minutes, lines.join("\n")))
+    }
+
+    /// 空き時間を「・07/01 10:00〜11:00（60分）」の形式で表示する
+    fn format_free_slot(&self, (slot_start, slot_end): &(DateTime<Utc>, DateTime<Utc>)) -> String {
+        let app_config = &self.config.app;
+        let start_jst = slot_start.with_timezone(&Tokyo);
+        format!(
+            "・{} {}〜{}（{}分）",
+            app_config.format_date(start_jst.date_naive()),
+            start_jst.format(app_config.time_format()),
+            slot_end.with_timezone(&Tokyo).format(app_config.time_format()),
+            (*slot_end - *slot_start).num_minutes()
+        )
+    }
+
+    /// 希望の時間帯が既存の予定と重なる場合、近い空き時間を提案するメッセージを返す
+    ///
+    /// 希望日とその翌日から、同じ長さの空き時間を開始時刻の近い順に最大3件探す。
+    async fn suggest_alternative_slots(
+        &self,
+        backend: &dyn CalendarBackend,
+        title: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Option<String>> {
+        let date = start.with_timezone(&Tokyo).date_naive();
+        let window = (day_window(date).0, day_window(date + chrono::Duration::days(1)).1);
+        let events = backend.list_primary_events(window.0, window.1).await?;
+        let conflicts = conflicting_events(&events, start, end);
+        if conflicts.is_empty() {
+            return Ok(None);
         }
 
-        result.push('\n');
-        result
+        let names: Vec<String> = conflicts
+            .iter()
+            .map(|event| format!("「{}」", event.summary.as_deref().unwrap_or("（タイトルなし）")))
+            .collect();
+        let message = format!("⚠️ 予定「{}」は{}と時間が重なるため作成していません。", title, names.join("、"));
+        let slots = nearest_free_slots(&events, window, (start, end), self.clock.now(), 3);
+        if slots.is_empty() {
+            return Ok(Some(format!(
+                "{}\n当日と翌日に{}分の空き時間は見つかりませんでした。",
+                message,
+                (end - start).num_minutes()
+            )));
+        }
+
+        let lines: Vec<String> = slots.iter().map(|slot| self.format_free_slot(slot)).collect();
+        Ok(Some(format!(
+            "{}\n近い空き時間の候補:\n{}\n候補の時間を指定して、もう一度依頼してください。",
+            message,
+            lines.join("\n")
+        )))
+    }
+
+    /// 期間内の予定を取得し、LLMに要約させる
+    ///
+    /// 取得した予定一覧をコンテキストとして2回目のLLMリクエストを行い、その応答文を返す。
+    async fn summarize_events(
+        &self,
+        backend: &dyn CalendarBackend,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<String> {
+        let events = backend.list_primary_events(start, end).await?;
+        if events.is_empty() {
+            return Ok("指定された期間に予定はありません。".to_string());
+        }
+
+        let listing: String = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| self.format_google_calendar_event(event, i + 1))
+            .collect();
+        let request = LLMRequest {
+            user_input: "以下の予定を3つの箇条書きで要約してください。アクションはGENERAL_RESPONSEとし、要約をresponse_textに入れてください。".to_string(),
+            context: Some(format!(
+                "期間: {} 〜 {}\n予定一覧:\n{}",
+                start.with_timezone(&Tokyo).format(self.config.app.datetime_format()),
+                end.with_timezone(&Tokyo).format(self.config.app.datetime_format()),
+                listing
+            )),
+            conversation_history: None,
+            temperature: None,
+        };
+
+        let response = self.llm.process_request(request).await?;
+        Ok(response.response_text)
     }
 
     /// クエリの時間範囲を取得
@@ -367,42 +1020,151 @@ impl Scheduler {
         match (response.start_time, response.end_time) {
             (Some(start), Some(end)) => (start, end),
             _ => {
-                // デフォルト: 今日の00:00から1週間後の23:59まで
-                let now = Utc::now();
+                // デフォルト: 今日の00:00からdefault_list_days日後の23:59まで
+                let days = self.config.calendar.default_list_days.unwrap_or(7).max(1);
+                let now = self.clock.now();
                 let start_of_today = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
-                let end_of_week = start_of_today + chrono::Duration::days(7) - chrono::Duration::seconds(1);
-                (start_of_today, end_of_week)
+                let end_of_range = start_of_today + chrono::Duration::days(days) - chrono::Duration::seconds(1);
+                (start_of_today, end_of_range)
             }
         }
     }
 
-    // Googleカレンダーにイベントを新規作成
-    async fn create_event_from_data(&mut self, event_data: EventData) -> Result<String> {
-        // 必要な情報が揃っているかチェック
-        let title = event_data.title.as_ref()
-            .ok_or_else(|| SchedulerError::ValidationError("タイトルが必要です".to_string()))?;
-
-        let start_time_str = event_data.start_time.as_ref()
-            .ok_or_else(|| SchedulerError::ValidationError("開始時刻が必要です".to_string()))?;
+    /// 予定一覧の時間範囲を取得（今後の予定をすべて対象にする場合は終了なし）
+    fn get_list_time_range(&self, response: &LLMResponse) -> (DateTime<Utc>, Option<DateTime<Utc>>) {
+        let all_upcoming = response
+            .event_data
+            .as_ref()
+            .and_then(|data| data.all_upcoming)
+            .unwrap_or(false);
+        if all_upcoming {
+            return (response.start_time.unwrap_or_else(|| self.clock.now()), None);
+        }
+        let (start, end) = self.get_query_time_range(response);
+        (start, Some(end))
+    }
 
-        let end_time_str = event_data.end_time.as_ref()
-            .ok_or_else(|| SchedulerError::ValidationError("終了時刻が必要です".to_string()))?;
+    /// 開始時刻が過去の予定は作成を保留し、確認を求めるメッセージを返す
+    ///
+    /// 年の入力ミスなどで意図せず過去に予定を作るのを防ぐ。
+    fn hold_past_event(&mut self, event_data: &EventData) -> Option<String> {
+        let start_time = self.parse_datetime(event_data.start_time.as_deref()?).ok()?;
+        if start_time >= self.clock.now() {
+            return None;
+        }
+        self.pending_past_event = Some(event_data.clone());
+        Some(format!(
+            "⚠️ 開始時刻 {} は過去の日時です（年や日付の入力ミスではありませんか？）。\nこのまま作成する場合は「はい」、取りやめる場合は「いいえ」と入力してください。",
+            self.config.app.format_datetime(start_time.with_timezone(&Tokyo).naive_local())
+        ))
+    }
 
-        let start_time = self.parse_datetime(start_time_str)?;
-        let end_time = self.parse_datetime(end_time_str)?;
+    /// 参加者に予定が入っている（または確認できない）予定は作成を保留し、確認を求めるメッセージを返す
+    async fn hold_busy_attendees(&mut self, event_data: &EventData) -> Option<String> {
+        let calendar_client = self.calendar_client.as_ref()?;
+        let warning = self.busy_attendee_warning(calendar_client, event_data).await?;
+        self.pending_busy_attendees = Some(event_data.clone());
+        Some(warning)
+    }
+
+    /// 予定あり・不明の参加者を知らせる確認の文面（参加者がいない、または全員空いていればNone）
+    async fn busy_attendee_warning(&self, backend: &dyn CalendarBackend, event_data: &EventData) -> Option<String> {
+        if event_data.attendees.is_empty() {
+            return None;
+        }
+        let start_time = self.parse_datetime(event_data.start_time.as_deref()?).ok()?;
+        let end_time = self.parse_datetime(event_data.end_time.as_deref()?).ok()?;
+        let results = check_attendee_availability(backend, &event_data.attendees, start_time, end_time).await;
+        let lines = unavailable_attendee_lines(&results, &self.config.app);
+        if lines.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "⚠️ 参加者の空き状況を確認しました（予定あり・不明の方）:\n{}\nこのまま作成する場合は「はい」、取りやめる場合は「いいえ」と入力してください。",
+            lines.join("\n")
+        ))
+    }
+
+    /// 作成する予定の内容を組み立てる（優先度の指定がなければ app.default_priority を使う）
+    ///
+    /// 通知の指定があれば calendar.default_reminders の代わりにその通知を付ける。
+    fn build_calendar_event(
+        &self,
+        event_data: &EventData,
+        title: &str,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> google_calendar3::api::Event {
+        let mut builder = EventBuilder::new()
+            .summary(title)
+            .start_time(start_time)
+            .end_time(end_time)
+            .attendees(&event_data.attendees);
+        if let Some(description) = &event_data.description {
+            builder = builder.description(&render_description_template(description, title, start_time, end_time));
+        }
+        if let Some(location) = &event_data.location {
+            builder = builder.location(location);
+        }
+        if let Some(transparency) = &event_data.transparency {
+            builder = builder.transparency(transparency);
+        }
+        if let Some(visibility) = &event_data.visibility {
+            builder = builder.visibility(visibility);
+        }
+        let priority = event_data.priority.clone().or_else(|| self.config.app.default_priority());
+        if let Some(color_id) = priority.as_ref().and_then(priority_color_id) {
+            builder = builder.color_id(color_id);
+        }
+        let mut event = builder.attachments(&event_data.attachments).build();
+        if let Some(minutes) = &event_data.reminders {
+            event.reminders = Some(reminder_overrides(minutes));
+        }
+        event
+    }
+
+    // Googleカレンダーにイベントを新規作成
+    async fn create_event_from_data(&mut self, event_data: EventData) -> Result<String> {
+        // 必要な情報が揃っているかチェック
+        let title = event_data.title.as_ref()
+            .ok_or_else(|| SchedulerError::ValidationError("タイトルが必要です".to_string()))?;
+
+        let start_time_str = event_data.start_time.as_ref()
+            .ok_or_else(|| SchedulerError::ValidationError("開始時刻が必要です".to_string()))?;
+
+        let end_time_str = event_data.end_time.as_ref()
+            .ok_or_else(|| SchedulerError::ValidationError("終了時刻が必要です".to_string()))?;
+
+        let start_time = self.parse_datetime(start_time_str)?;
+        let end_time = self.parse_datetime(end_time_str)?;
+
+        if end_time <= start_time {
+            return Err(SchedulerError::ValidationError(
+                "終了時刻は開始時刻より後である必要があります".to_string(),
+            )
+            .into());
+        }
+        let title = &self.config.calendar.decorate_title(title);
+
+        // 既存の予定と重なる場合は作成せず、近い空き時間を提案する
+        if let Some(ref calendar_client) = self.calendar_client {
+            if let Some(suggestion) = self
+                .suggest_alternative_slots(calendar_client, title, start_time, end_time)
+                .await?
+            {
+                return Ok(suggestion);
+            }
+        }
+
+        let event = self.build_calendar_event(&event_data, title, start_time, end_time);
 
         // Google Calendarにイベントを作成する
         if let Some(ref calendar_client) = self.calendar_client {
-            match calendar_client.create_event_from_event_data(
-                title,
-                start_time_str,
-                end_time_str,
-                event_data.description.as_deref(),
-                event_data.location.as_deref(),
-            ).await {
-                Ok(_id) => {
+            match calendar_client.create_primary_event(event).await {
+                Ok(created) => {
                     // 成功時のログはコメントアウト（TUIに表示されるため）
                     // Google Calendarにイベントを作成しました
+                    notify_event_created(self.config.app.webhook_url.as_deref(), &created, &self.config.app).await;
                 }
                 Err(e) => {
                     return Err(e.into());
@@ -421,58 +1183,276 @@ impl Scheduler {
         );
         self.save_conversation_history()?;
 
+        let app_config = &self.config.app;
         Ok(format!(
             "{}。\n開始: {}\n終了: {}",
             success_message,
-            start_time.with_timezone(&Tokyo).format("%Y-%m-%d %H:%M"),
-            end_time.with_timezone(&Tokyo).format("%Y-%m-%d %H:%M")
+            app_config.format_datetime(start_time.with_timezone(&Tokyo).naive_local()),
+            app_config.format_datetime(end_time.with_timezone(&Tokyo).naive_local())
         ))
     }
 
-    // Googleカレンダーのイベントを削除
-    async fn delete_event(&mut self, event_data: EventData) -> Result<(), String> {
+    /// Googleカレンダーの予定を削除する（確認が必要な予定は削除を保留し、確認を求めるメッセージを返す）
+    async fn delete_event(&mut self, event_data: EventData) -> Result<Option<String>, String> {
         // Google Calendarイベントの削除
         if let Some(ref calendar_client) = self.calendar_client {
-            // イベントIDが指定されている場合
-            if let Some(event_id) = &event_data.id {
-                calendar_client.delete_event("primary", event_id).await
-                    .map_err(|e| format!("Google Calendarからの削除に失敗しました: {}", e))?;
+            // 参加者への通知要否と優先度を確かめるため、削除前にイベントを取得する
+            let event = if let Some(event_id) = &event_data.id {
+                calendar_client.get_primary_event_by_id(event_id).await
+                    .map_err(|e| format!("イベントの取得に失敗しました: {}", e))?
             } else if let Some(title) = &event_data.title {
-                // タイトルで検索して削除（従来の方法）
-                // 今日の予定から該当するタイトルのイベントを検索
-                match calendar_client.get_primary_events(50).await {
-                    Ok(events) => {
-                        if let Some(items) = events.items {
-                            if let Some(event) = items.iter().find(|e| {
-                                e.summary.as_ref().map_or(false, |s| s.contains(title))
-                            }) {
-                                if let Some(event_id) = &event.id {
-                                    calendar_client.delete_event("primary", event_id).await
-                                        .map_err(|e| format!("Google Calendarからの削除に失敗しました: {}", e))?;
-                                } else {
-                                    return Err("イベントIDが見つかりません".to_string());
-                                }
-                            } else {
-                                return Err(format!("該当するイベントが見つかりません: {}", title));
-                            }
-                        } else {
-                            return Err("イベントが見つかりません".to_string());
-                        }
-                    }
-                    Err(e) => return Err(format!("イベント検索に失敗しました: {}", e)),
-                }
+                // タイトルで検索して削除（あいまい検索）
+                self.resolve_event_by_title(title).await?
             } else {
                 return Err("削除対象のイベントIDまたはタイトルが必要です".to_string());
+            };
+            if let Some(confirmation) = self.hold_deletion(&event) {
+                return Ok(Some(confirmation));
             }
+            self.delete_confirmed_event(&event).await?;
         } else {
             return Err("Google Calendarクライアントが設定されていません。".to_string());
         }
         
         self.save_conversation_history().unwrap();
-        Ok(())
+        Ok(None)
+    }
+
+    /// 優先度が高い予定（または設定ですべての予定）の削除を保留し、確認を求めるメッセージを返す
+    fn hold_deletion(&mut self, event: &google_calendar3::api::Event) -> Option<String> {
+        let confirm_all = self.config.calendar.confirm_delete.unwrap_or(false);
+        if !deletion_needs_confirmation(event, confirm_all) {
+            return None;
+        }
+        let priority = match event_priority(event) {
+            Priority::Urgent => "、優先度: 緊急",
+            Priority::High => "、優先度: 高",
+            _ => "",
+        };
+        self.pending_deletion = Some(event.clone());
+        Some(format!(
+            "⚠️ 予定「{}」（{}{}）を削除しますか？\n削除する場合は「はい」、取りやめる場合は「いいえ」と入力してください。",
+            event.summary.as_deref().unwrap_or_default(),
+            format_event_time_range(event, &self.config.app),
+            priority
+        ))
+    }
+
+    async fn delete_confirmed_event(&self, event: &google_calendar3::api::Event) -> Result<(), String> {
+        let calendar_client = self.calendar_client.as_ref()
+            .ok_or_else(|| "Google Calendarクライアントが設定されていません。".to_string())?;
+        let send_updates = self.config.calendar.delete_send_updates.as_deref();
+        delete_event_with_notice(calendar_client, event, send_updates).await
+            .map_err(|e| format!("Google Calendarからの削除に失敗しました: {}", e))
     }
+
+    // Googleカレンダーのイベントを更新
+    async fn update_event(&mut self, event_data: EventData) -> Result<String, String> {
+        let calendar_client = self.calendar_client.as_ref()
+            .ok_or_else(|| "Google Calendarクライアントが設定されていません。".to_string())?;
+
+        // 更新対象のイベントを特定
+        let mut event = if let Some(event_id) = &event_data.id {
+            calendar_client.get_primary_event_by_id(event_id).await
+                .map_err(|e| format!("イベントの取得に失敗しました: {}", e))?
+        } else if let Some(title) = &event_data.title {
+            self.resolve_event_by_title(title).await?
+        } else {
+            return Err("更新対象のイベントIDまたはタイトルが必要です".to_string());
+        };
+
+        let event_id = event.id.clone()
+            .ok_or_else(|| "イベントIDが見つかりません".to_string())?;
+
+        if let Some(description) = &event_data.description {
+            event.description = Some(description.clone());
+        }
+        if let Some(addition) = &event_data.append_description {
+            // 取得した説明の末尾に追記し、既存の内容を残す
+            event.description = Some(append_description(event.description.as_deref(), addition));
+        }
+        if let Some(priority) = &event_data.priority {
+            // Mediumは既定色に戻す
+            event.color_id = priority_color_id(priority).map(|color_id| color_id.to_string());
+        }
+        if let Some(location) = &event_data.location {
+            event.location = Some(location.clone());
+        }
+        if !event_data.add_attendees.is_empty() || !event_data.remove_attendees.is_empty() {
+            // 取得した参加者リストを元に増減し、他の参加者を落とさないようにする
+            let attendees = apply_attendee_changes(
+                event.attendees.as_deref().unwrap_or_default(),
+                &event_data.add_attendees,
+                &event_data.remove_attendees,
+            )
+            .map_err(|e| e.to_string())?;
+            event.attendees = Some(attendees);
+        }
+        if let Some(minutes) = &event_data.reminders {
+            event.reminders = Some(reminder_overrides(minutes));
+        }
+        if let Some(start_time_str) = &event_data.start_time {
+            let start_time = self.parse_datetime(start_time_str).map_err(|e| e.to_string())?;
+            event.start = Some(google_calendar3::api::EventDateTime {
+                date_time: Some(start_time),
+                time_zone: Some("Asia/Tokyo".to_string()),
+                ..Default::default()
+            });
+        }
+        if let Some(end_time_str) = &event_data.end_time {
+            let end_time = self.parse_datetime(end_time_str).map_err(|e| e.to_string())?;
+            event.end = Some(google_calendar3::api::EventDateTime {
+                date_time: Some(end_time),
+                time_zone: Some("Asia/Tokyo".to_string()),
+                ..Default::default()
+            });
+        }
+
+        // 追加する参加者に、この時間帯の予定が入っていないかを確認する
+        let mut availability_lines = Vec::new();
+        if let (false, Some(start), Some(end)) = (
+            event_data.add_attendees.is_empty(),
+            event_start(&event),
+            event.end.as_ref().and_then(|end| end.date_time),
+        ) {
+            let results = check_attendee_availability(calendar_client, &event_data.add_attendees, start, end).await;
+            availability_lines = unavailable_attendee_lines(&results, &self.config.app);
+        }
+
+        // 時刻を変えた場合は、移動前の自分自身を除いて他の予定と重ならないかを確認する
+        let mut conflict_names = Vec::new();
+        if let (true, Some(start), Some(end)) = (
+            event_data.start_time.is_some() || event_data.end_time.is_some(),
+            event_start(&event),
+            event.end.as_ref().and_then(|end| end.date_time),
+        ) {
+            if let Ok(events) = calendar_client.list_primary_events(start, end).await {
+                conflict_names = conflicting_events_excluding(&events, start, end, &event_id)
+                    .into_iter()
+                    .map(|other| format!("「{}」", other.summary.as_deref().unwrap_or("（タイトルなし）")))
+                    .collect();
+            }
+        }
+
+        let updated = calendar_client.update_primary_event(&event_id, event).await
+            .map_err(|e| format!("Google Calendarの更新に失敗しました: {}", e))?;
+
+        let mut message = format!(
+            "予定「{}」を更新しました。",
+            updated.summary.unwrap_or_default()
+        );
+        if !conflict_names.is_empty() {
+            message.push_str(&format!("\n⚠️ {}と時間が重なっています。", conflict_names.join("、")));
+        }
+        if !availability_lines.is_empty() {
+            message.push_str(&format!(
+                "\n\n追加した参加者の空き状況（予定あり・不明の方）:\n{}",
+                availability_lines.join("\n")
+            ));
+        }
+        Ok(message)
+    }
+
+    /// 予定を同じ長さのまま次の空き時間へ移動する
+    async fn reschedule_event(&self, event_data: EventData) -> Result<String, String> {
+        let calendar_client = self.calendar_client.as_ref()
+            .ok_or_else(|| "Google Calendarクライアントが設定されていません。".to_string())?;
+
+        let event = if let Some(event_id) = &event_data.id {
+            calendar_client.get_primary_event_by_id(event_id).await
+                .map_err(|e| format!("イベントの取得に失敗しました: {}", e))?
+        } else if let Some(title) = &event_data.title {
+            self.resolve_event_by_title(title).await?
+        } else {
+            return Err("移動する予定のタイトルが必要です".to_string());
+        };
+        let title = event.summary.clone().unwrap_or_default();
+        let original_time = format_event_time_range(&event, &self.config.app);
+
+        match reschedule_to_next_free_slot(calendar_client, &event, self.clock.now(), 7, self.config.app.working_hours()).await {
+            Ok(Some(moved)) if event_start(&moved) == event_start(&event) => Ok(format!(
+                "予定「{}」（{}）は他の予定と重なっていないため、時間はそのままです。",
+                title, original_time
+            )),
+            Ok(Some(moved)) => Ok(format!(
+                "予定「{}」を {} から {} に移動しました。",
+                title,
+                original_time,
+                format_event_time_range(&moved, &self.config.app)
+            )),
+            Ok(None) => Ok(format!(
+                "予定「{}」を移せる空き時間が7日先まで見つかりませんでした。予定は変更していません。",
+                title
+            )),
+            Err(e) => Err(format!("予定の移動に失敗しました: {}", e)),
+        }
+    }
+
+    /// 予定の詳細（説明と通知の設定を含む）を返す
+    async fn get_event_details(&self, event_data: EventData) -> Result<String, String> {
+        let calendar_client = self.calendar_client.as_ref()
+            .ok_or_else(|| "Google Calendarクライアントが設定されていません。".to_string())?;
+
+        let event = if let Some(event_id) = &event_data.id {
+            calendar_client.get_primary_event_by_id(event_id).await
+                .map_err(|e| format!("イベントの取得に失敗しました: {}", e))?
+        } else if let Some(title) = &event_data.title {
+            self.resolve_event_by_title(title).await?
+        } else {
+            return Err("詳細を表示するイベントIDまたはタイトルが必要です".to_string());
+        };
+
+        Ok(format_event_details(&event, &self.config.app))
+    }
+
+    /// タイトルから対象のイベントを特定する（あいまい検索）
+    ///
+    /// 候補が一意に決まらない場合は、類似度順の候補一覧をエラーとして返す
+    async fn resolve_event_by_title(&self, title: &str) -> Result<google_calendar3::api::Event, String> {
+        let calendar_client = self.calendar_client.as_ref()
+            .ok_or_else(|| "Google Calendarクライアントが設定されていません。".to_string())?;
+
+        let events = calendar_client.get_primary_events(50).await
+            .map_err(|e| format!("イベント検索に失敗しました: {}", e))?;
+        self.pick_event_by_title(&events.items.unwrap_or_default(), title)
+    }
+
+    /// 取得したイベントからタイトルに最も近いものを選ぶ（閾値は設定から読む）
+    fn pick_event_by_title(
+        &self,
+        items: &[google_calendar3::api::Event],
+        title: &str,
+    ) -> Result<google_calendar3::api::Event, String> {
+        if items.is_empty() {
+            return Err("イベントが見つかりません".to_string());
+        }
+
+        let threshold = self.config.app.fuzzy_match_threshold.unwrap_or(0.6);
+        let candidates = rank_events_by_title(items, title, threshold);
+
+        match candidates.as_slice() {
+            [] => Err(format!("該当するイベントが見つかりません: {}", title)),
+            [(_, event)] => Ok((*event).clone()),
+            [(top_score, top), (second_score, _), ..] if top_score - second_score >= 0.1 => {
+                Ok((*top).clone())
+            }
+            _ => {
+                let mut message = format!("「{}」に一致する予定が複数あります。どの予定か指定してください:\n", title);
+                for (i, (score, event)) in candidates.iter().take(3).enumerate() {
+                    let line = self.format_google_calendar_event(event, i + 1);
+                    message.push_str(&format!("{} (一致度 {:.0}%)\n", line.trim_end(), score * 100.0));
+                }
+                Err(message.trim_end().to_string())
+            }
+        }
+    }
+
     fn parse_datetime(&self, datetime_str: &str) -> Result<DateTime<Utc>, SchedulerError> {
         use chrono::{NaiveDateTime, TimeZone};
+
+        // 「午後3時」のような表現を先に「15:00」へ揃える
+        let normalized = normalize_japanese_time(datetime_str);
+        let datetime_str = normalized.as_str();
         
         // RFC3339形式を最初に試行（タイムゾーン付き）
         if let Ok(dt) = DateTime::parse_from_rfc3339(datetime_str) {
@@ -493,6 +1473,11 @@ impl Scheduler {
             }
         }
         
+        // 「2025-07-01 10:00 PST」のように末尾にタイムゾーンがあればその時差で解釈する
+        if let Some(dt) = parse_datetime_with_zone(datetime_str, self.clock.now()) {
+            return Ok(dt);
+        }
+
         // タイムゾーンなしの形式（日本時間として解釈）
         let formats_naive = [
             "%Y-%m-%d %H:%M:%S",        // 2025-07-01 15:30:00
@@ -576,8 +1561,10 @@ impl Scheduler {
         log
     }
 
-    /// Google Calendarと同期する
-    pub async fn sync_with_google_calendar(&mut self) -> Result<String> {
+    /// Google Calendarの予定を取得し、件数と先頭`limit`件の一覧を返す
+    ///
+    /// `limit`を省略した場合は設定の`sync_display_limit`を使う。
+    pub async fn sync_with_google_calendar(&mut self, limit: Option<usize>) -> Result<String> {
         let calendar_client = self.calendar_client.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Google Calendarクライアントが設定されていません"))?;
             
@@ -587,17 +1574,41 @@ impl Scheduler {
         if google_events.is_empty() {
             return Ok("Google Calendarに予定が見つかりませんでした。".to_string());
         }
-        
-        let sync_messages: Vec<String> = google_events
-            .iter()
-            .filter_map(|event| event.summary.as_ref().map(|summary| format!("• {}", summary)))
-            .collect();
 
-        Ok(format!(
-            "Google Calendarから {} 件の予定を確認しました:\n{}",
-            sync_messages.len(),
-            sync_messages.join("\n")
-        ))
+        let limit = limit
+            .or(self.config.calendar.sync_display_limit)
+            .unwrap_or(DEFAULT_SYNC_DISPLAY_LIMIT);
+        Ok(format_sync_summary(&google_events, limit))
+    }
+
+    /// 現在使っているLLMのモデル名
+    pub fn llm_model(&self) -> Option<String> {
+        self.llm.model()
+    }
+
+    /// 以降のリクエストで使うLLMのモデルを切り替え、結果のメッセージを返す
+    ///
+    /// 既知のモデル以外も指定できるが、その場合は警告を添える。`persist`なら設定ファイルにも保存する。
+    pub fn set_llm_model(&mut self, model: &str, persist: bool) -> Result<String> {
+        let model = model.trim();
+        self.llm.set_model(model)?;
+        self.config.llm.model = Some(model.to_string());
+        // 別のモデルの応答を使い回さないようにする
+        self.response_cache.clear();
+
+        let mut message = format!("✅ モデルを {} に切り替えました", model);
+        if !is_known_model(model) {
+            message.push_str(&format!(
+                "\n⚠️ {} は既知のモデルではありません（{}）。名前が正しいか確認してください",
+                model,
+                KNOWN_MODELS.join(", ")
+            ));
+        }
+        if persist {
+            ConfigManager::new()?.save_llm_model(model)?;
+            message.push_str("\n💾 設定ファイルに保存しました");
+        }
+        Ok(message)
     }
 
     /// デバッグモードを設定
@@ -607,10 +1618,10 @@ impl Scheduler {
         
         if enabled {
             if schedule_ai_agent::debug::is_debug_enabled() {
-                eprintln!("🔍 DEBUG SUCCESS: デバッグモードを有効にしました");
+                status("🔍 DEBUG SUCCESS: デバッグモードを有効にしました");
             }
         } else {
-            eprintln!("デバッグモードを無効にしました");
+            status("デバッグモードを無効にしました");
         }
     }
 
@@ -650,6 +1661,224 @@ impl Scheduler {
     }
 }
 
+/// 予定を作成・変更・削除するアクションか
+fn modifies_calendar(action: &ActionType) -> bool {
+    matches!(
+        action,
+        ActionType::CreateEvent
+            | ActionType::UpdateEvent
+            | ActionType::DeleteEvent
+            | ActionType::OutOfOffice
+            | ActionType::RescheduleEvent
+    )
+}
+
+/// 実行しなかった後続のアクションがあれば、その件数を`message`に添える
+fn note_skipped_follow_ups(message: String, skipped: usize) -> String {
+    if skipped == 0 {
+        return message;
+    }
+    format!("{}\n（続く{}件の操作は実行していません）", message, skipped)
+}
+
+/// LLMの応答文ではなく処理結果をそのまま表示するアクションか
+fn shows_result(action: &ActionType) -> bool {
+    matches!(
+        action,
+        ActionType::ListEvents
+            | ActionType::SearchEvents
+            | ActionType::GetEventDetails
+            | ActionType::FindNextEvent
+            | ActionType::FindFreeTime
+            | ActionType::RescheduleEvent
+            | ActionType::SummarizeEvents
+    )
+}
+
+/// `flag`がtrueになるまで待つ
+async fn wait_for_cancel(flag: &AtomicBool) {
+    while !flag.load(Ordering::SeqCst) {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+}
+
+/// 確認への返答を解釈する（はい: Some(true)、いいえ: Some(false)、それ以外: None）
+fn confirmation_answer(input: &str) -> Option<bool> {
+    let answer = input.trim().trim_end_matches(['。', '!', '！']).to_lowercase();
+    match answer.as_str() {
+        "はい" | "うん" | "お願いします" | "作成して" | "作成" | "削除して" | "削除" | "yes" | "y" | "ok" => Some(true),
+        "いいえ" | "いや" | "やめて" | "やめる" | "キャンセル" | "no" | "n" => Some(false),
+        _ => None,
+    }
+}
+
+/// 設定がない場合に同期結果で一覧表示する件数
+const DEFAULT_SYNC_DISPLAY_LIMIT: usize = 20;
+
+/// 同期結果を件数の要約と先頭`limit`件の一覧にまとめる
+fn format_sync_summary(events: &[google_calendar3::api::Event], limit: usize) -> String {
+    let sync_messages: Vec<String> = events
+        .iter()
+        .filter_map(|event| {
+            event
+                .summary
+                .as_ref()
+                .map(|summary| format!("• {} [{:?}]", summary, event_priority(event)))
+        })
+        .collect();
+
+    let total = sync_messages.len();
+    let mut lines = vec![if total > limit {
+        format!("Google Calendarから {} 件の予定を確認しました（先頭{}件を表示）:", total, limit)
+    } else {
+        format!("Google Calendarから {} 件の予定を確認しました:", total)
+    }];
+    lines.extend(sync_messages.into_iter().take(limit));
+    if total > limit {
+        lines.push(format!("...他{}件", total - limit));
+    }
+    lines.join("\n")
+}
+
+/// 予定の詳細（一覧と同じ1行に説明と通知の設定を加える）
+pub fn format_event_details(event: &google_calendar3::api::Event, app_config: &AppConfig) -> String {
+    let mut result = format_event_line(event, 1, app_config, true);
+    if let Some(url) = event.location.as_deref().and_then(map_url) {
+        result.push_str(&format!("   🗺️ 地図: {}\n", url));
+    }
+    result.push_str(&format!("   🔔 通知: {}\n", format_reminders(event)));
+    result
+}
+
+/// 開始時の会話履歴（読み込まない場合は空の履歴）
+fn initial_history(storage: &Storage, load_history: bool) -> Result<ConversationHistory> {
+    if load_history {
+        storage.load_conversation_history()
+    } else {
+        Ok(ConversationHistory::new())
+    }
+}
+
+/// 一覧表示用に予定を1行（説明を含める場合は2行）にまとめる
+pub fn format_event_line(
+    event: &google_calendar3::api::Event,
+    index: usize,
+    app_config: &AppConfig,
+    show_description: bool,
+) -> String {
+    let mut result = format!("{}. ", index);
+
+    // タイトル（必須項目として最初に表示）
+    if let Some(summary) = &event.summary {
+        result.push_str(&format!("📝 {}", summary));
+    } else {
+        result.push_str("📝 (タイトルなし)");
+    }
+
+    // 開始・終了時刻を1行にまとめる
+    let time_info = format_event_time_range(event, app_config);
+    if !time_info.is_empty() {
+        result.push_str(&format!(" 🕐 {}", time_info));
+    }
+
+    // 場所（ある場合のみ）
+    if let Some(location) = &event.location {
+        result.push_str(&format!(" 📍 {}", location));
+    }
+
+    // 説明（有効な場合のみ、改行をまとめて冒頭だけ）
+    if show_description {
+        if let Some(description) = event.description.as_deref() {
+            let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !description.is_empty() {
+                result.push_str(&format!(
+                    "\n   💬 {}",
+                    truncate_graphemes(&description, LIST_DESCRIPTION_LENGTH)
+                ));
+            }
+        }
+    }
+
+    result.push('\n');
+    result
+}
+
+/// 一覧に表示する説明文の最大文字数（書記素単位）
+const LIST_DESCRIPTION_LENGTH: usize = 40;
+
+/// 書記素の途中で切らないように`max`文字までに切り詰める（切り詰めた場合は末尾に…）
+fn truncate_graphemes(text: &str, max: usize) -> String {
+    let mut graphemes = text.graphemes(true);
+    let head: String = graphemes.by_ref().take(max).collect();
+    if graphemes.next().is_some() {
+        format!("{}…", head)
+    } else {
+        head
+    }
+}
+
+/// タイトルとの類似度でイベントを順位付けする
+///
+/// 類似度が`threshold`未満の候補は除外し、類似度の高い順に返す
+fn rank_events_by_title<'a>(
+    events: &'a [google_calendar3::api::Event],
+    query: &str,
+    threshold: f64,
+) -> Vec<(f64, &'a google_calendar3::api::Event)> {
+    let mut candidates: Vec<(f64, &google_calendar3::api::Event)> = events
+        .iter()
+        .filter_map(|event| {
+            let summary = event.summary.as_deref()?;
+            let score = title_similarity(query, summary);
+            (score >= threshold).then_some((score, event))
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    candidates
+}
+
+/// 検索語とタイトルの類似度（0.0〜1.0）を計算する
+///
+/// 完全一致は1.0、部分一致は0.9、それ以外は編集距離から算出する
+fn title_similarity(query: &str, title: &str) -> f64 {
+    let query = query.trim().to_lowercase();
+    let title = title.trim().to_lowercase();
+
+    if query.is_empty() || title.is_empty() {
+        return 0.0;
+    }
+    if query == title {
+        return 1.0;
+    }
+    if title.contains(&query) || query.contains(&title) {
+        return 0.9;
+    }
+
+    let max_len = query.chars().count().max(title.chars().count());
+    let distance = levenshtein_distance(&query, &title);
+    1.0 - distance as f64 / max_len as f64
+}
+
+/// 文字単位のレーベンシュタイン距離
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
 #[derive(Debug)]
 pub struct ScheduleStatistics {
     pub total_events: usize,
@@ -659,4 +1888,735 @@ pub struct ScheduleStatistics {
     pub medium_priority: usize,
     pub high_priority: usize,
     pub urgent_priority: usize,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use google_calendar3::api::{Event, FreeBusyResponse};
+    use schedule_ai_agent::clock::{FixedClock, SystemClock};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 呼び出し回数を数え、決まった応答を返すLLM（応答がなければエラーを返す）
+    #[derive(Default)]
+    struct CountingLLM {
+        calls: AtomicUsize,
+        response: Option<LLMResponse>,
+    }
+
+    impl CountingLLM {
+        fn replying(response: LLMResponse) -> Self {
+            Self {
+                response: Some(response),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LLM for CountingLLM {
+        async fn process_request(&self, _request: LLMRequest) -> Result<LLMResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.response
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("呼ばれるべきではありません"))
+        }
+
+        async fn test_connection(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 一時ディレクトリに保存し、カレンダーに連携していないテスト用のスケジューラー
+    ///
+    /// 一時ディレクトリはスケジューラーと一緒に破棄される。
+    struct TestScheduler {
+        scheduler: Scheduler,
+        _data_dir: TempDir,
+    }
+
+    impl TestScheduler {
+        fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+            self.scheduler = self.scheduler.with_clock(clock);
+            self
+        }
+    }
+
+    impl std::ops::Deref for TestScheduler {
+        type Target = Scheduler;
+
+        fn deref(&self) -> &Scheduler {
+            &self.scheduler
+        }
+    }
+
+    impl std::ops::DerefMut for TestScheduler {
+        fn deref_mut(&mut self) -> &mut Scheduler {
+            &mut self.scheduler
+        }
+    }
+
+    fn test_scheduler(llm: Arc<dyn LLM>, config: Config) -> Result<TestScheduler> {
+        let data_dir = TempDir::new();
+        let scheduler = Scheduler {
+            conversation_history: ConversationHistory::new(),
+            llm,
+            storage: Storage::with_data_dir(data_dir.to_path_buf())?,
+            calendar_client: None,
+            config,
+            clock: Arc::new(SystemClock),
+            pending_past_event: None,
+            pending_busy_attendees: None,
+            pending_deletion: None,
+            pending_follow_ups: Vec::new(),
+            response_cache: ResponseCache::new(0),
+            calendar_ids: HashMap::new(),
+            persist_history: true,
+            cancel_requested: Arc::new(AtomicBool::new(false)),
+        };
+        Ok(TestScheduler {
+            scheduler,
+            _data_dir: data_dir,
+        })
+    }
+
+    fn event_with_summary(summary: &str) -> Event {
+        Event {
+            summary: Some(summary.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// 受け取ったリクエストを記録し、固定の要約を返すLLM
+    #[derive(Default)]
+    struct RecordingLLM {
+        requests: std::sync::Mutex<Vec<LLMRequest>>,
+    }
+
+    #[async_trait::async_trait]
+    impl LLM for RecordingLLM {
+        async fn process_request(&self, request: LLMRequest) -> Result<LLMResponse> {
+            self.requests.lock().unwrap().push(request);
+            Ok(LLMResponse {
+                action: ActionType::GeneralResponse,
+                event_data: None,
+                response_text: "・週前半は会議が中心です".to_string(),
+                missing_data: None,
+                updated_conversation: None,
+                start_time: None,
+                end_time: None,
+                follow_up_actions: Vec::new(),
+            })
+        }
+
+        async fn test_connection(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 固定の予定を返すカレンダー
+    struct FixedCalendar(Vec<Event>);
+
+    #[async_trait::async_trait]
+    impl CalendarBackend for FixedCalendar {
+        async fn create_primary_event(&self, event: Event) -> Result<Event> {
+            Ok(event)
+        }
+
+        async fn list_primary_events(&self, _time_min: DateTime<Utc>, _time_max: DateTime<Utc>) -> Result<Vec<Event>> {
+            Ok(self.0.clone())
+        }
+
+        async fn get_primary_event(&self, event_id: &str) -> Result<Event> {
+            self.0
+                .iter()
+                .find(|event| event.id.as_deref() == Some(event_id))
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("not found"))
+        }
+
+        async fn patch_primary_event(&self, _event_id: &str, event: Event) -> Result<Event> {
+            Ok(event)
+        }
+
+        async fn delete_primary_event(&self, _event_id: &str, _send_updates: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn free_busy(&self, _calendar_ids: &[String], _time_min: DateTime<Utc>, _time_max: DateTime<Utc>) -> Result<FreeBusyResponse> {
+            Ok(FreeBusyResponse::default())
+        }
+
+        async fn account_email(&self) -> Result<String> {
+            Ok("me@example.com".to_string())
+        }
+
+        async fn list_calendars(&self) -> Result<Vec<google_calendar3::api::CalendarListEntry>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summarize_passes_events_to_second_prompt() -> Result<()> {
+        let llm = Arc::new(RecordingLLM::default());
+        let scheduler = test_scheduler(llm.clone(), Config::default())?;
+        let calendar = FixedCalendar(vec![
+            event_with_summary("週次定例"),
+            event_with_summary("設計レビュー"),
+        ]);
+
+        let now = Utc::now();
+        let summary = scheduler
+            .summarize_events(&calendar, now, now + chrono::Duration::days(7))
+            .await?;
+
+        assert_eq!(summary, "・週前半は会議が中心です");
+        let requests = llm.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let context = requests[0].context.as_deref().unwrap();
+        assert!(context.contains("週次定例"));
+        assert!(context.contains("設計レビュー"));
+        assert!(requests[0].user_input.contains("3つの箇条書き"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_response_shows_action_times_and_title() -> Result<()> {
+        use chrono::TimeZone;
+
+        let scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?;
+        let response = LLMResponse {
+            action: ActionType::CreateEvent,
+            event_data: Some(EventData {
+                title: Some("会議".to_string()),
+                start_time: Some("2025-07-01T15:00:00+09:00".to_string()),
+                end_time: Some("2025-07-01 16:00".to_string()),
+                ..Default::default()
+            }),
+            response_text: String::new(),
+            missing_data: None,
+            updated_conversation: None,
+            start_time: None,
+            end_time: None,
+            follow_up_actions: Vec::new(),
+        };
+
+        assert_eq!(
+            scheduler.explain_response(&response),
+            "🔎 解釈: 予定作成 / 2025-07-01 15:00-16:00 / 会議"
+        );
+
+        let list = LLMResponse {
+            action: ActionType::ListEvents,
+            event_data: None,
+            start_time: Some(Utc.with_ymd_and_hms(2025, 7, 1, 0, 0, 0).unwrap()),
+            end_time: Some(Utc.with_ymd_and_hms(2025, 7, 2, 0, 0, 0).unwrap()),
+            ..response
+        };
+        assert_eq!(
+            scheduler.explain_response(&list),
+            "🔎 解釈: 予定一覧 / 2025-07-01 09:00-2025-07-02 09:00"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_whitespace_input_skips_llm() -> Result<()> {
+        let llm = Arc::new(CountingLLM::default());
+        let mut scheduler = test_scheduler(llm.clone(), Config::default())?;
+
+        let response = scheduler.process_user_input(" \t　 ".to_string()).await?;
+
+        assert_eq!(response, "入力が空です");
+        assert_eq!(llm.calls.load(Ordering::SeqCst), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identical_input_is_served_from_cache() -> Result<()> {
+        let llm = Arc::new(RecordingLLM::default());
+        let mut scheduler = test_scheduler(llm.clone(), Config::default())?;
+        scheduler.response_cache = ResponseCache::new(8);
+
+        let first = scheduler.process_user_input("今週の予定の傾向は？".to_string()).await?;
+        let second = scheduler.process_user_input("  今週の予定の傾向は？ ".to_string()).await?;
+
+        assert_eq!(first, second);
+        assert_eq!(llm.requests.lock().unwrap().len(), 1);
+
+        scheduler.process_user_input("来週の予定の傾向は？".to_string()).await?;
+        assert_eq!(llm.requests.lock().unwrap().len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_follow_up_actions_run_in_order() -> Result<()> {
+        use chrono::TimeZone;
+
+        let general = |text: &str| LLMResponse {
+            action: ActionType::GeneralResponse,
+            event_data: None,
+            response_text: text.to_string(),
+            missing_data: None,
+            updated_conversation: None,
+            start_time: None,
+            end_time: None,
+            follow_up_actions: Vec::new(),
+        };
+        let past_event = LLMResponse {
+            action: ActionType::CreateEvent,
+            event_data: Some(EventData {
+                title: Some("定例".to_string()),
+                start_time: Some("2024-07-01T10:00:00+09:00".to_string()),
+                end_time: Some("2024-07-01T11:00:00+09:00".to_string()),
+                ..Default::default()
+            }),
+            ..general("予定を作成します")
+        };
+        let response = LLMResponse {
+            follow_up_actions: vec![general("続けて確認します"), past_event, general("実行されないはず")],
+            ..general("承知しました")
+        };
+
+        let mut scheduler = test_scheduler(Arc::new(CountingLLM::replying(response)), Config::default())?
+            .with_clock(Arc::new(FixedClock(Utc.with_ymd_and_hms(2025, 7, 1, 3, 0, 0).unwrap())));
+
+        let result = scheduler.process_user_input("返事して、定例も作って".to_string()).await?;
+
+        let parts: Vec<&str> = result.split("\n\n").collect();
+        assert_eq!(parts[0], "承知しました");
+        assert_eq!(parts[1], "続けて確認します");
+        // 確認が必要なアクションでは応答文ではなく確認の文面を示し、残りは確認の後まで保留する
+        assert!(parts[2].contains("過去の日時"));
+        assert!(parts[2].ends_with("（確認の後に、続く1件の操作を実行します）"));
+        assert_eq!(parts.len(), 3);
+        assert!(scheduler.pending_past_event.is_some());
+        assert_eq!(scheduler.pending_follow_ups.len(), 1);
+        let history = scheduler.storage.load_conversation_history()?;
+        assert!(history.messages.iter().any(|message| message.content == "続けて確認します"));
+
+        // 作成を取りやめると、保留していた操作も実行しなかったことを伝える
+        let cancelled = scheduler.process_user_input("いいえ".to_string()).await?;
+        assert_eq!(cancelled, "予定の作成を取りやめました。\n（続く1件の操作は実行していません）");
+        assert!(scheduler.pending_follow_ups.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_before_reply_changes_nothing() -> Result<()> {
+        let data_dir = TempDir::new();
+        let llm = Arc::new(RecordingLLM::default());
+        let mut scheduler = Scheduler::from_parts(
+            llm.clone(),
+            Storage::with_data_dir(data_dir.to_path_buf())?,
+            Config::default(),
+            true,
+        )?;
+
+        // 応答を受け取る前に取りやめれば、会話履歴にも応答のキャッシュにも残さない
+        scheduler.cancel_handle().store(true, Ordering::SeqCst);
+        let error = scheduler.process_user_input("今週の予定をまとめて".to_string()).await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<SchedulerError>(), Some(SchedulerError::Cancelled)));
+        assert!(scheduler.conversation_history.messages.is_empty());
+
+        // 取りやめを求められていなければ、同じ入力をあらためてLLMに問い合わせる
+        scheduler.cancel_handle().store(false, Ordering::SeqCst);
+        assert_eq!(
+            scheduler.process_user_input("今週の予定をまとめて".to_string()).await?,
+            "・週前半は会議が中心です"
+        );
+        assert_eq!(llm.requests.lock().unwrap().len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_start_empty_ignores_saved_history() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let mut saved = ConversationHistory::new();
+        saved.add_user_message("先週の会話".to_string(), None);
+        storage.save_conversation_history(&saved)?;
+
+        let mut scheduler = Scheduler::from_parts(
+            Arc::new(RecordingLLM::default()),
+            Storage::with_data_dir(data_dir.to_path_buf())?,
+            Config::default(),
+            false,
+        )?;
+        assert!(scheduler.conversation_history().messages.is_empty());
+
+        // 話しかけても終了しても、保存済みの履歴は上書きしない（2回目はキャッシュから応答し、履歴に残す）
+        scheduler.process_user_input("今週の予定の傾向は？".to_string()).await?;
+        scheduler.process_user_input("今週の予定の傾向は？".to_string()).await?;
+        assert_eq!(scheduler.conversation_history().messages.len(), 2);
+        scheduler.flush_conversation_history()?;
+        assert_eq!(storage.load_conversation_history()?.messages[0].content, "先週の会話");
+
+        // 履歴を消去した後は、新しい履歴を保存する
+        scheduler.clear_conversation_history()?;
+        assert!(!data_dir.join("conversation_history.json").exists());
+        scheduler.process_user_input("今週の予定の傾向は？".to_string()).await?;
+        assert_eq!(storage.load_conversation_history()?.messages.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_usage_metrics_append_one_line_per_request() -> Result<()> {
+        let mut config = Config::default();
+        config.app.usage_metrics = Some(true);
+        let mut scheduler = test_scheduler(Arc::new(RecordingLLM::default()), config)?;
+
+        scheduler.process_user_input("社外秘の打ち合わせについて".to_string()).await?;
+        scheduler.process_user_input("今週の予定の傾向は？".to_string()).await?;
+        scheduler.process_user_input("   ".to_string()).await?;
+
+        let log = std::fs::read_to_string(scheduler.storage.get_data_directory_path().join("usage_metrics.jsonl"))?;
+        assert_eq!(log.lines().count(), 2);
+        assert!(!log.contains("社外秘"));
+        let records = scheduler.storage.load_usage_records()?;
+        assert!(records.iter().all(|record| record.success && record.action == Some(ActionType::GeneralResponse)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_past_start_is_held_for_confirmation() -> Result<()> {
+        use chrono::TimeZone;
+
+        let now = Utc.with_ymd_and_hms(2025, 7, 1, 3, 0, 0).unwrap();
+        let mut scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?
+            .with_clock(Arc::new(FixedClock(now)));
+        let event_data = |start: &str| EventData {
+            title: Some("定例".to_string()),
+            start_time: Some(start.to_string()),
+            ..Default::default()
+        };
+
+        let warning = scheduler.hold_past_event(&event_data("2024-07-01T10:00:00+09:00")).unwrap();
+        assert!(warning.contains("過去の日時"));
+        assert!(scheduler.pending_past_event.is_some());
+
+        scheduler.pending_past_event = None;
+        assert!(scheduler.hold_past_event(&event_data("2025-07-02T10:00:00+09:00")).is_none());
+        assert!(scheduler.pending_past_event.is_none());
+
+        assert_eq!(confirmation_answer("はい"), Some(true));
+        assert_eq!(confirmation_answer(" いいえ。"), Some(false));
+        assert_eq!(confirmation_answer("明日の予定は？"), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deleting_urgent_event_requires_confirmation() -> Result<()> {
+        let mut scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?;
+        let colored = |summary: &str, color_id: &str| Event {
+            id: Some(summary.to_string()),
+            summary: Some(summary.to_string()),
+            color_id: Some(color_id.to_string()),
+            ..Default::default()
+        };
+
+        let confirmation = scheduler.hold_deletion(&colored("役員会議", "11")).unwrap();
+        assert!(confirmation.contains("優先度: 緊急"));
+        assert_eq!(
+            scheduler.pending_deletion.as_ref().and_then(|event| event.id.as_deref()),
+            Some("役員会議")
+        );
+
+        // 優先度の低い予定は確認せずに削除する
+        scheduler.pending_deletion = None;
+        assert!(scheduler.hold_deletion(&colored("雑談", "8")).is_none());
+        assert!(scheduler.pending_deletion.is_none());
+
+        // 確認を取りやめると削除しない
+        scheduler.pending_deletion = Some(colored("役員会議", "11"));
+        assert_eq!(scheduler.process_user_input("いいえ".to_string()).await?, "予定の削除を取りやめました。");
+        assert!(scheduler.pending_deletion.is_none());
+
+        // 設定ですべての予定を確認対象にできる
+        scheduler.config.calendar.confirm_delete = Some(true);
+        assert!(scheduler.hold_deletion(&colored("雑談", "8")).is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_create_offers_alternatives() -> Result<()> {
+        use chrono::TimeZone;
+
+        let at = |hour, minute| Tokyo.with_ymd_and_hms(2025, 7, 1, hour, minute, 0).unwrap().with_timezone(&Utc);
+        let scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?
+            .with_clock(Arc::new(FixedClock(at(8, 0))));
+        let mut meeting = event_with_summary("週次定例");
+        meeting.start = Some(google_calendar3::api::EventDateTime { date_time: Some(at(10, 0)), ..Default::default() });
+        meeting.end = Some(google_calendar3::api::EventDateTime { date_time: Some(at(11, 0)), ..Default::default() });
+        let calendar = FixedCalendar(vec![meeting]);
+
+        let suggestion = scheduler
+            .suggest_alternative_slots(&calendar, "打ち合わせ", at(10, 30), at(11, 30))
+            .await?
+            .unwrap();
+        assert!(suggestion.contains("「週次定例」と時間が重なる"));
+        assert!(suggestion.contains("・07/01 11:00〜12:00（60分）"));
+        assert!(suggestion.contains("・07/01 09:00〜10:00（60分）"));
+
+        let free = scheduler
+            .suggest_alternative_slots(&calendar, "打ち合わせ", at(13, 0), at(14, 0))
+            .await?;
+        assert!(free.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_with_unavailable_attendees_asks_first() -> Result<()> {
+        use chrono::TimeZone;
+
+        let at = |hour, minute| Tokyo.with_ymd_and_hms(2025, 7, 1, hour, minute, 0).unwrap().with_timezone(&Utc);
+        let mut scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?
+            .with_clock(Arc::new(FixedClock(at(8, 0))));
+        let mut event_data = EventData {
+            title: Some("設計レビュー".to_string()),
+            start_time: Some("2025-07-01T10:00:00+09:00".to_string()),
+            end_time: Some("2025-07-01T11:00:00+09:00".to_string()),
+            attendees: vec!["sato@example.com".to_string()],
+            ..Default::default()
+        };
+
+        // 空き状況を確認できない参加者は、作成する前に知らせる
+        let warning = scheduler
+            .busy_attendee_warning(&FixedCalendar(Vec::new()), &event_data)
+            .await
+            .unwrap();
+        assert!(warning.contains("・sato@example.com: 不明"));
+        assert!(warning.contains("「はい」"));
+
+        // 作成する予定には参加者を含める
+        let event = scheduler.build_calendar_event(&event_data, "設計レビュー", at(10, 0), at(11, 0));
+        let emails: Vec<_> = event.attendees.unwrap_or_default().into_iter().filter_map(|attendee| attendee.email).collect();
+        assert_eq!(emails, ["sato@example.com"]);
+
+        // 確認待ちの間に「いいえ」と答えれば作成しない
+        scheduler.pending_busy_attendees = Some(event_data.clone());
+        assert!(scheduler.awaiting_confirmation());
+        assert_eq!(scheduler.process_user_input("いいえ".to_string()).await?, "予定の作成を取りやめました。");
+        assert!(scheduler.pending_busy_attendees.is_none());
+
+        // 参加者がいなければ確認しない
+        event_data.attendees.clear();
+        assert!(scheduler.busy_attendee_warning(&FixedCalendar(Vec::new()), &event_data).await.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_priority_applies_when_unspecified() -> Result<()> {
+        use chrono::TimeZone;
+
+        let mut config = Config::default();
+        config.app.default_priority = Some("urgent".to_string());
+        let scheduler = test_scheduler(Arc::new(CountingLLM::default()), config)?;
+        let mut event_data = EventData {
+            title: Some("定例".to_string()),
+            ..Default::default()
+        };
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let end = start + chrono::Duration::hours(1);
+
+        let event = scheduler.build_calendar_event(&event_data, "定例", start, end);
+        assert_eq!(event.color_id.as_deref(), priority_color_id(&Priority::Urgent));
+
+        // 指定された優先度は既定値より優先する
+        event_data.priority = Some(Priority::Low);
+        let event = scheduler.build_calendar_event(&event_data, "定例", start, end);
+        assert_eq!(event.color_id.as_deref(), priority_color_id(&Priority::Low));
+
+        let mut config = Config::default();
+        config.app.default_priority = Some("someday".to_string());
+        assert!(config.validate().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_reminders_override_defaults() -> Result<()> {
+        use chrono::TimeZone;
+
+        let scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?;
+        let mut event_data = EventData {
+            title: Some("定例".to_string()),
+            ..Default::default()
+        };
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let end = start + chrono::Duration::hours(1);
+
+        // 通知の指定がなければ、作成時にクライアントが calendar.default_reminders を付ける
+        let event = scheduler.build_calendar_event(&event_data, "定例", start, end);
+        assert!(event.reminders.is_none());
+
+        // 予定ごとに指定された通知は既定の通知の代わりに使う
+        event_data.reminders = Some(vec![5]);
+        let event = scheduler.build_calendar_event(&event_data, "定例", start, end);
+        let overrides = event.reminders.and_then(|reminders| reminders.overrides).unwrap_or_default();
+        assert_eq!(overrides.iter().filter_map(|reminder| reminder.minutes).collect::<Vec<_>>(), [5]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_toggle_debug_mode_flips_state() -> Result<()> {
+        let mut scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?;
+        let before = scheduler.is_debug_enabled();
+
+        scheduler.toggle_debug_mode();
+        assert_eq!(scheduler.is_debug_enabled(), !before);
+        assert_eq!(scheduler.config.app.debug_mode, Some(!before));
+
+        scheduler.toggle_debug_mode();
+        assert_eq!(scheduler.is_debug_enabled(), before);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_list_range_uses_injected_clock() -> Result<()> {
+        use chrono::TimeZone;
+
+        let now = Utc.with_ymd_and_hms(2025, 7, 1, 3, 0, 0).unwrap();
+        let scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?
+            .with_clock(Arc::new(FixedClock(now)));
+        let response = LLMResponse {
+            action: ActionType::ListEvents,
+            event_data: None,
+            response_text: String::new(),
+            missing_data: None,
+            updated_conversation: None,
+            start_time: None,
+            end_time: None,
+            follow_up_actions: Vec::new(),
+        };
+
+        let (start, end) = scheduler.get_query_time_range(&response);
+
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 7, 1, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2025, 7, 7, 23, 59, 59).unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_range_respects_config_and_all_upcoming() -> Result<()> {
+        use chrono::TimeZone;
+
+        let now = Utc.with_ymd_and_hms(2025, 7, 1, 3, 0, 0).unwrap();
+        let mut config = Config::default();
+        config.calendar.default_list_days = Some(30);
+        let scheduler = test_scheduler(Arc::new(CountingLLM::default()), config)?
+            .with_clock(Arc::new(FixedClock(now)));
+        let mut response = LLMResponse {
+            action: ActionType::ListEvents,
+            event_data: None,
+            response_text: String::new(),
+            missing_data: None,
+            updated_conversation: None,
+            start_time: None,
+            end_time: None,
+            follow_up_actions: Vec::new(),
+        };
+
+        let (start, end) = scheduler.get_list_time_range(&response);
+        assert_eq!(start, Utc.with_ymd_and_hms(2025, 7, 1, 0, 0, 0).unwrap());
+        assert_eq!(end, Some(Utc.with_ymd_and_hms(2025, 7, 30, 23, 59, 59).unwrap()));
+
+        response.event_data = Some(EventData {
+            all_upcoming: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(scheduler.get_list_time_range(&response), (now, None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_range_with_custom_format() {
+        use chrono::TimeZone;
+        use google_calendar3::api::EventDateTime;
+
+        let start = Tokyo.with_ymd_and_hms(2025, 7, 1, 15, 0, 0).unwrap().with_timezone(&Utc);
+        let end = Tokyo.with_ymd_and_hms(2025, 7, 1, 16, 30, 0).unwrap().with_timezone(&Utc);
+        let event = Event {
+            start: Some(EventDateTime { date_time: Some(start), ..Default::default() }),
+            end: Some(EventDateTime { date_time: Some(end), ..Default::default() }),
+            ..Default::default()
+        };
+        let mut app_config = Config::default().app;
+        app_config.date_format = Some("%d.%m.".to_string());
+        app_config.time_format = Some("%I:%M %p".to_string());
+
+        assert_eq!(format_event_time_range(&event, &app_config), "01.07. 03:00 PM-04:30 PM");
+    }
+
+    #[test]
+    fn test_fuzzy_match_misspelled_title() -> Result<()> {
+        let scheduler = test_scheduler(Arc::new(CountingLLM::default()), Config::default())?;
+        let events = vec![
+            event_with_summary("ランチ"),
+            event_with_summary("定例ミーティング"),
+            event_with_summary("歯医者"),
+        ];
+
+        let candidates = rank_events_by_title(&events, "定例ミーティグ", 0.6);
+        assert_eq!(candidates[0].1.summary.as_deref(), Some("定例ミーティング"));
+
+        let picked = scheduler.pick_event_by_title(&events, "定例ミーティグ").unwrap();
+        assert_eq!(picked.summary.as_deref(), Some("定例ミーティング"));
+        assert!(scheduler.pick_event_by_title(&events, "打ち合わせ").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_description_is_truncated_safely_and_opt_in() {
+        let mut event = event_with_summary("家族旅行");
+        event.description = Some("👨‍👩‍👧‍👦".repeat(45) + "\n持ち物：カメラ");
+        let app_config = Config::default().app;
+
+        let compact = format_event_line(&event, 1, &app_config, false);
+        let detailed = format_event_line(&event, 1, &app_config, true);
+
+        assert!(!compact.contains("💬"));
+        let description_line = detailed.lines().nth(1).unwrap();
+        assert!(description_line.ends_with('…'));
+        assert_eq!(description_line.matches("👨‍👩‍👧‍👦").count(), 40);
+        assert!(!description_line.contains("持ち物"));
+
+        let short = Event { description: Some("資料を持参".to_string()), ..event };
+        assert!(format_event_line(&short, 1, &app_config, true).contains("💬 資料を持参\n"));
+    }
+
+    #[test]
+    fn test_sync_summary_is_capped() {
+        let events: Vec<google_calendar3::api::Event> = (1..=60)
+            .map(|i| google_calendar3::api::Event {
+                summary: Some(format!("予定{}", i)),
+                ..Default::default()
+            })
+            .collect();
+
+        let summary = format_sync_summary(&events, 20);
+
+        assert!(summary.starts_with("Google Calendarから 60 件の予定を確認しました（先頭20件を表示）"));
+        assert!(summary.contains("• 予定20 "));
+        assert!(!summary.contains("• 予定21 "));
+        assert!(summary.ends_with("...他40件"));
+    }
+}
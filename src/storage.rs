@@ -1,48 +1,113 @@
-use crate::models::{Schedule, ConversationHistory};
+use crate::config::{ensure_app_directory, AppConfig};
+use crate::models::{Schedule, ScheduleDiff, ConversationHistory, UsageRecord};
 use anyhow::{anyhow, Result};
+use google_calendar3::api::Events;
+use schedule_ai_agent::output::status;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// 予定のキャッシュに保持する期間キーの上限（超えたら最も古く保存したものから捨てる）
+const EVENT_CACHE_CAPACITY: usize = 32;
+
 pub struct Storage {
     data_dir: PathBuf,
     schedule_file: PathBuf,
     conversation_file: PathBuf,
+    event_cache_file: PathBuf,
+    usage_metrics_file: PathBuf,
 }
 
 impl Storage {
     pub fn new() -> Result<Self> {
-        let data_dir = Self::get_data_directory()?;
+        Self::with_data_dir(ensure_app_directory()?)
+    }
+
+    /// 指定したディレクトリをデータ保存先として使用する
+    pub fn with_data_dir(data_dir: PathBuf) -> Result<Self> {
         let schedule_file = data_dir.join("schedule.json");
         let conversation_file = data_dir.join("conversation_history.json");
+        let event_cache_file = data_dir.join("event_cache.json");
+        let usage_metrics_file = data_dir.join("usage_metrics.jsonl");
 
         // データディレクトリが存在しない場合は作成
         if !data_dir.exists() {
             fs::create_dir_all(&data_dir)?;
-            println!("データディレクトリを作成しました: {}", data_dir.display());
+            status(&format!("データディレクトリを作成しました: {}", data_dir.display()));
         }
 
         Ok(Self {
             data_dir,
             schedule_file,
             conversation_file,
+            event_cache_file,
+            usage_metrics_file,
         })
     }
 
     pub fn save_schedule(&self, schedule: &Schedule) -> Result<()> {
         let json_data = serde_json::to_string_pretty(schedule)?;
-        println!("スケジュールを保存: {}", self.schedule_file.display());
+        status(&format!("スケジュールを保存: {}", self.schedule_file.display()));
         fs::write(&self.schedule_file, json_data)?;
         Ok(())
     }
 
+    /// スケジュールを読み込む
+    ///
+    /// ファイルが壊れている場合は`.corrupt`を付けて退避し、最新の読み込めるバックアップから
+    /// 復元する。バックアップもなければ空のスケジュールから始める。
     pub fn load_schedule(&self) -> Result<Schedule> {
         if !self.schedule_file.exists() {
             return Ok(Schedule::new());
         }
 
         let json_data = fs::read_to_string(&self.schedule_file)?;
-        let schedule: Schedule = serde_json::from_str(&json_data)?;
-        Ok(schedule)
+        let error = match serde_json::from_str(&json_data) {
+            Ok(schedule) => return Ok(schedule),
+            Err(e) => e,
+        };
+
+        let corrupt_file = set_aside_corrupt_file(&self.schedule_file)?;
+        status(&format!(
+            "⚠️ スケジュールを読み込めませんでした（{}）。元のファイルは {} に退避しました",
+            error,
+            corrupt_file.display()
+        ));
+        if let Some((backup, schedule)) = self.latest_readable_backup()? {
+            fs::copy(&backup, &self.schedule_file)?;
+            status(&format!("バックアップから復元しました: {}", backup.display()));
+            return Ok(schedule);
+        }
+        status("利用できるバックアップがないため、空のスケジュールで開始します");
+        Ok(Schedule::new())
+    }
+
+    /// `load_schedule`と同じ内容を、退避や復元などのファイル操作をせずに読み込む
+    pub fn peek_schedule(&self) -> Result<Schedule> {
+        if !self.schedule_file.exists() {
+            return Ok(Schedule::new());
+        }
+
+        let json_data = fs::read_to_string(&self.schedule_file)?;
+        if let Ok(schedule) = serde_json::from_str(&json_data) {
+            return Ok(schedule);
+        }
+        Ok(self
+            .latest_readable_backup()?
+            .map(|(_, schedule)| schedule)
+            .unwrap_or_else(Schedule::new))
+    }
+
+    /// 読み込める最新のバックアップとその内容を返す
+    fn latest_readable_backup(&self) -> Result<Option<(PathBuf, Schedule)>> {
+        for backup in self.list_backups()? {
+            let restored = fs::read_to_string(&backup)
+                .ok()
+                .and_then(|data| serde_json::from_str::<Schedule>(&data).ok());
+            if let Some(schedule) = restored {
+                return Ok(Some((backup, schedule)));
+            }
+        }
+        Ok(None)
     }
 
     pub fn backup_schedule(&self) -> Result<PathBuf> {
@@ -57,18 +122,46 @@ impl Storage {
         Ok(backup_file)
     }
 
-    pub fn restore_schedule(&self, backup_file: &Path) -> Result<()> {
+    /// `auto_backup`が有効な場合、変更前のスケジュールをバックアップする
+    ///
+    /// バックアップ後は`backup_count`を超えた古いバックアップを削除する。
+    /// スケジュールファイルがまだ存在しない場合は何もしない。
+    pub fn backup_before_mutation(&self, app_config: &AppConfig) -> Result<Option<PathBuf>> {
+        if !app_config.auto_backup.unwrap_or(false) || !self.schedule_file.exists() {
+            return Ok(None);
+        }
+
+        let backup_file = self.backup_schedule()?;
+        self.prune_backups(app_config.backup_count.unwrap_or(5))?;
+        Ok(Some(backup_file))
+    }
+
+    /// 新しい順に`keep`件を残して古いバックアップを削除する
+    pub fn prune_backups(&self, keep: usize) -> Result<()> {
+        for old_backup in self.list_backups()?.into_iter().skip(keep.max(1)) {
+            fs::remove_file(old_backup)?;
+        }
+        Ok(())
+    }
+
+    /// バックアップからスケジュールを復元し、`auto_backup`で作成したバックアップを返す
+    ///
+    /// 選んだバックアップは先に読み込んでおくため、復元前のバックアップで古いものとして
+    /// 削除されても復元できる。
+    pub fn restore_schedule(&self, backup_file: &Path, app_config: &AppConfig) -> Result<Option<PathBuf>> {
         if !backup_file.exists() {
             return Err(anyhow!("指定されたバックアップファイルが存在しません"));
         }
+        let restored = fs::read(backup_file)?;
 
         // 現在のファイルをバックアップ
-        if self.schedule_file.exists() {
+        let auto_backup = self.backup_before_mutation(app_config)?;
+        if auto_backup.is_none() && self.schedule_file.exists() {
             let _ = self.backup_schedule();
         }
 
-        fs::copy(backup_file, &self.schedule_file)?;
-        Ok(())
+        fs::write(&self.schedule_file, restored)?;
+        Ok(auto_backup)
     }
 
     pub fn export_schedule(&self, export_path: &Path) -> Result<()> {
@@ -80,6 +173,12 @@ impl Storage {
         Ok(())
     }
 
+    /// `path`のスケジュールで置き換えた場合の差分を求める（ファイルは書き換えない）
+    pub fn preview_replace(&self, path: &Path) -> Result<ScheduleDiff> {
+        let incoming = self.import_schedule(path)?;
+        Ok(self.peek_schedule()?.diff(&incoming))
+    }
+
     pub fn import_schedule(&self, import_path: &Path) -> Result<Schedule> {
         if !import_path.exists() {
             return Err(anyhow!("インポートするファイルが存在しません"));
@@ -92,29 +191,98 @@ impl Storage {
 
     pub fn save_conversation_history(&self, conversation: &ConversationHistory) -> Result<()> {
         let json_data = serde_json::to_string_pretty(conversation)?;
-        println!("会話履歴を保存: {}", self.conversation_file.display());
+        status(&format!("会話履歴を保存: {}", self.conversation_file.display()));
         fs::write(&self.conversation_file, json_data)?;
         Ok(())
     }
 
+    /// 会話履歴を読み込む
+    ///
+    /// ファイルが壊れている場合は`.corrupt`を付けて退避し、空の履歴から始める。
     pub fn load_conversation_history(&self) -> Result<ConversationHistory> {
         if !self.conversation_file.exists() {
             return Ok(ConversationHistory::new());
         }
 
         let json_data = fs::read_to_string(&self.conversation_file)?;
-        let conversation: ConversationHistory = serde_json::from_str(&json_data)?;
-        Ok(conversation)
+        match serde_json::from_str(&json_data) {
+            Ok(conversation) => Ok(conversation),
+            Err(e) => {
+                let corrupt_file = set_aside_corrupt_file(&self.conversation_file)?;
+                status(&format!(
+                    "⚠️ 会話履歴を読み込めなかったため、新しい履歴で開始します（{}）。元のファイルは {} に退避しました",
+                    e,
+                    corrupt_file.display()
+                ));
+                Ok(ConversationHistory::new())
+            }
+        }
     }
 
     pub fn clear_conversation_history(&self) -> Result<()> {
         if self.conversation_file.exists() {
             fs::remove_file(&self.conversation_file)?;
-            println!("会話履歴をクリアしました");
+            status("会話履歴をクリアしました");
+        }
+        Ok(())
+    }
+
+    /// Google Calendarから取得した予定を期間キーごとにキャッシュする
+    ///
+    /// `EVENT_CACHE_CAPACITY`件を超えた場合は、最も古く保存した期間から捨てる。
+    pub fn save_event_cache(&self, key: &str, events: &Events) -> Result<()> {
+        let mut cache = self.load_event_cache_file()?;
+        cache.retain(|(cached_key, _)| cached_key != key);
+        cache.push((key.to_string(), events.clone()));
+        let overflow = cache.len().saturating_sub(EVENT_CACHE_CAPACITY);
+        cache.drain(..overflow);
+        fs::write(&self.event_cache_file, serde_json::to_string_pretty(&cache)?)?;
+        Ok(())
+    }
+
+    /// キャッシュ済みの予定を取得する
+    pub fn load_event_cache(&self, key: &str) -> Result<Option<Events>> {
+        Ok(self
+            .load_event_cache_file()?
+            .into_iter()
+            .find(|(cached_key, _)| cached_key == key)
+            .map(|(_, events)| events))
+    }
+
+    /// 保存順（古い順）の期間キーと予定の一覧。読み込めないキャッシュは空として扱う
+    fn load_event_cache_file(&self) -> Result<Vec<(String, Events)>> {
+        if !self.event_cache_file.exists() {
+            return Ok(Vec::new());
         }
+
+        let json_data = fs::read_to_string(&self.event_cache_file)?;
+        Ok(serde_json::from_str(&json_data).unwrap_or_default())
+    }
+
+    /// 利用状況ログに1行追記する
+    pub fn append_usage_record(&self, record: &UsageRecord) -> Result<()> {
+        use std::io::Write;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.usage_metrics_file)?;
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
         Ok(())
     }
 
+    /// 利用状況ログを読み込む（読めない行は読み飛ばす）
+    pub fn load_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        if !self.usage_metrics_file.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(fs::read_to_string(&self.usage_metrics_file)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
     pub fn list_backups(&self) -> Result<Vec<PathBuf>> {
         let mut backups = Vec::new();
 
@@ -154,22 +322,196 @@ impl Storage {
         Ok(backups)
     }
 
-    fn get_data_directory() -> Result<PathBuf> {
-        // ホームディレクトリ内にアプリケーション専用のディレクトリを作成
-        let home_dir = dirs::home_dir()
-            .ok_or_else(|| anyhow!("ホームディレクトリが見つかりません"))?;
+    pub fn get_data_directory_path(&self) -> &Path {
+        &self.data_dir
+    }
+}
 
-        Ok(home_dir.join(".schedule_ai_agent"))
+/// 壊れたファイルを`.corrupt`を付けた名前に退避し、退避先を返す。
+/// 以前の退避ファイルを上書きしないよう、既にあれば`.corrupt.1`、`.corrupt.2`…と番号を振る
+fn set_aside_corrupt_file(path: &Path) -> Result<PathBuf> {
+    let mut base = path.as_os_str().to_owned();
+    base.push(".corrupt");
+    let mut corrupt_file = PathBuf::from(&base);
+    let mut counter = 1;
+    while corrupt_file.exists() {
+        let mut numbered = base.clone();
+        numbered.push(format!(".{}", counter));
+        corrupt_file = PathBuf::from(numbered);
+        counter += 1;
     }
+    fs::rename(path, &corrupt_file)?;
+    Ok(corrupt_file)
 }
 
-// dirsクレートの代替実装（依存関係を減らすため）
-mod dirs {
-    use std::path::PathBuf;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_auto_backup_before_import() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        storage.save_schedule(&Schedule::new())?;
+        let original = fs::read_to_string(data_dir.join("schedule.json"))?;
 
-    pub fn home_dir() -> Option<PathBuf> {
-        std::env::var_os("HOME")
-            .or_else(|| std::env::var_os("USERPROFILE"))
-            .map(PathBuf::from)
+        let import_file = data_dir.join("import.json");
+        fs::write(&import_file, serde_json::to_string(&Schedule::new())?)?;
+
+        let mut config = Config::default();
+        config.app.auto_backup = Some(true);
+        let backup = storage.backup_before_mutation(&config.app)?;
+        let imported = storage.import_schedule(&import_file)?;
+        storage.save_schedule(&imported)?;
+
+        let backup = backup.expect("auto_backup有効時はバックアップが作成される");
+        assert_eq!(fs::read_to_string(&backup)?, original);
+        assert_eq!(storage.list_backups()?, vec![backup]);
+
+        Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_restore_oldest_backup_survives_rotation() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let oldest = data_dir.join("schedule_backup_20250101_000000.json");
+        fs::write(&oldest, serde_json::to_string(&Schedule::new())?)?;
+        fs::File::options()
+            .write(true)
+            .open(&oldest)?
+            .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_735_689_600))?;
+        let original = fs::read_to_string(&oldest)?;
+        storage.save_schedule(&Schedule::new())?;
+        fs::write(data_dir.join("schedule.json"), "{\"events\": []}")?;
+
+        // 1件だけ残す設定では、復元前のバックアップで選んだバックアップが整理される
+        let mut config = Config::default();
+        config.app.auto_backup = Some(true);
+        config.app.backup_count = Some(1);
+        let backup = storage.restore_schedule(&oldest, &config.app)?;
+
+        assert!(backup.is_some());
+        assert!(!oldest.exists());
+        assert_eq!(fs::read_to_string(data_dir.join("schedule.json"))?, original);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_dry_run_leaves_schedule_untouched() -> Result<()> {
+        use crate::models::Event;
+
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let start = chrono::Utc::now();
+        let end = start + chrono::Duration::hours(1);
+        let kept = Event::new("定例".to_string(), start, end);
+        let mut moved = Event::new("1on1".to_string(), start, end);
+        let dropped = Event::new("歯医者".to_string(), start, end);
+        let mut current = Schedule::new();
+        for event in [kept.clone(), moved.clone(), dropped] {
+            current.add_event(event);
+        }
+        storage.save_schedule(&current)?;
+        let original = fs::read_to_string(data_dir.join("schedule.json"))?;
+
+        moved.end_time = end + chrono::Duration::minutes(30);
+        let mut incoming = Schedule::new();
+        for event in [kept, moved, Event::new("採用面接".to_string(), start, end)] {
+            incoming.add_event(event);
+        }
+        let import_file = data_dir.join("import.json");
+        fs::write(&import_file, serde_json::to_string(&incoming)?)?;
+
+        let diff = storage.preview_replace(&import_file)?;
+
+        assert_eq!(fs::read_to_string(data_dir.join("schedule.json"))?, original);
+        let titles = |events: &[Event]| events.iter().map(|e| e.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&diff.added), vec!["採用面接"]);
+        assert_eq!(titles(&diff.removed), vec!["歯医者"]);
+        assert_eq!(titles(&diff.changed), vec!["1on1"]);
+
+        // 現在のファイルが壊れていても、プレビューでは退避や復元を行わない
+        fs::write(data_dir.join("schedule.json"), "not json")?;
+        let diff = storage.preview_replace(&import_file)?;
+        assert_eq!(diff.added.len(), 3);
+        assert_eq!(fs::read_to_string(data_dir.join("schedule.json"))?, "not json");
+        assert!(!data_dir.join("schedule.json.corrupt").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_cache_keeps_most_recent_periods() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let events = Events::default();
+
+        for day in 0..EVENT_CACHE_CAPACITY {
+            storage.save_event_cache(&format!("today:{}", day), &events)?;
+        }
+        // 保存し直した期間は新しいものとして扱い、上限を超えたら最も古いものを捨てる
+        storage.save_event_cache("today:0", &events)?;
+        storage.save_event_cache("week:0", &events)?;
+
+        assert!(storage.load_event_cache("today:0")?.is_some());
+        assert!(storage.load_event_cache("today:1")?.is_none());
+        assert!(storage.load_event_cache("week:0")?.is_some());
+        assert_eq!(storage.load_event_cache_file()?.len(), EVENT_CACHE_CAPACITY);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_conversation_history_is_set_aside() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let truncated = r#"{"messages": [{"role": "user", "cont"#;
+        fs::write(data_dir.join("conversation_history.json"), truncated)?;
+
+        let history = storage.load_conversation_history()?;
+
+        assert!(history.messages.is_empty());
+        assert!(!data_dir.join("conversation_history.json").exists());
+        assert_eq!(fs::read_to_string(data_dir.join("conversation_history.json.corrupt"))?, truncated);
+
+        // 2回目の破損は前回の退避ファイルを上書きしない
+        fs::write(data_dir.join("conversation_history.json"), "not json")?;
+        storage.load_conversation_history()?;
+        assert_eq!(fs::read_to_string(data_dir.join("conversation_history.json.corrupt"))?, truncated);
+        assert_eq!(fs::read_to_string(data_dir.join("conversation_history.json.corrupt.1"))?, "not json");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_schedule_falls_back_to_backup_or_empty() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = Storage::with_data_dir(data_dir.to_path_buf())?;
+        let schedule_file = data_dir.join("schedule.json");
+
+        // バックアップがなければ空のスケジュールになる
+        fs::write(&schedule_file, "{\"events\": [")?;
+        assert!(storage.load_schedule()?.events.is_empty());
+        assert!(data_dir.join("schedule.json.corrupt").exists());
+        assert!(!schedule_file.exists());
+
+        // 読み込めるバックアップがあればそこから復元する
+        let mut schedule = Schedule::new();
+        schedule.add_event(crate::models::Event::new(
+            "定例".to_string(),
+            chrono::Utc::now(),
+            chrono::Utc::now() + chrono::Duration::hours(1),
+        ));
+        storage.save_schedule(&schedule)?;
+        storage.backup_schedule()?;
+        fs::write(&schedule_file, "not json")?;
+        assert_eq!(storage.load_schedule()?.events.len(), 1);
+        assert_eq!(storage.load_schedule()?.events.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+//! テストで使う補助機能
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// テスト用の一時ディレクトリ（破棄されると中身ごと削除する）
+///
+/// アサーションが失敗してテストが途中で終わっても、ディレクトリは残らない。
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("schedule_ai_agent_test_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).expect("一時ディレクトリを作成できません");
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
@@ -1,3 +1,8 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::io::{stdout, Stdout};
 use anyhow::Result;
 use crossterm::{
@@ -7,6 +12,7 @@ use crossterm::{
 };
 use ratatui::{
     backend::CrosstermBackend,
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
@@ -15,6 +21,7 @@ use ratatui::{
 };
 use unicode_segmentation::UnicodeSegmentation;
 use ratatui::backend::Backend;
+use schedule_ai_agent::output::status;
 
 use crate::scheduler::Scheduler;
 
@@ -35,6 +42,61 @@ pub struct ChatApp {
     show_help: bool,
     /// メッセージリストのスクロール状態
     scroll_state: ratatui::widgets::ListState,
+    /// ステータスバーに表示するカレンダーの状態
+    calendar_status: CalendarStatus,
+    /// ターミナルをrawモード・代替画面に切り替えているかどうか
+    terminal_active: bool,
+    /// 処理中に押され、処理の後で扱うキー入力
+    pending_events: VecDeque<Event>,
+    /// 折り返し済みのメッセージ（内容のハッシュと幅ごと）。再描画のたびに折り返し直さないようにする
+    wrap_cache: RefCell<HashMap<(u64, usize), String>>,
+}
+
+/// 1行として表示する最大の幅（超えた部分は「…」で省略する）
+const MAX_LINE_WIDTH: usize = 2000;
+
+/// 折り返し結果を覚えておく件数（超えたら捨てて作り直す）
+const WRAP_CACHE_CAPACITY: usize = 256;
+
+/// 1つの書記素の表示幅（絵文字やワイド文字は2）
+fn grapheme_width(g: &str) -> usize {
+    // ASCII文字は確実に幅1
+    if g.chars().all(|c| c.is_ascii()) {
+        return 1;
+    }
+
+    // 絵文字や記号の幅判定を簡素化
+    match g.chars().next() {
+        Some(c) => {
+            match c as u32 {
+                // 一般的な絵文字
+                0x1F600..=0x1F64F | // Emoticons
+                0x1F300..=0x1F5FF | // Misc Symbols and Pictographs
+                0x1F680..=0x1F6FF | // Transport and Map
+                0x1F1E6..=0x1F1FF | // Regional indicators
+                0x2600..=0x26FF   | // Misc symbols
+                0x2700..=0x27BF   | // Dingbats
+                0x1F900..=0x1F9FF   // Supplemental Symbols and Pictographs
+                => 2,
+                // 日本語文字（ひらがな、カタカナ、漢字）
+                0x3040..=0x309F | // ひらがな
+                0x30A0..=0x30FF | // カタカナ
+                0x4E00..=0x9FAF   // CJK統合漢字
+                => 2,
+                // その他は幅1
+                _ => 1,
+            }
+        }
+        None => 0,
+    }
+}
+
+/// ステータスバーに表示するGoogle Calendarの連携状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CalendarStatus {
+    Disconnected,
+    /// 連携中（今日の残り件数を取得できなかった場合はNone）
+    Connected { remaining_today: Option<usize> },
 }
 
 #[derive(Clone)]
@@ -42,6 +104,8 @@ pub struct ChatMessage {
     pub role: MessageRole,
     pub content: String,
     pub timestamp: chrono::DateTime<chrono::Local>,
+    /// 同じ内容の応答が続いた回数（2以上なら「(x2)」のように表示する）
+    pub repeat_count: usize,
 }
 
 #[derive(Clone, PartialEq)]
@@ -89,41 +153,55 @@ impl ChatApp {
         }
     }
 
+    /// カーソル直前の単語を削除（Ctrl+W）
+    fn delete_word_before_cursor(&mut self) {
+        self.cursor_position = delete_word_before(&mut self.input, self.cursor_position);
+    }
+
+    /// 入力行をすべて削除（Ctrl+U）
+    fn clear_input(&mut self) {
+        self.input.clear();
+        self.cursor_position = 0;
+    }
+
     /// 文字列の表示幅を計算（絵文字やワイド文字を考慮）
     fn calculate_display_width(&self, text: &str) -> usize {
-        text.graphemes(true)
-            .map(|g| {
-                // ASCII文字は確実に幅1
-                if g.chars().all(|c| c.is_ascii()) {
-                    return 1;
-                }
-                
-                // 絵文字や記号の幅判定を簡素化
-                match g.chars().next() {
-                    Some(c) => {
-                        match c as u32 {
-                            // 一般的な絵文字
-                            0x1F600..=0x1F64F | // Emoticons
-                            0x1F300..=0x1F5FF | // Misc Symbols and Pictographs
-                            0x1F680..=0x1F6FF | // Transport and Map
-                            0x1F1E6..=0x1F1FF | // Regional indicators
-                            0x2600..=0x26FF   | // Misc symbols
-                            0x2700..=0x27BF   | // Dingbats
-                            0x1F900..=0x1F9FF   // Supplemental Symbols and Pictographs
-                            => 2,
-                            // 日本語文字（ひらがな、カタカナ、漢字）
-                            0x3040..=0x309F | // ひらがな
-                            0x30A0..=0x30FF | // カタカナ
-                            0x4E00..=0x9FAF   // CJK統合漢字
-                            => 2,
-                            // その他は幅1
-                            _ => 1,
-                        }
-                    }
-                    None => 0,
-                }
-            })
-            .sum()
+        text.graphemes(true).map(grapheme_width).sum()
+    }
+
+    /// 表示幅が`MAX_LINE_WIDTH`を超える行を切り詰め、末尾に「…」を付ける
+    fn clamp_line<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        // 表示幅はバイト数を超えないため、短い行は数えずに返す
+        if line.len() <= MAX_LINE_WIDTH {
+            return Cow::Borrowed(line);
+        }
+        let mut width = 0;
+        for (index, grapheme) in line.grapheme_indices(true) {
+            width += grapheme_width(grapheme);
+            if width > MAX_LINE_WIDTH {
+                return Cow::Owned(format!("{}…", &line[..index]));
+            }
+        }
+        Cow::Borrowed(line)
+    }
+
+    /// 折り返した結果を内容と幅ごとに覚えておき、同じメッセージの再描画では計算し直さない
+    fn cached_wrap(&self, content: &str, width: usize) -> String {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        let key = (hasher.finish(), width);
+        if let Some(wrapped) = self.wrap_cache.borrow().get(&key) {
+            return wrapped.clone();
+        }
+
+        let wrapped = self.wrap_message_content(content, width);
+        let mut cache = self.wrap_cache.borrow_mut();
+        // 端末の幅が変わるなどして古い結果がたまりすぎたら捨てる
+        if cache.len() >= WRAP_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(key, wrapped.clone());
+        wrapped
     }
 
     /// メッセージ内容を指定された幅で適切に折り返す
@@ -134,6 +212,10 @@ impl ChatApp {
         let mut wrapped_lines = Vec::new();
         
         for line in content.lines() {
+            // 空白のない極端に長い行で折り返しが遅くならないよう、先に切り詰める
+            let line = self.clamp_line(line);
+            let line = line.as_ref();
+
             // 表示幅を計算
             let line_width = self.calculate_display_width(line);
             
@@ -195,17 +277,17 @@ impl ChatApp {
         let mut current_width = 0;
         
         for grapheme in text.graphemes(true) {
-            let grapheme_width = self.calculate_display_width(grapheme);
+            let width = grapheme_width(grapheme);
             
-            if current_width + grapheme_width <= max_width {
+            if current_width + width <= max_width {
                 current_line.push_str(grapheme);
-                current_width += grapheme_width;
+                current_width += width;
             } else {
                 if !current_line.is_empty() {
                     result.push(current_line);
                 }
                 current_line = grapheme.to_string();
-                current_width = grapheme_width;
+                current_width = width;
             }
         }
         
@@ -222,10 +304,10 @@ impl ChatApp {
         let mut current_width = 0;
         
         for grapheme in line.graphemes(true) {
-            let grapheme_width = self.calculate_display_width(grapheme);
-            if current_width + grapheme_width <= max_width {
+            let width = grapheme_width(grapheme);
+            if current_width + width <= max_width {
                 result.push_str(grapheme);
-                current_width += grapheme_width;
+                current_width += width;
             } else {
                 break;
             }
@@ -242,6 +324,7 @@ impl ChatApp {
             role: MessageRole::System,
             content: "スケジュールAIアシスタントへようこそ!\n\n以下のことができます:\n• 予定の追加・変更・削除\n• 空き時間の確認\n• スケジュールの最適化\n• 自然言語での予定管理\n\n入力して Enter を押すか、Ctrl+H でヘルプを表示してください。".to_string(),
             timestamp: chrono::Local::now(),
+            repeat_count: 1,
         });
         
         let mut scroll_state = ListState::default();
@@ -257,9 +340,73 @@ impl ChatApp {
             is_processing: false,
             show_help: false,
             scroll_state,
+            calendar_status: CalendarStatus::Disconnected,
+            terminal_active: false,
+            pending_events: VecDeque::new(),
+            wrap_cache: RefCell::new(HashMap::new()),
         }
     }
 
+    /// 次に扱うイベント（処理中に押されたキーを先に返す。なければ少し待って読み取る）
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        if let Some(event) = self.pending_events.pop_front() {
+            return Ok(Some(event));
+        }
+        if event::poll(std::time::Duration::from_millis(50))? {
+            return Ok(Some(event::read()?));
+        }
+        Ok(None)
+    }
+
+    /// 応答を待つ間に表示するメッセージ
+    fn thinking_placeholder(&self) -> String {
+        format!("🤔 {}", self.scheduler.thinking_message())
+    }
+
+    /// 会話履歴を保存する（失敗しても終了は妨げず、警告を表示するだけにする）
+    fn save_history_on_exit(&self) {
+        if let Err(e) = self.scheduler.flush_conversation_history() {
+            status(&format!("⚠️ 会話履歴の保存に失敗しました: {}", e));
+        }
+    }
+
+    /// カレンダーの連携状態と今日の残り件数を取り直す
+    async fn refresh_calendar_status(&mut self) {
+        self.calendar_status = if self.scheduler.is_calendar_connected() {
+            CalendarStatus::Connected {
+                remaining_today: self.scheduler.remaining_today_event_count().await,
+            }
+        } else {
+            CalendarStatus::Disconnected
+        };
+    }
+
+    /// 設定で有効な場合、開始時に今日の予定を表示する（未連携・取得失敗時は表示しない）
+    async fn show_startup_agenda(&mut self) {
+        if self.scheduler.show_agenda_on_start() {
+            let agenda = self.scheduler.todays_agenda().await;
+            self.push_agenda(agenda);
+        }
+    }
+
+    /// 今日の予定を1件1行でシステムメッセージとして追加する
+    fn push_agenda(&mut self, agenda: Option<Vec<String>>) {
+        let Some(agenda) = agenda else { return };
+        let mut content = "📅 今日の予定:".to_string();
+        if agenda.is_empty() {
+            content.push_str("\n  今日の予定はありません");
+        }
+        for line in agenda {
+            content.push_str(&format!("\n  {}", line));
+        }
+        self.messages.push(ChatMessage {
+            role: MessageRole::System,
+            content,
+            timestamp: chrono::Local::now(),
+            repeat_count: 1,
+        });
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         // ターミナルセットアップ
         enable_raw_mode()?;
@@ -267,10 +414,15 @@ impl ChatApp {
         execute!(stdout, EnterAlternateScreen)?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
+        self.terminal_active = true;
 
         let result = self.run_app(&mut terminal).await;
 
+        // 端末を戻す前に会話履歴を保存する（エラー終了時も同様）
+        self.save_history_on_exit();
+
         // ターミナルクリーンアップ
+        self.terminal_active = false;
         disable_raw_mode()?;
         execute!(
             terminal.backend_mut(),
@@ -281,7 +433,36 @@ impl ChatApp {
         result
     }
 
+    /// 画面全体を描画し、描画した内容を返す
+    ///
+    /// `stay_at_bottom`がtrueならスクロールの選択を外し、最新のメッセージを表示する。
+    fn draw_screen(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>, stay_at_bottom: bool) -> Result<Buffer> {
+        let completed = terminal.draw(|f| {
+            let [messages_area, input_area, status_area] = screen_layout(f.size());
+
+            let mut scroll_state = self.scroll_state.clone();
+            if stay_at_bottom {
+                scroll_state.select(None);
+            }
+            self.render_messages_with_state(f, messages_area, &mut scroll_state);
+            self.render_input(f, input_area);
+            self.render_status_bar(f, status_area);
+            self.scroll_state = scroll_state;
+
+            if self.show_help {
+                self.render_help(f);
+            }
+        })?;
+        let screen = completed.buffer.clone();
+
+        // 描画後にターミナルをフラッシュして画面更新を確実にする
+        terminal.backend_mut().flush()?;
+        Ok(screen)
+    }
+
     async fn run_app(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+        self.refresh_calendar_status().await;
+        self.show_startup_agenda().await;
         loop {
             // 描画前にスクロール状態をチェック
             let should_stay_at_bottom = self.scroll_state.selected().is_none() || 
@@ -289,235 +470,214 @@ impl ChatApp {
                     selected >= self.messages.len().saturating_sub(2)
                 });
             
-            terminal.draw(|f| {
-                let chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .margin(1)
-                    .constraints([
-                        Constraint::Min(5),     // メッセージエリア（最小5行確保）
-                        Constraint::Length(3),  // 入力エリア
-                        Constraint::Length(1),  // ステータスバー
-                    ])
-                    .split(f.size());
-
-                // スクロール状態のクローンを作成
-                let mut local_scroll_state = self.scroll_state.clone();
-                
-                // 最下部に留まるべき場合は選択をクリア
-                if should_stay_at_bottom {
-                    local_scroll_state.select(None);
-                }
-                
-                self.render_messages_with_state(f, chunks[0], &mut local_scroll_state);
-                self.render_input(f, chunks[1]);
-                self.render_status_bar(f, chunks[2]);
-                
-                // スクロール状態を更新
-                self.scroll_state = local_scroll_state;
+            self.draw_screen(terminal, should_stay_at_bottom)?;
 
-                if self.show_help {
-                    self.render_help(f);
+            if let Some(Event::Key(key)) = self.next_event()? {
+                // KeyEventKindが押下の場合のみ処理
+                if key.kind != KeyEventKind::Press {
+                    continue;
                 }
-            })?;
-            
-            // 描画後にターミナルをフラッシュして画面更新を確実にする
-            terminal.backend_mut().flush()?;
-
-            if event::poll(std::time::Duration::from_millis(50))? {
-                if let Event::Key(key) = event::read()? {
-                    // KeyEventKindが押下の場合のみ処理
-                    if key.kind != KeyEventKind::Press {
-                        continue;
-                    }
 
-                    match key.code {
-                        KeyCode::Esc => {
-                            if self.show_help {
-                                self.show_help = false;
-                            } else {
-                                self.should_quit = true;
-                            }
-                        }
-                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                match key.code {
+                    KeyCode::Esc => {
+                        if self.show_help {
+                            self.show_help = false;
+                        } else {
                             self.should_quit = true;
                         }
-                        KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            self.show_help = !self.show_help;
-                        }
-                        KeyCode::Enter => {
-                            if !self.show_help && !self.is_processing {
-                                let input_text = self.input.trim().to_string();
-                                if !input_text.is_empty() {
-                                    // デバッグコマンドかどうかをチェック
-                                    if let Some(response) = self.handle_debug_commands(&input_text) {
-                                        // デバッグコマンドの場合は即座に応答を表示
-                                        self.messages.push(ChatMessage {
-                                            role: MessageRole::User,
-                                            content: input_text.clone(),
-                                            timestamp: chrono::Local::now(),
-                                        });
-                                        self.messages.push(ChatMessage {
-                                            role: MessageRole::Assistant,
-                                            content: response,
-                                            timestamp: chrono::Local::now(),
-                                        });
-                                        self.input.clear();
-                                        self.cursor_position = 0;
-                                        self.update_scroll_to_bottom();
-                                        continue;
-                                    }
-                                    
-                                    // 先にユーザーメッセージを追加して画面に表示
+                    }
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.should_quit = true;
+                    }
+                    KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        self.show_help = !self.show_help;
+                    }
+                    KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) && !self.is_processing => {
+                        self.scheduler.toggle_debug_mode();
+                    }
+                    KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) && !self.show_help => {
+                        self.cursor_position = 0;
+                    }
+                    KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) && !self.show_help => {
+                        self.cursor_position = self.char_count();
+                    }
+                    KeyCode::Char('w')
+                        if key.modifiers.contains(KeyModifiers::CONTROL) && !self.show_help && !self.is_processing =>
+                    {
+                        self.delete_word_before_cursor();
+                    }
+                    KeyCode::Char('u')
+                        if key.modifiers.contains(KeyModifiers::CONTROL) && !self.show_help && !self.is_processing =>
+                    {
+                        self.clear_input();
+                    }
+                    KeyCode::Enter => {
+                        if !self.show_help && !self.is_processing {
+                            let input_text = self.input.trim().to_string();
+                            if !input_text.is_empty() {
+                                // デバッグ・同期コマンドかどうかをチェック
+                                let command_response = match self.handle_debug_commands(&input_text) {
+                                    Some(response) => Some(response),
+                                    None => match self.handle_model_command(&input_text) {
+                                        Some(response) => Some(response),
+                                        None => self.handle_sync_command(&input_text).await,
+                                    },
+                                };
+                                if let Some(response) = command_response {
+                                    // デバッグコマンドの場合は即座に応答を表示
                                     self.messages.push(ChatMessage {
                                         role: MessageRole::User,
                                         content: input_text.clone(),
                                         timestamp: chrono::Local::now(),
+                                        repeat_count: 1,
                                     });
-
-                                    // 入力をクリアして最下部にスクロール
-                                    self.input.clear();
-                                    self.cursor_position = 0;
-                                    self.update_scroll_to_bottom();
-                                    
-                                    // 処理中メッセージを追加
                                     self.messages.push(ChatMessage {
                                         role: MessageRole::Assistant,
-                                        content: "🤔 考え中です...".to_string(),
+                                        content: response,
                                         timestamp: chrono::Local::now(),
+                                        repeat_count: 1,
                                     });
-                                    
-                                    self.is_processing = true;
+                                    self.input.clear();
+                                    self.cursor_position = 0;
                                     self.update_scroll_to_bottom();
-                                    
-                                    // 画面を一度描画して処理中メッセージを表示
-                                    terminal.draw(|f| {
-                                        let chunks = Layout::default()
-                                            .direction(Direction::Vertical)
-                                            .margin(1)
-                                            .constraints([
-                                                Constraint::Min(5),
-                                                Constraint::Length(3),
-                                                Constraint::Length(1),
-                                            ])
-                                            .split(f.size());
-
-                                        let mut scroll_state_clone = self.scroll_state.clone();
-                                        self.render_messages_with_state(f, chunks[0], &mut scroll_state_clone);
-                                        self.render_input(f, chunks[1]);
-                                        self.render_status_bar(f, chunks[2]);
-                                        self.scroll_state = scroll_state_clone;
-
-                                        if self.show_help {
-                                            self.render_help(f);
-                                        }
-                                    })?;
-                                    terminal.backend_mut().flush()?;
-                                    
-                                    // AIの処理を実行
-                                    let processing_msg_index = self.messages.len() - 1;
-                                    if schedule_ai_agent::debug::is_debug_enabled() {
-                                        eprintln!("🔍 TUI DEBUG: AIの処理を開始します: '{}'", input_text);
-                                    }
-                                    match self.scheduler.process_user_input(input_text).await {
-                                        Ok(response) => {
-                                            if schedule_ai_agent::debug::is_debug_enabled() {
-                                                eprintln!("🔍 TUI DEBUG: AIからレスポンスを受信: '{}'", response);
-                                            }
-                                            let cleaned_response = self.clean_response(&response);
-                                            if schedule_ai_agent::debug::is_debug_enabled() {
-                                                eprintln!("🔍 TUI DEBUG: クリーンアップ後のレスポンス: '{}'", cleaned_response);
-                                            }
-                                            if let Some(msg) = self.messages.get_mut(processing_msg_index) {
-                                                msg.content = if cleaned_response.is_empty() {
-                                                    "✅ 処理が完了しました。".to_string()
-                                                } else {
-                                                    cleaned_response
-                                                };
-                                                msg.timestamp = chrono::Local::now();
-                                                if schedule_ai_agent::debug::is_debug_enabled() {
-                                                    eprintln!("🔍 TUI DEBUG: メッセージを更新しました: '{}'", msg.content);
+                                    continue;
+                                }
+                                
+                                // 先にユーザーメッセージを追加して画面に表示
+                                self.messages.push(ChatMessage {
+                                    role: MessageRole::User,
+                                    content: input_text.clone(),
+                                    timestamp: chrono::Local::now(),
+                                    repeat_count: 1,
+                                });
+
+                                // 入力をクリアして最下部にスクロール
+                                self.input.clear();
+                                self.cursor_position = 0;
+                                self.update_scroll_to_bottom();
+                                
+                                // 処理中メッセージを追加
+                                self.messages.push(ChatMessage {
+                                    role: MessageRole::Assistant,
+                                    content: self.thinking_placeholder(),
+                                    timestamp: chrono::Local::now(),
+                                    repeat_count: 1,
+                                });
+                                
+                                self.is_processing = true;
+                                self.update_scroll_to_bottom();
+                                
+                                // 画面を一度描画して処理中メッセージを表示（待つ間はこの画面を使い回す）
+                                let screen = self.draw_screen(terminal, false)?;
+                                
+                                // AIの処理を実行
+                                let processing_msg_index = self.messages.len() - 1;
+                                if schedule_ai_agent::debug::is_debug_enabled() {
+                                    status(&format!("🔍 TUI DEBUG: AIの処理を開始します: '{}'", input_text));
+                                }
+                                // 応答を待つ間もキー入力を確認し、Esc / Ctrl+X で処理の取りやめを求める
+                                // （実際に取りやめるのは、会話履歴やカレンダーを変更する前の時点だけ）
+                                let thinking_message = self.scheduler.thinking_message();
+                                let started = std::time::Instant::now();
+                                let cancel = self.scheduler.cancel_handle();
+                                cancel.store(false, std::sync::atomic::Ordering::SeqCst);
+                                let result = {
+                                    let mut request = Box::pin(self.scheduler.process_user_input(input_text.clone()));
+                                    loop {
+                                        tokio::select! {
+                                            result = &mut request => break result,
+                                            _ = tokio::time::sleep(std::time::Duration::from_millis(50)) => {
+                                                if take_cancel_keys(&mut self.pending_events)? {
+                                                    cancel.store(true, std::sync::atomic::Ordering::SeqCst);
                                                 }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            if schedule_ai_agent::debug::is_debug_enabled() {
-                                                eprintln!("🔍 TUI DEBUG: エラーが発生: {:?}", e);
-                                            }
-                                            if let Some(msg) = self.messages.get_mut(processing_msg_index) {
-                                                msg.content = format!("❌ エラーが発生しました:\n{}\n\n💡 別の方法で試してみてください。", e);
-                                                msg.timestamp = chrono::Local::now();
+                                                let text = processing_status_text(spinner_frame(started.elapsed()), &thinking_message);
+                                                draw_processing_status(terminal, &screen, &text)?;
                                             }
                                         }
                                     }
-                                    
-                                    self.is_processing = false;
+                                };
+                                if result.as_ref().err().is_some_and(is_cancelled) {
+                                    self.cancel_processing(input_text, processing_msg_index);
                                     self.update_scroll_to_bottom();
-                                    
-                                    // AI処理完了後の画面更新を即座に反映
-                                    terminal.draw(|f| {
-                                        let chunks = Layout::default()
-                                            .direction(Direction::Vertical)
-                                            .margin(1)
-                                            .constraints([
-                                                Constraint::Min(5),
-                                                Constraint::Length(3),
-                                                Constraint::Length(1),
-                                            ])
-                                            .split(f.size());
-
-                                        let mut scroll_state_clone = self.scroll_state.clone();
-                                        self.render_messages_with_state(f, chunks[0], &mut scroll_state_clone);
-                                        self.render_input(f, chunks[1]);
-                                        self.render_status_bar(f, chunks[2]);
-                                        self.scroll_state = scroll_state_clone;
-
-                                        if self.show_help {
-                                            self.render_help(f);
+                                    continue;
+                                }
+                                match result {
+                                    Ok(response) => {
+                                        if schedule_ai_agent::debug::is_debug_enabled() {
+                                            status(&format!("🔍 TUI DEBUG: AIからレスポンスを受信: '{}'", response));
+                                        }
+                                        let cleaned_response = self.clean_response(&response);
+                                        if schedule_ai_agent::debug::is_debug_enabled() {
+                                            status(&format!("🔍 TUI DEBUG: クリーンアップ後のレスポンス: '{}'", cleaned_response));
+                                        }
+                                        let content = if cleaned_response.is_empty() {
+                                            "✅ 処理が完了しました。".to_string()
+                                        } else {
+                                            cleaned_response
+                                        };
+                                        if schedule_ai_agent::debug::is_debug_enabled() {
+                                            status(&format!("🔍 TUI DEBUG: メッセージを更新しました: '{}'", content));
+                                        }
+                                        self.complete_message(processing_msg_index, content);
+                                    }
+                                    Err(e) => {
+                                        if schedule_ai_agent::debug::is_debug_enabled() {
+                                            status(&format!("🔍 TUI DEBUG: エラーが発生: {:?}", e));
                                         }
-                                    })?;
-                                    terminal.backend_mut().flush()?;
+                                        if let Some(msg) = self.messages.get_mut(processing_msg_index) {
+                                            msg.content = format!("❌ エラーが発生しました:\n{}\n\n💡 {}", e, error_hint(&e).unwrap_or("別の方法で試してみてください。"));
+                                            msg.timestamp = chrono::Local::now();
+                                        }
+                                    }
                                 }
+                                
+                                self.refresh_calendar_status().await;
+                                self.is_processing = false;
+                                self.update_scroll_to_bottom();
+                                
+                                // AI処理完了後の画面更新を即座に反映
+                                self.draw_screen(terminal, false)?;
                             }
                         }
-                        KeyCode::Char(c) => {
-                            if !self.show_help && !self.is_processing {
-                                self.insert_char_at_cursor(c);
-                            }
+                    }
+                    KeyCode::Char(c) => {
+                        if !self.show_help && !self.is_processing {
+                            self.insert_char_at_cursor(c);
                         }
-                        KeyCode::Backspace => {
-                            if !self.show_help && !self.is_processing && self.cursor_position > 0 {
-                                self.delete_char_before_cursor();
-                            }
+                    }
+                    KeyCode::Backspace => {
+                        if !self.show_help && !self.is_processing && self.cursor_position > 0 {
+                            self.delete_char_before_cursor();
                         }
-                        KeyCode::Left => {
-                            if !self.show_help && self.cursor_position > 0 {
-                                self.cursor_position -= 1;
-                            }
+                    }
+                    KeyCode::Left => {
+                        if !self.show_help && self.cursor_position > 0 {
+                            self.cursor_position -= 1;
                         }
-                        KeyCode::Right => {
-                            if !self.show_help && self.cursor_position < self.char_count() {
-                                self.cursor_position += 1;
-                            }
+                    }
+                    KeyCode::Right => {
+                        if !self.show_help && self.cursor_position < self.char_count() {
+                            self.cursor_position += 1;
                         }
-                        KeyCode::Up => {
-                            if !self.show_help && !self.messages.is_empty() {
-                                let current = self.scroll_state.selected().unwrap_or(self.messages.len().saturating_sub(1));
-                                if current > 0 {
-                                    self.scroll_state.select(Some(current - 1));
-                                }
+                    }
+                    KeyCode::Up => {
+                        if !self.show_help && !self.messages.is_empty() {
+                            let current = self.scroll_state.selected().unwrap_or(self.messages.len().saturating_sub(1));
+                            if current > 0 {
+                                self.scroll_state.select(Some(current - 1));
                             }
                         }
-                        KeyCode::Down => {
-                            if !self.show_help && !self.messages.is_empty() {
-                                let current = self.scroll_state.selected().unwrap_or(0);
-                                let max_index = self.messages.len().saturating_sub(1);
-                                if current < max_index {
-                                    self.scroll_state.select(Some(current + 1));
-                                }
+                    }
+                    KeyCode::Down => {
+                        if !self.show_help && !self.messages.is_empty() {
+                            let current = self.scroll_state.selected().unwrap_or(0);
+                            let max_index = self.messages.len().saturating_sub(1);
+                            if current < max_index {
+                                self.scroll_state.select(Some(current + 1));
                             }
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
 
@@ -528,6 +688,39 @@ impl ChatApp {
         Ok(())
     }
 
+    /// 処理中メッセージを応答で置き換える
+    ///
+    /// 今回の入力の直前にあるAIの応答が同じ内容なら、その応答を取り除いて回数を引き継ぎ、
+    /// 最新の応答に「(x2)」のようにまとめて表示する。
+    fn complete_message(&mut self, processing_msg_index: usize, content: String) {
+        let previous_reply = processing_msg_index.checked_sub(2).filter(|&previous| {
+            self.messages[previous + 1].role == MessageRole::User
+                && self.messages[previous].role == MessageRole::Assistant
+                && self.messages[previous].content == content
+        });
+        let repeat_count = previous_reply.map_or(1, |previous| self.messages[previous].repeat_count + 1);
+        let Some(msg) = self.messages.get_mut(processing_msg_index) else {
+            return;
+        };
+        msg.content = content;
+        msg.timestamp = chrono::Local::now();
+        msg.repeat_count = repeat_count;
+        if let Some(previous) = previous_reply {
+            self.messages.remove(previous);
+        }
+    }
+
+    /// 処理中の依頼を取りやめ、送信した入力を入力欄に戻す
+    fn cancel_processing(&mut self, input: String, processing_msg_index: usize) {
+        if let Some(msg) = self.messages.get_mut(processing_msg_index) {
+            msg.content = "キャンセルしました".to_string();
+            msg.timestamp = chrono::Local::now();
+        }
+        self.cursor_position = input.chars().count();
+        self.input = input;
+        self.is_processing = false;
+    }
+
     async fn handle_user_input(&mut self, input: String) -> Result<()> {
         // AIの応答を取得するためにinputをクローン
         let input_for_processing = input.clone();
@@ -537,13 +730,15 @@ impl ChatApp {
             role: MessageRole::User,
             content: input,
             timestamp: chrono::Local::now(),
+            repeat_count: 1,
         });
 
         // 処理中メッセージを表示
         self.messages.push(ChatMessage {
             role: MessageRole::Assistant,
-            content: "🤔 考え中です...".to_string(),
+            content: self.thinking_placeholder(),
             timestamp: chrono::Local::now(),
+            repeat_count: 1,
         });
 
         // 新しいメッセージが追加されたので最下部にスクロール
@@ -560,24 +755,26 @@ impl ChatApp {
                 let cleaned_response = self.clean_response(&response);
                 
                 // 処理中メッセージを実際の応答に置き換え
-                if let Some(msg) = self.messages.get_mut(processing_msg_index) {
-                    msg.content = if cleaned_response.is_empty() {
-                        "✅ 処理が完了しました。".to_string()
-                    } else {
-                        cleaned_response
-                    };
-                    msg.timestamp = chrono::Local::now();
-                }
+                let content = if cleaned_response.is_empty() {
+                    "✅ 処理が完了しました。".to_string()
+                } else {
+                    cleaned_response
+                };
+                self.complete_message(processing_msg_index, content);
             }
             Err(e) => {
                 // エラーメッセージを表示（scheduler.rsで既にAIの応答とエラーメッセージが組み合わされている）
                 if let Some(msg) = self.messages.get_mut(processing_msg_index) {
-                    msg.content = format!("🤖 {}", e);
+                    msg.content = match error_hint(&e) {
+                        Some(hint) => format!("🤖 {}\n\n💡 {}", e, hint),
+                        None => format!("🤖 {}", e),
+                    };
                     msg.timestamp = chrono::Local::now();
                 }
             }
         }
 
+        self.refresh_calendar_status().await;
         self.is_processing = false;
         // メッセージ更新後に最下部を表示
         self.update_scroll_to_bottom();
@@ -592,8 +789,9 @@ impl ChatApp {
         // 処理中メッセージを表示
         self.messages.push(ChatMessage {
             role: MessageRole::Assistant,
-            content: "🤔 考え中です...".to_string(),
+            content: self.thinking_placeholder(),
             timestamp: chrono::Local::now(),
+            repeat_count: 1,
         });
 
         // 新しいメッセージが追加されたので最下部にスクロール
@@ -610,24 +808,26 @@ impl ChatApp {
                 let cleaned_response = self.clean_response(&response);
                 
                 // 処理中メッセージを実際の応答に置き換え
-                if let Some(msg) = self.messages.get_mut(processing_msg_index) {
-                    msg.content = if cleaned_response.is_empty() {
-                        "✅ 処理が完了しました。".to_string()
-                    } else {
-                        cleaned_response
-                    };
-                    msg.timestamp = chrono::Local::now();
-                }
+                let content = if cleaned_response.is_empty() {
+                    "✅ 処理が完了しました。".to_string()
+                } else {
+                    cleaned_response
+                };
+                self.complete_message(processing_msg_index, content);
             }
             Err(e) => {
                 // エラーメッセージを表示（scheduler.rsで既にAIの応答とエラーメッセージが組み合わされている）
                 if let Some(msg) = self.messages.get_mut(processing_msg_index) {
-                    msg.content = format!("🤖 {}", e);
+                    msg.content = match error_hint(&e) {
+                        Some(hint) => format!("🤖 {}\n\n💡 {}", e, hint),
+                        None => format!("🤖 {}", e),
+                    };
                     msg.timestamp = chrono::Local::now();
                 }
             }
         }
 
+        self.refresh_calendar_status().await;
         self.is_processing = false;
         // メッセージ更新後に最下部を表示
         self.update_scroll_to_bottom();
@@ -794,7 +994,11 @@ impl ChatApp {
                     ),
                 };
 
-                let header = format!("[{}] {}", timestamp, prefix);
+                let header = if m.repeat_count > 1 {
+                    format!("[{}] {} (x{})", timestamp, prefix, m.repeat_count)
+                } else {
+                    format!("[{}] {}", timestamp, prefix)
+                };
                 
                 // メッセージ内容の処理
                 let processed_content = match m.role {
@@ -806,7 +1010,7 @@ impl ChatApp {
                 
                 // 安全な幅でコンテンツを折り返し
                 let content_width = available_width.saturating_sub(4).max(6) as usize; // インデント分を引く、最低6文字確保
-                let wrapped_content = self.wrap_message_content(&processed_content, content_width);
+                let wrapped_content = self.cached_wrap(&processed_content, content_width);
                 
                 // テキスト構築
                 let mut lines = Vec::new();
@@ -934,12 +1138,16 @@ impl ChatApp {
     fn render_status_bar(&self, f: &mut Frame, area: Rect) {
         let (status_text, status_style) = if self.is_processing {
             (
-                "🔄 AIが考え中です... お待ちください",
+                processing_status_text(SPINNER_FRAMES[0], &self.scheduler.thinking_message()),
                 Style::default().fg(Color::Yellow).add_modifier(Modifier::SLOW_BLINK)
             )
         } else {
             (
-                "✅ 準備完了 | ↑↓: スクロール | Ctrl+H: ヘルプ | Ctrl+C/Esc: 終了 | メッセージを入力してEnterで送信",
+                format!(
+                    "✅ 準備完了 | {}{} | ↑↓: スクロール | Ctrl+H: ヘルプ | Ctrl+C/Esc: 終了 | メッセージを入力してEnterで送信",
+                    calendar_status_text(self.calendar_status),
+                    if self.scheduler.is_debug_enabled() { " | 🔍 デバッグON" } else { "" }
+                ),
                 Style::default().fg(Color::Gray)
             )
         };
@@ -968,8 +1176,13 @@ impl ChatApp {
             Line::from("  ↑/↓        - Scroll through messages"),
             Line::from("  Ctrl+H     - Toggle this help dialog"),
             Line::from("  Ctrl+C/Esc - Quit application"),
+            Line::from("  Esc/Ctrl+X - Cancel the request while the AI is thinking"),
             Line::from("  ←/→        - Move cursor in input field"),
+            Line::from("  Ctrl+A/E   - Move cursor to start/end of input"),
             Line::from("  Backspace  - Delete character"),
+            Line::from("  Ctrl+W     - Delete previous word"),
+            Line::from("  Ctrl+U     - Clear input"),
+            Line::from("  Ctrl+D     - Toggle debug mode (logs go to tui.log)"),
             Line::from(""),
             Line::from(vec![
                 Span::styled("💡 Example Commands:", Style::default().fg(Color::Green).add_modifier(Modifier::UNDERLINED))
@@ -989,6 +1202,8 @@ impl ChatApp {
             Line::from("  • '/debug toggle' - Toggle debug mode"),
             Line::from("  • '/debug status' - Show debug status"),
             Line::from("  • '/debug help' - Show debug help"),
+            Line::from("  • '/sync [--limit N]' - Sync with Google Calendar"),
+            Line::from("  • '/model [name] [--save]' - Show or switch the LLM model"),
             Line::from(""),
             Line::from(vec![
                 Span::styled("🎯 Features:", Style::default().fg(Color::Magenta).add_modifier(Modifier::UNDERLINED))
@@ -1016,6 +1231,52 @@ impl ChatApp {
         f.render_widget(help_paragraph, area);
     }
 
+    /// 「/sync [--limit N]」でGoogle Calendarと同期する
+    async fn handle_sync_command(&mut self, input: &str) -> Option<String> {
+        let mut words = input.split_whitespace();
+        if words.next() != Some("/sync") {
+            return None;
+        }
+        let limit = match (words.next(), words.next()) {
+            (None, _) => None,
+            (Some("--limit"), Some(value)) => match value.parse::<usize>() {
+                Ok(limit) => Some(limit),
+                Err(_) => return Some("❌ --limit には件数を指定してください".to_string()),
+            },
+            _ => return Some("❌ 使用法: /sync [--limit 件数]".to_string()),
+        };
+
+        let response = match self.scheduler.sync_with_google_calendar(limit).await {
+            Ok(result) => format!("🔄 {}", result),
+            Err(e) => format!("❌ 同期エラー: {}", e),
+        };
+        self.refresh_calendar_status().await;
+        Some(response)
+    }
+
+    /// 「/model [モデル名] [--save]」で使用するLLMのモデルを表示・変更する
+    fn handle_model_command(&mut self, input: &str) -> Option<String> {
+        let mut words = input.split_whitespace();
+        if words.next() != Some("/model") {
+            return None;
+        }
+        let words: Vec<&str> = words.collect();
+        let persist = words.contains(&"--save");
+
+        let response = match words.iter().find(|word| **word != "--save") {
+            None => format!(
+                "🧠 現在のモデル: {}\n既知のモデル: {}",
+                self.scheduler.llm_model().unwrap_or_else(|| "(切り替え非対応)".to_string()),
+                crate::llm::KNOWN_MODELS.join(", ")
+            ),
+            Some(model) => match self.scheduler.set_llm_model(model, persist) {
+                Ok(message) => message,
+                Err(e) => format!("❌ モデルの切り替えに失敗しました: {}", e),
+            },
+        };
+        Some(response)
+    }
+
     /// デバッグコマンドを処理する
     fn handle_debug_commands(&mut self, input: &str) -> Option<String> {
         match input {
@@ -1044,6 +1305,129 @@ impl ChatApp {
     }
 }
 
+/// 処理中の依頼を取りやめるキー（Esc / Ctrl+X）か
+fn is_cancel_key(code: KeyCode, modifiers: KeyModifiers) -> bool {
+    match code {
+        KeyCode::Esc => true,
+        KeyCode::Char('x') => modifiers.contains(KeyModifiers::CONTROL),
+        _ => false,
+    }
+}
+
+/// 処理中にステータスバーで回すスピナーの各コマ
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// スピナーのコマを進める間隔
+const SPINNER_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// 処理開始からの経過時間に応じたスピナーのコマ
+fn spinner_frame(elapsed: std::time::Duration) -> &'static str {
+    let tick = elapsed.as_millis() / SPINNER_INTERVAL.as_millis();
+    SPINNER_FRAMES[(tick % SPINNER_FRAMES.len() as u128) as usize]
+}
+
+/// 処理中のステータスバーの文言
+fn processing_status_text(frame: &str, thinking_message: &str) -> String {
+    format!("{} {} お待ちください | Esc/Ctrl+X: キャンセル", frame, thinking_message)
+}
+
+/// メッセージエリア・入力エリア・ステータスバーの配置
+fn screen_layout(area: Rect) -> [Rect; 3] {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(5),     // メッセージエリア（最小5行確保）
+            Constraint::Length(3),  // 入力エリア
+            Constraint::Length(1),  // ステータスバー
+        ])
+        .split(area);
+    [chunks[0], chunks[1], chunks[2]]
+}
+
+/// 応答を待つ間、直前に描画した画面のステータスバーだけを差し替えて描き直す
+///
+/// 処理中はスケジューラーを借用しているため画面を組み立て直せず、描画済みの`screen`を使い回す。
+/// 変わるのはステータスバーだけなので、端末に書き込まれるのもその行だけになる。
+fn draw_processing_status<B: Backend>(terminal: &mut Terminal<B>, screen: &Buffer, text: &str) -> Result<()> {
+    terminal.draw(|f| {
+        // 端末の大きさが変わっていれば、次の描画までステータスバーだけを表示する
+        if screen.area == f.size() {
+            *f.buffer_mut() = screen.clone();
+        }
+        let status_area = screen_layout(f.size())[2];
+        let status = Paragraph::new(text.to_string())
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center);
+        f.render_widget(Clear, status_area);
+        f.render_widget(status, status_area);
+    })?;
+    terminal.backend_mut().flush()?;
+    Ok(())
+}
+
+/// 処理中に押されたキーを読み取り、キャンセルが押されたか判定する
+///
+/// キャンセル以外のキーは`pending`に残し、処理が終わった後で通常どおり扱う。
+fn take_cancel_keys(pending: &mut VecDeque<Event>) -> Result<bool> {
+    let mut cancelled = false;
+    while event::poll(std::time::Duration::ZERO)? {
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press && is_cancel_key(key.code, key.modifiers) => {
+                cancelled = true;
+            }
+            other => pending.push_back(other),
+        }
+    }
+    Ok(cancelled)
+}
+
+/// 依頼の取りやめによるエラーか
+fn is_cancelled(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<crate::models::SchedulerError>(),
+        Some(crate::models::SchedulerError::Cancelled)
+    )
+}
+
+/// ステータスバーのカレンダー表示
+fn calendar_status_text(status: CalendarStatus) -> String {
+    match status {
+        CalendarStatus::Disconnected => "📅 カレンダー未接続".to_string(),
+        CalendarStatus::Connected { remaining_today: Some(count) } => format!("📅 今日の残り{}件", count),
+        CalendarStatus::Connected { remaining_today: None } => "📅 カレンダー接続中".to_string(),
+    }
+}
+
+/// エラーの種類が分かる場合はそれに合わせたヒントを返す
+fn error_hint(error: &anyhow::Error) -> Option<&'static str> {
+    error
+        .downcast_ref::<crate::models::SchedulerError>()
+        .map(|e| e.hint())
+}
+
+/// カーソル（書記素単位）直前の単語を削除し、新しいカーソル位置を返す
+///
+/// readlineのCtrl+Wと同様に、直前の空白をまたいでから空白以外を削除する。
+fn delete_word_before(input: &mut String, cursor: usize) -> usize {
+    let graphemes: Vec<&str> = input.graphemes(true).collect();
+    let cursor = cursor.min(graphemes.len());
+    let is_space = |g: &str| g.chars().all(char::is_whitespace);
+
+    let mut start = cursor;
+    while start > 0 && is_space(graphemes[start - 1]) {
+        start -= 1;
+    }
+    while start > 0 && !is_space(graphemes[start - 1]) {
+        start -= 1;
+    }
+
+    let byte_start: usize = graphemes[..start].iter().map(|g| g.len()).sum();
+    let byte_end: usize = graphemes[..cursor].iter().map(|g| g.len()).sum();
+    input.drain(byte_start..byte_end);
+    start
+}
+
 // ヘルプダイアログを中央に配置するためのヘルパー関数
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
@@ -1064,3 +1448,259 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+impl Drop for ChatApp {
+    /// パニックなどで`run`の後始末を通らなかった場合も、会話履歴を保存して端末を元に戻す
+    fn drop(&mut self) {
+        if !self.terminal_active {
+            return;
+        }
+        self.save_history_on_exit();
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_calendar_status_text() {
+        assert_eq!(calendar_status_text(CalendarStatus::Disconnected), "📅 カレンダー未接続");
+        assert_eq!(
+            calendar_status_text(CalendarStatus::Connected { remaining_today: Some(3) }),
+            "📅 今日の残り3件"
+        );
+        assert_eq!(
+            calendar_status_text(CalendarStatus::Connected { remaining_today: None }),
+            "📅 カレンダー接続中"
+        );
+    }
+
+    /// 一時ディレクトリに保存し、既定の設定で動くアプリ
+    fn test_app(data_dir: &std::path::Path) -> Result<ChatApp> {
+        test_app_with_config(data_dir, crate::config::Config::default())
+    }
+
+    fn test_app_with_config(data_dir: &std::path::Path, config: crate::config::Config) -> Result<ChatApp> {
+        let scheduler = Scheduler::from_parts(
+            std::sync::Arc::new(crate::llm::MockLLMClient::new()),
+            crate::storage::Storage::with_data_dir(data_dir.to_path_buf())?,
+            config,
+            true,
+        )?;
+        Ok(ChatApp::new(scheduler))
+    }
+
+    #[test]
+    fn test_agenda_is_added_after_welcome() -> Result<()> {
+        let data_dir = TempDir::new();
+        let mut app = test_app(&data_dir)?;
+
+        app.push_agenda(Some(vec![
+            "1. 📝 朝会 🕐 09:00-09:30".to_string(),
+            "2. 📝 設計レビュー 🕐 14:00-15:00".to_string(),
+        ]));
+        assert_eq!(app.messages.len(), 2);
+        assert!(app.messages[1].role == MessageRole::System);
+        assert_eq!(
+            app.messages[1].content,
+            "📅 今日の予定:\n  1. 📝 朝会 🕐 09:00-09:30\n  2. 📝 設計レビュー 🕐 14:00-15:00"
+        );
+
+        app.push_agenda(Some(Vec::new()));
+        assert!(app.messages[2].content.contains("今日の予定はありません"));
+
+        // 未連携・取得失敗の場合は予定の欄を出さない
+        app.push_agenda(None);
+        assert_eq!(app.messages.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_startup_agenda_follows_config() -> Result<()> {
+        let data_dir = TempDir::new();
+        let mut app = test_app(&data_dir)?;
+        app.show_startup_agenda().await;
+        assert_eq!(app.messages.len(), 1);
+
+        // 有効でもカレンダー未連携なら予定を取得できないので何も足さない
+        let mut config = crate::config::Config::default();
+        config.app.show_agenda_on_start = Some(true);
+        let mut app = test_app_with_config(&data_dir, config)?;
+        assert!(app.scheduler.show_agenda_on_start());
+        app.show_startup_agenda().await;
+        assert_eq!(app.messages.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_is_saved_when_app_is_dropped_mid_session() -> Result<()> {
+        let data_dir = TempDir::new();
+        let storage = crate::storage::Storage::with_data_dir(data_dir.to_path_buf())?;
+        let mut app = test_app(&data_dir)?;
+        assert!(!data_dir.join("conversation_history.json").exists());
+
+        // パニックで後始末を通らずに破棄された場合を再現する
+        app.terminal_active = true;
+        app.should_quit = true;
+        drop(app);
+
+        assert!(data_dir.join("conversation_history.json").exists());
+        storage.load_conversation_history()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spinner_frames_cycle_with_elapsed_time() {
+        let at = |millis| spinner_frame(std::time::Duration::from_millis(millis));
+
+        assert_eq!(at(0), SPINNER_FRAMES[0]);
+        assert_eq!(at(99), SPINNER_FRAMES[0]);
+        assert_eq!(at(100), SPINNER_FRAMES[1]);
+        assert_eq!(at(950), SPINNER_FRAMES[9]);
+        // 最後のコマの次は最初に戻る
+        assert_eq!(at(1000), SPINNER_FRAMES[0]);
+        assert_eq!(at(1250), SPINNER_FRAMES[2]);
+
+        assert_eq!(
+            processing_status_text(at(0), "Thinking..."),
+            "⠋ Thinking... お待ちください | Esc/Ctrl+X: キャンセル"
+        );
+    }
+
+    #[test]
+    fn test_cancel_restores_input_and_stops_processing() -> Result<()> {
+        let data_dir = TempDir::new();
+        let mut app = test_app(&data_dir)?;
+        app.messages.push(ChatMessage {
+            role: MessageRole::Assistant,
+            content: "🤔 考え中です...".to_string(),
+            timestamp: chrono::Local::now(),
+            repeat_count: 1,
+        });
+        app.is_processing = true;
+
+        app.cancel_processing("明日の会議を追加".to_string(), app.messages.len() - 1);
+
+        assert!(!app.is_processing);
+        assert_eq!(app.input, "明日の会議を追加");
+        assert_eq!(app.cursor_position, 8);
+        assert_eq!(app.messages.last().unwrap().content, "キャンセルしました");
+
+        assert!(is_cancel_key(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(is_cancel_key(KeyCode::Char('x'), KeyModifiers::CONTROL));
+        assert!(!is_cancel_key(KeyCode::Char('x'), KeyModifiers::NONE));
+
+        let cancelled: anyhow::Error = crate::models::SchedulerError::Cancelled.into();
+        assert!(is_cancelled(&cancelled));
+        assert!(!is_cancelled(&anyhow::anyhow!("通信エラー")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identical_consecutive_responses_are_collapsed() -> Result<()> {
+        let data_dir = TempDir::new();
+        let mut app = test_app(&data_dir)?;
+        let initial = app.messages.len();
+
+        app.handle_user_input("こんにちは".to_string()).await?;
+        app.handle_user_input("こんにちは".to_string()).await?;
+
+        // 2つの入力は残し、同じ内容の応答は最新の1件にまとめる
+        assert_eq!(app.messages.len(), initial + 3);
+        let roles: Vec<bool> = app.messages[initial..].iter().map(|msg| msg.role == MessageRole::User).collect();
+        assert_eq!(roles, [true, true, false]);
+        assert_eq!(app.messages.last().unwrap().repeat_count, 2);
+
+        // 内容が違う応答はそのまま追加する
+        app.handle_user_input("予定の一覧を見せて".to_string()).await?;
+        assert_eq!(app.messages.len(), initial + 5);
+        assert_eq!(app.messages.last().unwrap().repeat_count, 1);
+        assert_eq!(app.messages[initial + 2].repeat_count, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_processing_status_redraws_only_the_status_bar() -> Result<()> {
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(40, 12))?;
+        let screen = terminal
+            .draw(|f| {
+                let [messages_area, _, status_area] = screen_layout(f.size());
+                f.render_widget(Paragraph::new("こんにちは"), messages_area);
+                f.render_widget(Paragraph::new("準備完了"), status_area);
+            })?
+            .buffer
+            .clone();
+
+        draw_processing_status(&mut terminal, &screen, "⠋ 考え中")?;
+
+        let status_row = screen_layout(screen.area)[2].y;
+        let drawn = terminal.backend().buffer().clone();
+        for y in 0..screen.area.height {
+            // ワイド文字の後ろのセルは空白になるため、空白を除いて比べる
+            let row = |buffer: &Buffer| {
+                (0..buffer.area.width).map(|x| buffer.get(x, y).symbol().to_string()).collect::<String>().replace(' ', "")
+            };
+            if y == status_row {
+                assert!(row(&drawn).contains("考え中"));
+                assert!(!row(&drawn).contains("準備完了"));
+            } else {
+                assert_eq!(row(&drawn), row(&screen));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_very_long_single_line_is_truncated_quickly() -> Result<()> {
+        let data_dir = TempDir::new();
+        let app = test_app(&data_dir)?;
+        let content = "予".repeat(50 * 1024 / 3);
+        assert!(content.len() >= 50 * 1000);
+
+        let started = std::time::Instant::now();
+        let wrapped = app.wrap_message_content(&content, 80);
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        assert!(wrapped.ends_with('…'));
+        let width: usize = wrapped.lines().map(|line| app.calculate_display_width(line)).sum();
+        assert!(width <= MAX_LINE_WIDTH + 1);
+        assert!(wrapped.lines().all(|line| app.calculate_display_width(line) <= 80));
+
+        // 上限以内の行はそのまま折り返す
+        assert_eq!(app.wrap_message_content(&"a".repeat(25), 10), "aaaaaaaaaa\naaaaaaaaaa\naaaaa");
+
+        // 再描画では同じ内容・幅の結果を使い回し、幅が変われば折り返し直す
+        assert_eq!(app.cached_wrap(&content, 80), wrapped);
+        assert_eq!(app.cached_wrap(&content, 80), wrapped);
+        assert_eq!(app.wrap_cache.borrow().len(), 1);
+        app.cached_wrap(&content, 40);
+        assert_eq!(app.wrap_cache.borrow().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_word_from_end() {
+        let mut input = "hello world".to_string();
+        let cursor = delete_word_before(&mut input, 11);
+
+        assert_eq!(input, "hello ");
+        assert_eq!(cursor, 6);
+    }
+
+    #[test]
+    fn test_delete_word_skips_trailing_spaces_and_keeps_rest() {
+        let mut input = "明日 会議  を追加".to_string();
+        let cursor = delete_word_before(&mut input, 7);
+
+        assert_eq!(input, "明日 を追加");
+        assert_eq!(cursor, 3);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::calendar::format_event_time_range;
+use crate::config::AppConfig;
+use anyhow::Result;
+use google_calendar3::api::Event;
+use schedule_ai_agent::output::status;
+use serde_json::{json, Value};
+
+/// Webhookへの通知を待つ時間の上限
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// 作成した予定の概要（SlackのIncoming Webhookでもそのまま表示できるよう text を含める）
+pub fn created_event_payload(event: &Event, app_config: &AppConfig) -> Value {
+    let title = event.summary.as_deref().unwrap_or("(タイトルなし)");
+    let time = format_event_time_range(event, app_config);
+    let location = event.location.as_deref().filter(|location| !location.is_empty());
+
+    let mut text = format!("📅 予定が作成されました: {} ({})", title, time);
+    if let Some(location) = location {
+        text.push_str(&format!(" @{}", location));
+    }
+
+    json!({
+        "event": "created",
+        "title": title,
+        "time": time,
+        "start": event.start.as_ref().and_then(|start| start.date_time).map(|dt| dt.to_rfc3339()),
+        "end": event.end.as_ref().and_then(|end| end.date_time).map(|dt| dt.to_rfc3339()),
+        "location": location,
+        "text": text,
+    })
+}
+
+/// 作成した予定を`url`へ通知する（URL未設定なら何もしない）
+///
+/// 通知に失敗しても予定の作成は成功のままとし、警告を表示するだけにする。
+pub async fn notify_event_created(url: Option<&str>, event: &Event, app_config: &AppConfig) {
+    let Some(url) = url.filter(|url| !url.is_empty()) else {
+        return;
+    };
+    if let Err(e) = post_json(url, &created_event_payload(event, app_config)).await {
+        status(&format!("⚠️ Webhookへの通知に失敗しました（予定は作成済みです）: {}", e));
+    }
+}
+
+async fn post_json(url: &str, payload: &Value) -> Result<()> {
+    reqwest::Client::new()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(payload)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use google_calendar3::api::EventDateTime;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_webhook_receives_created_event_summary() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(body_partial_json(json!({
+                "event": "created",
+                "title": "設計レビュー",
+                "time": "07/01 10:00-11:00",
+                "location": "会議室A",
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let start = Utc.with_ymd_and_hms(2025, 7, 1, 1, 0, 0).unwrap();
+        let event = Event {
+            summary: Some("設計レビュー".to_string()),
+            location: Some("会議室A".to_string()),
+            start: Some(EventDateTime { date_time: Some(start), ..Default::default() }),
+            end: Some(EventDateTime { date_time: Some(start + chrono::Duration::hours(1)), ..Default::default() }),
+            ..Default::default()
+        };
+        let app_config = crate::config::Config::default().app;
+
+        notify_event_created(Some(&format!("{}/hook", server.uri())), &event, &app_config).await;
+        server.verify().await;
+
+        // 通知先に届かなくてもパニックやエラーにはならない
+        notify_event_created(Some("http://127.0.0.1:9/unreachable"), &event, &app_config).await;
+    }
+}